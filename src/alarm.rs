@@ -0,0 +1,937 @@
+//! Timers that wake the system from suspend
+use core::fmt;
+use core::time::Duration;
+use std::error::Error;
+use std::io;
+use std::sync::{Arc, Weak};
+use std::time::SystemTime;
+
+use crate::timer_handle::TimerControl;
+use crate::{Deadline, Instant, TimerHandle};
+
+/// A timer that wakes the system from suspend when its deadline passes.
+///
+/// Ordinary timers, including [`sleep_until`](crate::sleep_until), fire
+/// after resume if their deadline passed while the system was suspended. A
+/// `WakeAlarm` resumes the system instead, so periodic jobs of a daemon run on
+/// time on laptops and phones.
+///
+/// | Platform       | Timer                                   | Requirement                           |
+/// |----------------|-----------------------------------------|---------------------------------------|
+/// | Linux, Android | timerfd on `CLOCK_BOOTTIME_ALARM`       | `CAP_WAKE_ALARM`                      |
+/// | Android apps   | `AlarmManager` through JNI              | `android-alarm`, `android::AlarmManager` installed |
+/// | Windows        | waitable timer with `fResume`           | wake timers allowed by the power plan |
+/// | macOS          | `IOPMSchedulePowerEvent` wake event     | root, outside the App Sandbox         |
+///
+/// Elsewhere creating the alarm fails with [`io::ErrorKind::Unsupported`].
+/// On Windows setting it fails with `ERROR_NOT_SUPPORTED` if the system
+/// can't be woken by timers.
+///
+/// On macOS the wake event is scheduled in wall-clock time with a resolution
+/// of a second and is listed by `pmset -g sched`. Scheduling it fails with
+/// [`io::ErrorKind::PermissionDenied`] unless the process runs as root. The
+/// App Sandbox doesn't allow it at all, so Mac App Store apps can't use wake
+/// alarms. Waiting notices the deadline at most a second late.
+///
+/// Applications that should keep working without wake alarms, e.g. in
+/// containers without `CAP_WAKE_ALARM`, can create the alarm with
+/// [`WakeAlarm::new_or_fallback`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use boot_time::{Duration, Instant, WakeAlarm};
+///
+/// let alarm = WakeAlarm::new()?;
+/// alarm.set(Instant::now() + Duration::from_secs(3600))?;
+/// // the system may suspend in the meantime
+/// alarm.wait()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct WakeAlarm {
+    inner: Arc<imp::Alarm>,
+    warning: Option<WakeAlarmWarning>,
+}
+
+impl WakeAlarm {
+    /// Creates a disarmed alarm.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the platform has no wake alarms, the process lacks
+    /// the privilege to use them, or the system has no wake-capable real-time
+    /// clock.
+    pub fn new() -> io::Result<WakeAlarm> {
+        Ok(WakeAlarm {
+            inner: Arc::new(imp::Alarm::new(true)?),
+            warning: None,
+        })
+    }
+
+    /// Creates a disarmed alarm, or a timer on the same clock that doesn't
+    /// wake the system if wake alarms are unavailable.
+    ///
+    /// The reason for the downgrade is returned by [`WakeAlarm::warning`].
+    /// Linux, Android, Windows and macOS have such timers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the platform has no timers to fall back to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::WakeAlarm;
+    ///
+    /// # #[cfg(any(
+    /// #     all(
+    /// #         any(target_os = "linux", target_os = "android"),
+    /// #         any(not(feature = "linux-raw"), feature = "linux-libc"),
+    /// #     ),
+    /// #     windows,
+    /// #     target_os = "macos",
+    /// # ))]
+    /// # {
+    /// let alarm = WakeAlarm::new_or_fallback()?;
+    /// if let Some(warning) = alarm.warning() {
+    ///     eprintln!("deadlines passing during suspend will be late: {warning}");
+    /// }
+    /// # }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn new_or_fallback() -> io::Result<WakeAlarm> {
+        match WakeAlarm::new() {
+            Ok(alarm) => Ok(alarm),
+            Err(e) => Ok(WakeAlarm {
+                inner: Arc::new(imp::Alarm::new(false)?),
+                warning: Some(WakeAlarmWarning::new(e)),
+            }),
+        }
+    }
+
+    /// Returns `true` if wake alarms can be created, i.e. the platform
+    /// supports them and the process has the privilege to use them.
+    ///
+    /// On Linux and Android it probes for `CAP_WAKE_ALARM` and an alarm
+    /// capable real-time clock by creating a timer. On Windows the power
+    /// plan may still disable wake timers.
+    #[must_use]
+    pub fn is_supported() -> bool {
+        imp::Alarm::new(true).is_ok()
+    }
+
+    /// Returns why the alarm doesn't wake the system, if it was downgraded
+    /// by [`WakeAlarm::new_or_fallback`].
+    #[must_use]
+    pub fn warning(&self) -> Option<&WakeAlarmWarning> {
+        self.warning.as_ref()
+    }
+
+    /// Arms the alarm to fire at `deadline`, replacing the previous deadline.
+    ///
+    /// A deadline in the past fires immediately.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the OS rejects the deadline.
+    pub fn set(&self, deadline: impl Into<Deadline>) -> io::Result<()> {
+        self.set_with_slack(deadline, Duration::ZERO)
+    }
+
+    /// Arms the alarm to fire at `deadline`, or up to `slack` later,
+    /// replacing the previous deadline.
+    ///
+    /// The slack lets the OS coalesce the wakeup with others to save power.
+    /// Only Windows supports it, as the tolerable delay of the timer, in
+    /// milliseconds. Elsewhere it is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the OS rejects the deadline.
+    pub fn set_with_slack(&self, deadline: impl Into<Deadline>, slack: Duration) -> io::Result<()> {
+        self.inner.set(deadline.into().instant(), slack)
+    }
+
+    /// Disarms the alarm. A thread blocked in [`WakeAlarm::wait`] keeps
+    /// waiting for the next deadline.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the OS fails to cancel the timer.
+    pub fn cancel(&self) -> io::Result<()> {
+        self.inner.cancel()
+    }
+
+    /// Returns `true` if the alarm is set and its deadline hasn't passed.
+    #[must_use]
+    pub fn is_armed(&self) -> bool {
+        self.inner.is_armed()
+    }
+
+    /// Returns a handle to cancel or re-arm the alarm from other threads,
+    /// e.g. while one blocks in [`WakeAlarm::wait`].
+    ///
+    /// The handle does nothing once the alarm is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use boot_time::{Duration, Instant, WakeAlarm};
+    ///
+    /// let alarm = WakeAlarm::new()?;
+    /// let handle = alarm.handle();
+    /// std::thread::spawn(move || {
+    ///     handle.reset(Instant::now() + Duration::from_secs(60));
+    /// });
+    /// alarm.wait()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[must_use]
+    pub fn handle(&self) -> TimerHandle {
+        TimerHandle::new(AlarmControl(Arc::downgrade(&self.inner)))
+    }
+
+    /// Blocks until the alarm fires.
+    ///
+    /// A disarmed alarm blocks forever.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if waiting fails.
+    pub fn wait(&self) -> io::Result<()> {
+        self.inner.wait()
+    }
+}
+
+/// Blocks until the system clock reaches `time`, waking the system from
+/// suspend for it, e.g. to run a backup at 03:00 even if the laptop is
+/// asleep by then.
+///
+/// Unlike a [`WakeAlarm`], whose deadline is a span of the boot clock, the
+/// timer follows the wall clock: it fires at `time` even if the system clock
+/// is stepped in the meantime, e.g. by NTP.
+///
+/// | Platform       | Timer                                                 | Requirement                           |
+/// |----------------|-------------------------------------------------------|---------------------------------------|
+/// | Linux, Android | timerfd on `CLOCK_REALTIME_ALARM`                     | `CAP_WAKE_ALARM`                      |
+/// | Windows        | waitable timer with an absolute due time and `fResume` | wake timers allowed by the power plan |
+/// | macOS          | `IOPMSchedulePowerEvent` wake event                   | root, outside the App Sandbox         |
+///
+/// A time in the past returns immediately.
+///
+/// # Errors
+///
+/// Returns an error if the platform has no wake alarms or the process lacks
+/// the privilege to use them, with [`io::ErrorKind::Unsupported`] and
+/// [`io::ErrorKind::PermissionDenied`] like [`WakeAlarm::new`].
+///
+/// On Linux and Android the timer is cancelled when the system clock is set,
+/// with `TFD_TIMER_CANCEL_ON_SET`, and the sleep fails with
+/// [`io::ErrorKind::Interrupted`], so the caller can check whether `time` is
+/// still the one it wants and sleep again.
+///
+/// # Examples
+///
+/// ```no_run
+/// use boot_time::Duration;
+/// use std::io;
+/// use std::time::SystemTime;
+///
+/// let at = SystemTime::now() + Duration::from_secs(8 * 3600);
+/// loop {
+///     match boot_time::sleep_until_wall(at) {
+///         Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+///         r => break r,
+///     }
+/// }?;
+/// // run the backup
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn sleep_until_wall(time: SystemTime) -> io::Result<()> {
+    imp::sleep_until_wall(time)
+}
+
+struct AlarmControl(Weak<imp::Alarm>);
+
+impl TimerControl for AlarmControl {
+    fn cancel(&self) -> bool {
+        self.0
+            .upgrade()
+            .is_some_and(|alarm| alarm.is_armed() && alarm.cancel().is_ok())
+    }
+
+    fn reset(&self, deadline: Instant) -> bool {
+        self.0
+            .upgrade()
+            .is_some_and(|alarm| alarm.set(deadline, Duration::ZERO).is_ok())
+    }
+
+    fn is_armed(&self) -> bool {
+        self.0.upgrade().is_some_and(|alarm| alarm.is_armed())
+    }
+}
+
+/// Why [`WakeAlarm::new_or_fallback`] created a timer that doesn't wake the
+/// system.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WakeAlarmWarning {
+    /// The process lacks the privilege to wake the system, e.g.
+    /// `CAP_WAKE_ALARM` on Linux or root on macOS.
+    PermissionDenied(io::Error),
+    /// The system has no wake alarms, e.g. because the kernel lacks alarm
+    /// timers or the real-time clock can't wake the system.
+    Unsupported(io::Error),
+}
+
+impl WakeAlarmWarning {
+    fn new(e: io::Error) -> WakeAlarmWarning {
+        if e.kind() == io::ErrorKind::PermissionDenied {
+            WakeAlarmWarning::PermissionDenied(e)
+        } else {
+            WakeAlarmWarning::Unsupported(e)
+        }
+    }
+}
+
+impl fmt::Display for WakeAlarmWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WakeAlarmWarning::PermissionDenied(e) => {
+                write!(f, "not permitted to wake the system: {e}")
+            }
+            WakeAlarmWarning::Unsupported(e) => write!(f, "wake alarms are unavailable: {e}"),
+        }
+    }
+}
+
+impl Error for WakeAlarmWarning {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WakeAlarmWarning::PermissionDenied(e) | WakeAlarmWarning::Unsupported(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    any(not(feature = "linux-raw"), feature = "linux-libc")
+))]
+impl std::os::fd::AsFd for WakeAlarm {
+    fn as_fd(&self) -> std::os::fd::BorrowedFd<'_> {
+        self.inner.fd.as_fd()
+    }
+}
+
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    any(not(feature = "linux-raw"), feature = "linux-libc")
+))]
+impl std::os::fd::AsRawFd for WakeAlarm {
+    fn as_raw_fd(&self) -> std::os::fd::RawFd {
+        self.inner.fd.as_raw_fd()
+    }
+}
+
+#[cfg(windows)]
+impl std::os::windows::io::AsHandle for WakeAlarm {
+    fn as_handle(&self) -> std::os::windows::io::BorrowedHandle<'_> {
+        self.inner.handle.as_handle()
+    }
+}
+
+#[cfg(windows)]
+impl std::os::windows::io::AsRawHandle for WakeAlarm {
+    fn as_raw_handle(&self) -> std::os::windows::io::RawHandle {
+        self.inner.handle.as_raw_handle()
+    }
+}
+
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    any(not(feature = "linux-raw"), feature = "linux-libc")
+))]
+mod imp {
+    use core::time::Duration;
+    use std::io;
+
+    use std::mem;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::ptr;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use crate::timerfd::duration_timespec;
+    use crate::{Instant, TimerClock, TimerFd};
+
+    #[derive(Debug)]
+    pub(super) struct Alarm {
+        pub(super) fd: TimerFd,
+        // Wakes the device if the app may not use alarm timers
+        #[cfg(all(target_os = "android", feature = "android-alarm"))]
+        bridge: Option<crate::android::PendingAlarm>,
+    }
+
+    impl Alarm {
+        // Creating the timer fails with EPERM without `CAP_WAKE_ALARM`, and
+        // with EINVAL without alarm timers
+        pub(super) fn new(wake: bool) -> io::Result<Alarm> {
+            let clock = if wake {
+                TimerClock::BootAlarm
+            } else {
+                TimerClock::Boot
+            };
+            match TimerFd::new(clock) {
+                Ok(fd) => Ok(Alarm {
+                    fd,
+                    #[cfg(all(target_os = "android", feature = "android-alarm"))]
+                    bridge: None,
+                }),
+                #[cfg(all(target_os = "android", feature = "android-alarm"))]
+                Err(e) if wake => match crate::android::PendingAlarm::new() {
+                    Some(bridge) => Ok(Alarm {
+                        fd: TimerFd::new(TimerClock::Boot)?,
+                        bridge: Some(bridge?),
+                    }),
+                    None => Err(e),
+                },
+                Err(e) => Err(e),
+            }
+        }
+
+        // Alarm timers have no slack
+        pub(super) fn set(&self, deadline: Instant, _slack: Duration) -> io::Result<()> {
+            self.fd.set_deadline(deadline)?;
+            #[cfg(all(target_os = "android", feature = "android-alarm"))]
+            if let Some(bridge) = &self.bridge {
+                bridge.set(deadline)?;
+            }
+            Ok(())
+        }
+
+        pub(super) fn cancel(&self) -> io::Result<()> {
+            self.fd.disarm()?;
+            #[cfg(all(target_os = "android", feature = "android-alarm"))]
+            if let Some(bridge) = &self.bridge {
+                bridge.cancel()?;
+            }
+            Ok(())
+        }
+
+        pub(super) fn is_armed(&self) -> bool {
+            self.fd.is_armed()
+        }
+
+        pub(super) fn wait(&self) -> io::Result<()> {
+            self.fd.read_expirations().map(drop)
+        }
+    }
+
+    // `TimerFd` measures with clocks of the `Instant` timeline, so the
+    // real-time timer is created directly
+    pub(super) fn sleep_until_wall(time: SystemTime) -> io::Result<()> {
+        let fd = unsafe { libc::timerfd_create(libc::CLOCK_REALTIME_ALARM, libc::TFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        // A zero value would disarm the timer, so times up to the epoch are
+        // rounded up to a nanosecond past it, which is due
+        let since_epoch = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .max(Duration::from_nanos(1));
+        let mut spec: libc::itimerspec = unsafe { mem::zeroed() };
+        spec.it_value = duration_timespec(since_epoch);
+        let flags = libc::TFD_TIMER_ABSTIME | libc::TFD_TIMER_CANCEL_ON_SET;
+        if unsafe { libc::timerfd_settime(fd.as_raw_fd(), flags, &spec, ptr::null_mut()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut expirations = 0u64;
+        loop {
+            let r = unsafe {
+                libc::read(
+                    fd.as_raw_fd(),
+                    (&mut expirations as *mut u64).cast(),
+                    mem::size_of::<u64>(),
+                )
+            };
+            if r >= 0 {
+                return Ok(());
+            }
+            let e = io::Error::last_os_error();
+            match e.raw_os_error() {
+                Some(libc::EINTR) => {}
+                Some(libc::ECANCELED) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Interrupted,
+                        "the system clock was set",
+                    ))
+                }
+                _ => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use core::time::Duration;
+    use std::ffi::c_void;
+    use std::io;
+    use std::os::windows::io::{AsRawHandle, FromRawHandle, OwnedHandle};
+    use std::ptr;
+    use std::sync::{Mutex, MutexGuard};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use crate::Instant;
+
+    #[allow(clippy::upper_case_acronyms)]
+    type HANDLE = *mut c_void;
+    #[allow(clippy::upper_case_acronyms)]
+    type BOOL = i32;
+
+    const CREATE_WAITABLE_TIMER_HIGH_RESOLUTION: u32 = 0x2;
+    const TIMER_ALL_ACCESS: u32 = 0x1F_0003;
+    const INFINITE: u32 = u32::MAX;
+    const WAIT_OBJECT_0: u32 = 0;
+    const WAIT_FAILED: u32 = u32::MAX;
+    const ERROR_NOT_SUPPORTED: i32 = 50;
+    const POWER_REQUEST_CONTEXT_VERSION: u32 = 0;
+    const POWER_REQUEST_CONTEXT_SIMPLE_STRING: u32 = 0x1;
+    // 100 ns intervals from 1601-01-01, the `FILETIME` epoch, to the Unix
+    // epoch
+    const FILETIME_UNIX_EPOCH: i64 = 116_444_736_000_000_000;
+
+    type TimerApcRoutine = unsafe extern "system" fn(*mut c_void, u32, u32);
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn CreateWaitableTimerExW(
+            lpTimerAttributes: *mut c_void,
+            lpTimerName: *const u16,
+            dwFlags: u32,
+            dwDesiredAccess: u32,
+        ) -> HANDLE;
+        fn SetWaitableTimer(
+            hTimer: HANDLE,
+            lpDueTime: *const i64,
+            lPeriod: i32,
+            pfnCompletionRoutine: Option<TimerApcRoutine>,
+            lpArgToCompletionRoutine: *mut c_void,
+            fResume: BOOL,
+        ) -> BOOL;
+        fn SetWaitableTimerEx(
+            hTimer: HANDLE,
+            lpDueTime: *const i64,
+            lPeriod: i32,
+            pfnCompletionRoutine: Option<TimerApcRoutine>,
+            lpArgToCompletionRoutine: *mut c_void,
+            WakeContext: *const ReasonContext,
+            TolerableDelay: u32,
+        ) -> BOOL;
+        fn CancelWaitableTimer(hTimer: HANDLE) -> BOOL;
+        fn WaitForSingleObject(hHandle: HANDLE, dwMilliseconds: u32) -> u32;
+        fn GetLastError() -> u32;
+        fn SetLastError(dwErrCode: u32);
+    }
+
+    // `REASON_CONTEXT` with a simple reason string
+    #[repr(C)]
+    struct ReasonContext {
+        version: u32,
+        flags: u32,
+        simple_reason_string: *const u16,
+    }
+
+    #[derive(Debug)]
+    pub(super) struct Alarm {
+        pub(super) handle: OwnedHandle,
+        resume: bool,
+        // Waitable timers can't be queried without resetting them
+        deadline: Mutex<Option<Instant>>,
+    }
+
+    impl Alarm {
+        // High-resolution timers exist since Windows 10 1803, older versions
+        // get a timer with the default resolution of the system tick
+        pub(super) fn new(resume: bool) -> io::Result<Alarm> {
+            let mut handle = ptr::null_mut();
+            for flags in [CREATE_WAITABLE_TIMER_HIGH_RESOLUTION, 0] {
+                handle = unsafe {
+                    CreateWaitableTimerExW(ptr::null_mut(), ptr::null(), flags, TIMER_ALL_ACCESS)
+                };
+                if !handle.is_null() {
+                    break;
+                }
+            }
+            if handle.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Alarm {
+                handle: unsafe { OwnedHandle::from_raw_handle(handle) },
+                resume,
+                deadline: Mutex::new(None),
+            })
+        }
+
+        // Relative due times are measured in interrupt time, which counts
+        // the time the system sleeps, like `Instant`. They are negative, in
+        // 100 ns units. A tolerable delay requires `SetWaitableTimerEx`,
+        // which resumes the system when given a wake context.
+        pub(super) fn set(&self, deadline: Instant, slack: Duration) -> io::Result<()> {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let ticks = i64::try_from(timeout.as_nanos().div_ceil(100)).unwrap_or(i64::MAX);
+            let due = -ticks.max(1);
+            let handle = self.handle.as_raw_handle();
+            let mut armed = self.deadline();
+            // The calls don't reset the last error on success
+            unsafe { SetLastError(0) };
+            let ok = if slack.is_zero() {
+                let resume = BOOL::from(self.resume);
+                unsafe { SetWaitableTimer(handle, &due, 0, None, ptr::null_mut(), resume) }
+            } else {
+                let reason: Vec<u16> = "boot-time wake alarm\0".encode_utf16().collect();
+                let context = ReasonContext {
+                    version: POWER_REQUEST_CONTEXT_VERSION,
+                    flags: POWER_REQUEST_CONTEXT_SIMPLE_STRING,
+                    simple_reason_string: reason.as_ptr(),
+                };
+                let context: *const ReasonContext =
+                    if self.resume { &context } else { ptr::null() };
+                let delay = u32::try_from(slack.as_millis()).unwrap_or(u32::MAX);
+                unsafe {
+                    SetWaitableTimerEx(handle, &due, 0, None, ptr::null_mut(), context, delay)
+                }
+            };
+            if ok == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            // The timer is set even if the system can't be resumed by it
+            if self.resume && unsafe { GetLastError() } == ERROR_NOT_SUPPORTED as u32 {
+                unsafe { CancelWaitableTimer(handle) };
+                *armed = None;
+                return Err(io::Error::from_raw_os_error(ERROR_NOT_SUPPORTED));
+            }
+            *armed = Some(deadline);
+            Ok(())
+        }
+
+        pub(super) fn cancel(&self) -> io::Result<()> {
+            let mut armed = self.deadline();
+            if unsafe { CancelWaitableTimer(self.handle.as_raw_handle()) } == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            *armed = None;
+            Ok(())
+        }
+
+        pub(super) fn is_armed(&self) -> bool {
+            self.deadline()
+                .is_some_and(|deadline| Instant::now() < deadline)
+        }
+
+        fn deadline(&self) -> MutexGuard<'_, Option<Instant>> {
+            self.deadline.lock().unwrap_or_else(|e| e.into_inner())
+        }
+
+        pub(super) fn wait(&self) -> io::Result<()> {
+            match unsafe { WaitForSingleObject(self.handle.as_raw_handle(), INFINITE) } {
+                WAIT_OBJECT_0 => Ok(()),
+                WAIT_FAILED => Err(io::Error::last_os_error()),
+                r => Err(io::Error::other(format!("unexpected wait result {r}"))),
+            }
+        }
+
+        // Positive due times are absolute `FILETIME`s in system time, which
+        // the timer keeps following when the clock is set
+        fn set_wall(&self, time: SystemTime) -> io::Result<()> {
+            let due = match time.duration_since(UNIX_EPOCH) {
+                Ok(d) => i64::try_from(d.as_nanos() / 100)
+                    .unwrap_or(i64::MAX)
+                    .saturating_add(FILETIME_UNIX_EPOCH),
+                Err(e) => {
+                    let before = i64::try_from(e.duration().as_nanos() / 100).unwrap_or(i64::MAX);
+                    FILETIME_UNIX_EPOCH.saturating_sub(before).max(1)
+                }
+            };
+            let handle = self.handle.as_raw_handle();
+            let resume = BOOL::from(self.resume);
+            unsafe { SetLastError(0) };
+            if unsafe { SetWaitableTimer(handle, &due, 0, None, ptr::null_mut(), resume) } == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if self.resume && unsafe { GetLastError() } == ERROR_NOT_SUPPORTED as u32 {
+                unsafe { CancelWaitableTimer(handle) };
+                return Err(io::Error::from_raw_os_error(ERROR_NOT_SUPPORTED));
+            }
+            Ok(())
+        }
+    }
+
+    pub(super) fn sleep_until_wall(time: SystemTime) -> io::Result<()> {
+        let alarm = Alarm::new(true)?;
+        alarm.set_wall(time)?;
+        alarm.wait()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use core::time::Duration;
+    use std::ffi::{c_char, c_void};
+    use std::io;
+    use std::ptr;
+    use std::sync::{Condvar, Mutex, MutexGuard};
+    use std::thread;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use crate::Instant;
+
+    type CFStringRef = *const c_void;
+    type CFDateRef = *const c_void;
+    type CFAllocatorRef = *const c_void;
+    type CFAbsoluteTime = f64;
+
+    const CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+    // Seconds from the Unix epoch to the CoreFoundation epoch, 2001-01-01
+    const CF_ABSOLUTE_TIME_INTERVAL_SINCE_1970: f64 = 978_307_200.0;
+    const IO_RETURN_NOT_PRIVILEGED: i32 = 0xe000_02c1_u32 as i32;
+    // Waiting re-checks the clock this often, since condition variables
+    // measure with a clock that stops during sleep
+    const MAX_CHUNK: Duration = Duration::from_secs(1);
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOPMSchedulePowerEvent(
+            time_to_wake: CFDateRef,
+            my_id: CFStringRef,
+            event_type: CFStringRef,
+        ) -> i32;
+        fn IOPMCancelScheduledPowerEvent(
+            time_to_wake: CFDateRef,
+            my_id: CFStringRef,
+            event_type: CFStringRef,
+        ) -> i32;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFDateCreate(alloc: CFAllocatorRef, at: CFAbsoluteTime) -> CFDateRef;
+        fn CFStringCreateWithCString(
+            alloc: CFAllocatorRef,
+            c_str: *const c_char,
+            encoding: u32,
+        ) -> CFStringRef;
+        fn CFRelease(cf: *const c_void);
+    }
+
+    #[derive(Debug, Default)]
+    struct State {
+        deadline: Option<Instant>,
+        // The wall-clock time of the scheduled wake event
+        scheduled: Option<CFAbsoluteTime>,
+    }
+
+    #[derive(Debug)]
+    pub(super) struct Alarm {
+        state: Mutex<State>,
+        changed: Condvar,
+        wake: bool,
+    }
+
+    impl Alarm {
+        // Only root may schedule wake events
+        pub(super) fn new(wake: bool) -> io::Result<Alarm> {
+            if wake && unsafe { libc::geteuid() } != 0 {
+                return Err(not_privileged());
+            }
+            Ok(Alarm {
+                state: Mutex::default(),
+                changed: Condvar::new(),
+                wake,
+            })
+        }
+
+        // Wake events are scheduled in wall-clock time, so the deadline is
+        // converted through the current offset between the clocks
+        pub(super) fn set(&self, deadline: Instant, _slack: Duration) -> io::Result<()> {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let unix = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .saturating_add(timeout);
+            let at = unix.as_secs_f64() - CF_ABSOLUTE_TIME_INTERVAL_SINCE_1970;
+            let mut state = self.lock();
+            if let Some(previous) = state.scheduled.take() {
+                let _ = power_event(IOPMCancelScheduledPowerEvent, previous);
+            }
+            state.deadline = None;
+            if self.wake {
+                power_event(IOPMSchedulePowerEvent, at)?;
+                state.scheduled = Some(at);
+            }
+            state.deadline = Some(deadline);
+            self.changed.notify_all();
+            Ok(())
+        }
+
+        pub(super) fn cancel(&self) -> io::Result<()> {
+            let mut state = self.lock();
+            state.deadline = None;
+            match state.scheduled.take() {
+                Some(at) => power_event(IOPMCancelScheduledPowerEvent, at),
+                None => Ok(()),
+            }
+        }
+
+        pub(super) fn is_armed(&self) -> bool {
+            self.lock()
+                .deadline
+                .is_some_and(|deadline| Instant::now() < deadline)
+        }
+
+        pub(super) fn wait(&self) -> io::Result<()> {
+            let mut state = self.lock();
+            loop {
+                let Some(deadline) = state.deadline else {
+                    state = self.changed.wait(state).unwrap_or_else(|e| e.into_inner());
+                    continue;
+                };
+                let now = Instant::now();
+                if now >= deadline {
+                    state.deadline = None;
+                    state.scheduled = None;
+                    return Ok(());
+                }
+                let timeout = (deadline - now).min(MAX_CHUNK);
+                state = self
+                    .changed
+                    .wait_timeout(state, timeout)
+                    .unwrap_or_else(|e| e.into_inner())
+                    .0;
+            }
+        }
+
+        fn lock(&self) -> MutexGuard<'_, State> {
+            self.state.lock().unwrap_or_else(|e| e.into_inner())
+        }
+    }
+
+    impl Drop for Alarm {
+        fn drop(&mut self) {
+            if let Some(at) = self.lock().scheduled {
+                let _ = power_event(IOPMCancelScheduledPowerEvent, at);
+            }
+        }
+    }
+
+    fn not_privileged() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "scheduling wake events requires root",
+        )
+    }
+
+    type PowerEventFn = unsafe extern "C" fn(CFDateRef, CFStringRef, CFStringRef) -> i32;
+
+    // Schedules or cancels a `kIOPMAutoWake` event of this crate at `at`
+    fn power_event(f: PowerEventFn, at: CFAbsoluteTime) -> io::Result<()> {
+        unsafe {
+            let date = CFDateCreate(ptr::null(), at);
+            let id = CFStringCreateWithCString(
+                ptr::null(),
+                c"boot-time".as_ptr(),
+                CF_STRING_ENCODING_UTF8,
+            );
+            let kind =
+                CFStringCreateWithCString(ptr::null(), c"wake".as_ptr(), CF_STRING_ENCODING_UTF8);
+            let r = if date.is_null() || id.is_null() || kind.is_null() {
+                Err(io::Error::from(io::ErrorKind::OutOfMemory))
+            } else {
+                match f(date, id, kind) {
+                    0 => Ok(()),
+                    IO_RETURN_NOT_PRIVILEGED => Err(not_privileged()),
+                    r => Err(io::Error::other(format!(
+                        "IOPMSchedulePowerEvent failed: {r:#x}"
+                    ))),
+                }
+            };
+            for cf in [date, id, kind] {
+                if !cf.is_null() {
+                    CFRelease(cf);
+                }
+            }
+            r
+        }
+    }
+
+    // The wake event is removed once it fires. The sleeping thread re-checks
+    // the wall clock every chunk, since sleeps measure with a clock that
+    // stops during sleep.
+    pub(super) fn sleep_until_wall(time: SystemTime) -> io::Result<()> {
+        if unsafe { libc::geteuid() } != 0 {
+            return Err(not_privileged());
+        }
+        if time <= SystemTime::now() {
+            return Ok(());
+        }
+        let unix = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        power_event(
+            IOPMSchedulePowerEvent,
+            unix.as_secs_f64() - CF_ABSOLUTE_TIME_INTERVAL_SINCE_1970,
+        )?;
+        while let Ok(left) = time.duration_since(SystemTime::now()) {
+            thread::sleep(left.min(MAX_CHUNK));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(
+    all(
+        any(target_os = "linux", target_os = "android"),
+        any(not(feature = "linux-raw"), feature = "linux-libc")
+    ),
+    target_os = "macos",
+    windows
+)))]
+mod imp {
+    use core::time::Duration;
+    use std::io;
+    use std::time::SystemTime;
+
+    use crate::Instant;
+
+    #[derive(Debug)]
+    pub(super) enum Alarm {}
+
+    impl Alarm {
+        pub(super) fn new(_wake: bool) -> io::Result<Alarm> {
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "wake alarms are not supported on this platform",
+            ))
+        }
+
+        pub(super) fn set(&self, _deadline: Instant, _slack: Duration) -> io::Result<()> {
+            match *self {}
+        }
+
+        pub(super) fn cancel(&self) -> io::Result<()> {
+            match *self {}
+        }
+
+        pub(super) fn is_armed(&self) -> bool {
+            match *self {}
+        }
+
+        pub(super) fn wait(&self) -> io::Result<()> {
+            match *self {}
+        }
+    }
+
+    pub(super) fn sleep_until_wall(_time: SystemTime) -> io::Result<()> {
+        Alarm::new(true).map(|alarm| match alarm {})
+    }
+}
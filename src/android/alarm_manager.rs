@@ -0,0 +1,219 @@
+//! Wake alarms through `android.app.AlarmManager`
+use core::fmt;
+use core::time::Duration;
+use std::io;
+use std::mem;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::OnceLock;
+
+use jni::objects::{GlobalRef, JClass, JObject, JValue};
+use jni::{JNIEnv, JavaVM};
+
+use crate::Instant;
+
+// `AlarmManager.ELAPSED_REALTIME_WAKEUP`, whose time base is
+// `SystemClock.elapsedRealtime()`, i.e. `CLOCK_BOOTTIME`
+const ELAPSED_REALTIME_WAKEUP: i32 = 2;
+const FLAG_UPDATE_CURRENT: i32 = 0x0800_0000;
+const FLAG_IMMUTABLE: i32 = 0x0400_0000;
+
+static INSTALLED: OnceLock<AlarmManager> = OnceLock::new();
+
+/// A bridge to the `AlarmManager` system service, which wakes the device for
+/// [`WakeAlarm`](crate::WakeAlarm)s in apps.
+///
+/// Apps may not create timers on `CLOCK_BOOTTIME_ALARM`. Once an
+/// `AlarmManager` is [installed](AlarmManager::install), a `WakeAlarm`
+/// that can't create such a timer waits on `CLOCK_BOOTTIME` instead and
+/// schedules an exact alarm with `setExactAndAllowWhileIdle` for its
+/// deadline, which wakes the device even in Doze.
+///
+/// The alarms are delivered as broadcasts to a `BroadcastReceiver` subclass
+/// of the app, which has to be declared in its manifest. The receiver may
+/// do nothing: the waiting thread notices the deadline by itself once the
+/// device is awake. The device may suspend again as soon as `onReceive`
+/// returns, so work after the deadline should take a wake lock.
+///
+/// Since Android 12 the app needs the `SCHEDULE_EXACT_ALARM` or
+/// `USE_EXACT_ALARM` permission, and Doze limits how often an app's alarms
+/// fire to about once per 9 minutes.
+///
+/// # Examples
+///
+/// Installing the bridge from a native method called by the app at startup:
+///
+/// ```no_run
+/// use boot_time::android::AlarmManager;
+/// use jni::objects::{JClass, JObject};
+/// use jni::JNIEnv;
+///
+/// #[no_mangle]
+/// extern "system" fn Java_com_example_App_initAlarms<'local>(
+///     mut env: JNIEnv<'local>,
+///     _class: JClass<'local>,
+///     context: JObject<'local>,
+///     receiver: JClass<'local>,
+/// ) {
+///     if let Ok(manager) = AlarmManager::new(&mut env, &context, &receiver) {
+///         let _ = manager.install();
+///     }
+/// }
+/// ```
+pub struct AlarmManager {
+    vm: JavaVM,
+    context: GlobalRef,
+    receiver: GlobalRef,
+    manager: GlobalRef,
+    // Distinguishes the pending intents of the alarms
+    next_request_code: AtomicI32,
+}
+
+impl AlarmManager {
+    /// Looks up the `AlarmManager` service of `context`, an
+    /// `android.content.Context`, for alarms delivered to `receiver`, a
+    /// `BroadcastReceiver` subclass.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a JNI call fails or throws.
+    pub fn new(
+        env: &mut JNIEnv<'_>,
+        context: &JObject<'_>,
+        receiver: &JClass<'_>,
+    ) -> jni::errors::Result<AlarmManager> {
+        let service = env.new_string("alarm")?;
+        let manager = env
+            .call_method(
+                context,
+                "getSystemService",
+                "(Ljava/lang/String;)Ljava/lang/Object;",
+                &[JValue::Object(&service)],
+            )?
+            .l()?;
+        Ok(AlarmManager {
+            vm: env.get_java_vm()?,
+            context: env.new_global_ref(context)?,
+            receiver: env.new_global_ref(receiver)?,
+            manager: env.new_global_ref(manager)?,
+            next_request_code: AtomicI32::new(0),
+        })
+    }
+
+    /// Makes [`WakeAlarm`](crate::WakeAlarm)s created from now on use the
+    /// bridge when they can't wake the device by themselves.
+    ///
+    /// # Errors
+    ///
+    /// Returns the bridge back if one was installed already.
+    pub fn install(self) -> Result<(), AlarmManager> {
+        INSTALLED.set(self)
+    }
+
+    fn with_env<T, F>(&self, f: F) -> io::Result<T>
+    where
+        F: FnOnce(&mut JNIEnv<'_>) -> jni::errors::Result<T>,
+    {
+        super::with_env(&self.vm, f)
+    }
+}
+
+impl fmt::Debug for AlarmManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AlarmManager").finish_non_exhaustive()
+    }
+}
+
+/// An alarm scheduled through the installed [`AlarmManager`], cancelled on
+/// drop.
+pub(crate) struct PendingAlarm {
+    manager: &'static AlarmManager,
+    intent: GlobalRef,
+}
+
+impl PendingAlarm {
+    // Returns `None` if no `AlarmManager` is installed
+    pub(crate) fn new() -> Option<io::Result<PendingAlarm>> {
+        let manager = INSTALLED.get()?;
+        let code = manager.next_request_code.fetch_add(1, Ordering::Relaxed);
+        Some(manager.with_env(|env| {
+            let intent = env.new_object(
+                "android/content/Intent",
+                "(Landroid/content/Context;Ljava/lang/Class;)V",
+                &[
+                    JValue::Object(manager.context.as_obj()),
+                    JValue::Object(manager.receiver.as_obj()),
+                ],
+            )?;
+            let pending = env
+                .call_static_method(
+                    "android/app/PendingIntent",
+                    "getBroadcast",
+                    "(Landroid/content/Context;ILandroid/content/Intent;I)Landroid/app/PendingIntent;",
+                    &[
+                        JValue::Object(manager.context.as_obj()),
+                        JValue::Int(code),
+                        JValue::Object(&intent),
+                        JValue::Int(FLAG_UPDATE_CURRENT | FLAG_IMMUTABLE),
+                    ],
+                )?
+                .l()?;
+            Ok(PendingAlarm {
+                manager,
+                intent: env.new_global_ref(pending)?,
+            })
+        }))
+    }
+
+    // The deadline is converted to milliseconds of `elapsedRealtime`, which
+    // the alarm manager rounds to anyway
+    pub(crate) fn set(&self, deadline: Instant) -> io::Result<()> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        let at = elapsed_realtime()?.saturating_add(timeout);
+        let millis = i64::try_from(at.as_millis() + 1).unwrap_or(i64::MAX);
+        self.manager.with_env(|env| {
+            env.call_method(
+                self.manager.manager.as_obj(),
+                "setExactAndAllowWhileIdle",
+                "(IJLandroid/app/PendingIntent;)V",
+                &[
+                    JValue::Int(ELAPSED_REALTIME_WAKEUP),
+                    JValue::Long(millis),
+                    JValue::Object(self.intent.as_obj()),
+                ],
+            )
+            .map(drop)
+        })
+    }
+
+    pub(crate) fn cancel(&self) -> io::Result<()> {
+        self.manager.with_env(|env| {
+            env.call_method(
+                self.manager.manager.as_obj(),
+                "cancel",
+                "(Landroid/app/PendingIntent;)V",
+                &[JValue::Object(self.intent.as_obj())],
+            )
+            .map(drop)
+        })
+    }
+}
+
+impl Drop for PendingAlarm {
+    fn drop(&mut self) {
+        let _ = self.cancel();
+    }
+}
+
+impl fmt::Debug for PendingAlarm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingAlarm").finish_non_exhaustive()
+    }
+}
+
+fn elapsed_realtime() -> io::Result<Duration> {
+    let mut t: libc::timespec = unsafe { mem::zeroed() };
+    if unsafe { libc::clock_gettime(libc::CLOCK_BOOTTIME, &mut t) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(Duration::new(t.tv_sec as u64, t.tv_nsec as u32))
+}
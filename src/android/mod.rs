@@ -0,0 +1,44 @@
+//! Keeping Android devices awake for deadlines
+//!
+//! Android suspends aggressively, e.g. in Doze, and a deadline measured with
+//! [`Instant`](crate::Instant) is only noticed once the system wakes up again.
+//!
+//! With the `android-wakelock` feature, holding a [`WakeLock`] keeps the CPU
+//! running so the deadline is honored on time, and [`WakeLock::sleep_until`]
+//! holds one while sleeping until a deadline. The locks are
+//! `PowerManager.WakeLock`s taken through JNI with an installed
+//! [`PowerManager`].
+//!
+//! With the `android-alarm` feature, an installed [`AlarmManager`] lets
+//! [`WakeAlarm`](crate::WakeAlarm) wake the device in apps, which may not use
+//! `CLOCK_BOOTTIME_ALARM` directly.
+#[cfg(feature = "android-alarm")]
+mod alarm_manager;
+#[cfg(feature = "android-wakelock")]
+mod wake_lock;
+
+#[cfg(feature = "android-alarm")]
+pub use self::alarm_manager::AlarmManager;
+#[cfg(feature = "android-alarm")]
+pub(crate) use self::alarm_manager::PendingAlarm;
+#[cfg(feature = "android-wakelock")]
+pub use self::wake_lock::{PowerManager, WakeLock};
+
+use std::io;
+
+use jni::{JNIEnv, JavaVM};
+
+// Runs `f` with the thread attached to `vm`
+fn with_env<T, F>(vm: &JavaVM, f: F) -> io::Result<T>
+where
+    F: FnOnce(&mut JNIEnv<'_>) -> jni::errors::Result<T>,
+{
+    let mut env = vm.attach_current_thread().map_err(io::Error::other)?;
+    let r = f(&mut env);
+    // A thrown exception, e.g. a `SecurityException` without a permission,
+    // is reported as an error
+    if env.exception_check().unwrap_or(false) {
+        let _ = env.exception_clear();
+    }
+    r.map_err(io::Error::other)
+}
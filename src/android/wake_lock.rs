@@ -0,0 +1,239 @@
+//! Wake locks through `android.os.PowerManager`
+use core::fmt;
+use core::time::Duration;
+use std::io;
+use std::sync::OnceLock;
+
+use jni::objects::{GlobalRef, JObject, JValue};
+use jni::{JNIEnv, JavaVM};
+
+use crate::{Deadline, Instant};
+
+// `PowerManager.PARTIAL_WAKE_LOCK`, which keeps the CPU running while the
+// screen and keyboard may turn off
+const PARTIAL_WAKE_LOCK: i32 = 1;
+
+// How long a lock held for a deadline outlives it, so the device stays awake
+// until the sleeping thread has noticed the deadline
+const DEADLINE_MARGIN: Duration = Duration::from_secs(1);
+
+static INSTALLED: OnceLock<PowerManager> = OnceLock::new();
+
+/// A bridge to the `PowerManager` system service, which hands out
+/// [`WakeLock`]s.
+///
+/// Wake locks are taken with `PowerManager.newWakeLock`, which needs the
+/// `android.permission.WAKE_LOCK` permission in the app's manifest.
+///
+/// # Examples
+///
+/// Installing the bridge from a native method called by the app at startup:
+///
+/// ```no_run
+/// use boot_time::android::PowerManager;
+/// use jni::objects::{JClass, JObject};
+/// use jni::JNIEnv;
+///
+/// #[no_mangle]
+/// extern "system" fn Java_com_example_App_initWakeLocks<'local>(
+///     mut env: JNIEnv<'local>,
+///     _class: JClass<'local>,
+///     context: JObject<'local>,
+/// ) {
+///     if let Ok(manager) = PowerManager::new(&mut env, &context) {
+///         let _ = manager.install();
+///     }
+/// }
+/// ```
+pub struct PowerManager {
+    vm: JavaVM,
+    manager: GlobalRef,
+}
+
+impl PowerManager {
+    /// Looks up the `PowerManager` service of `context`, an
+    /// `android.content.Context`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a JNI call fails or throws.
+    pub fn new(env: &mut JNIEnv<'_>, context: &JObject<'_>) -> jni::errors::Result<PowerManager> {
+        let service = env.new_string("power")?;
+        let manager = env
+            .call_method(
+                context,
+                "getSystemService",
+                "(Ljava/lang/String;)Ljava/lang/Object;",
+                &[JValue::Object(&service)],
+            )?
+            .l()?;
+        Ok(PowerManager {
+            vm: env.get_java_vm()?,
+            manager: env.new_global_ref(manager)?,
+        })
+    }
+
+    /// Makes [`WakeLock`]s use the bridge.
+    ///
+    /// # Errors
+    ///
+    /// Returns the bridge back if one was installed already.
+    pub fn install(self) -> Result<(), PowerManager> {
+        INSTALLED.set(self)
+    }
+}
+
+impl fmt::Debug for PowerManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PowerManager").finish_non_exhaustive()
+    }
+}
+
+/// A partial wake lock, which keeps the CPU running until it is dropped.
+///
+/// Android suspends aggressively, e.g. in Doze, and a deadline measured with
+/// [`Instant`] is only noticed once the device wakes up again. Holding a wake
+/// lock until the deadline, e.g. with [`WakeLock::sleep_until`], honors it on
+/// time. A [`WakeAlarm`](crate::WakeAlarm) wakes the device without keeping
+/// it awake in between, but only on time with `CAP_WAKE_ALARM` or an
+/// installed `AlarmManager`.
+///
+/// The lock is a `PowerManager.WakeLock` that isn't reference counted, taken
+/// through the installed [`PowerManager`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use boot_time::android::WakeLock;
+///
+/// let _lock = WakeLock::acquire("my_app:sync")?;
+/// // the CPU keeps running until `_lock` is dropped
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct WakeLock {
+    manager: &'static PowerManager,
+    lock: GlobalRef,
+    tag: String,
+}
+
+impl WakeLock {
+    /// Acquires a wake lock with `tag`, which shows up in the battery
+    /// statistics.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`io::ErrorKind::Unsupported`] if no
+    /// [`PowerManager`] is installed, and an error if a JNI call fails or
+    /// throws, e.g. without the `WAKE_LOCK` permission.
+    pub fn acquire(tag: &str) -> io::Result<WakeLock> {
+        WakeLock::new(tag, None)
+    }
+
+    /// Acquires a wake lock with `tag`, which the system releases after
+    /// `timeout` even if the guard is leaked.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`WakeLock::acquire`].
+    pub fn acquire_timeout(tag: &str, timeout: Duration) -> io::Result<WakeLock> {
+        WakeLock::new(tag, Some(timeout))
+    }
+
+    /// Keeps the CPU running and puts the current thread to sleep until the
+    /// deadline, so it is honored on time even if the device would suspend.
+    ///
+    /// The lock is taken with a timeout a little past the deadline, so the
+    /// device can suspend again should the thread be stuck.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`WakeLock::acquire`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use boot_time::android::WakeLock;
+    /// use boot_time::{Duration, Instant};
+    ///
+    /// WakeLock::sleep_until("my_app:retry", Instant::now() + Duration::from_secs(30))?;
+    /// // retry the request
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn sleep_until(tag: &str, deadline: impl Into<Deadline>) -> io::Result<()> {
+        let deadline = deadline.into().instant();
+        let timeout = deadline
+            .saturating_duration_since(Instant::now())
+            .saturating_add(DEADLINE_MARGIN);
+        let _lock = WakeLock::acquire_timeout(tag, timeout)?;
+        crate::sleep_until(deadline);
+        Ok(())
+    }
+
+    /// Returns the tag of the lock.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// Returns `true` until the lock is released, which happens early if it
+    /// was acquired with a timeout that passed.
+    pub fn is_held(&self) -> bool {
+        super::with_env(&self.manager.vm, |env| {
+            env.call_method(self.lock.as_obj(), "isHeld", "()Z", &[])?
+                .z()
+        })
+        .unwrap_or(false)
+    }
+
+    fn new(tag: &str, timeout: Option<Duration>) -> io::Result<WakeLock> {
+        let Some(manager) = INSTALLED.get() else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "no `PowerManager` is installed",
+            ));
+        };
+        super::with_env(&manager.vm, |env| {
+            let name = env.new_string(tag)?;
+            let lock = env
+                .call_method(
+                    manager.manager.as_obj(),
+                    "newWakeLock",
+                    "(ILjava/lang/String;)Landroid/os/PowerManager$WakeLock;",
+                    &[JValue::Int(PARTIAL_WAKE_LOCK), JValue::Object(&name)],
+                )?
+                .l()?;
+            // A single `release` undoes any number of `acquire`s
+            env.call_method(&lock, "setReferenceCounted", "(Z)V", &[JValue::Bool(0)])?;
+            match timeout {
+                Some(timeout) => {
+                    let millis = i64::try_from(timeout.as_millis()).unwrap_or(i64::MAX);
+                    env.call_method(&lock, "acquire", "(J)V", &[JValue::Long(millis)])?
+                }
+                None => env.call_method(&lock, "acquire", "()V", &[])?,
+            };
+            Ok(WakeLock {
+                manager,
+                lock: env.new_global_ref(lock)?,
+                tag: tag.to_owned(),
+            })
+        })
+    }
+}
+
+impl Drop for WakeLock {
+    // Releasing a lock whose timeout passed is a no-op, since it isn't
+    // reference counted
+    fn drop(&mut self) {
+        let _ = super::with_env(&self.manager.vm, |env| {
+            env.call_method(self.lock.as_obj(), "release", "()V", &[])
+                .map(drop)
+        });
+    }
+}
+
+impl fmt::Debug for WakeLock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WakeLock")
+            .field("tag", &self.tag)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,177 @@
+//! A suspend-aware timer for `async-io`
+//!
+//! `async_io::Timer`, used by smol, measures with `CLOCK_MONOTONIC`, which
+//! stops while the system is suspended. The [`Timer`] of this module has the
+//! same API, with deadlines on the clock of [`Instant`]. It is driven by a
+//! [`TimerFd`] registered with the `async-io` reactor.
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use core::time::Duration;
+use std::io;
+
+use ::async_io::Async;
+use futures_core::Stream;
+
+use crate::{Deadline, Instant, TimerClock, TimerFd};
+
+/// A future or stream that completes at deadlines of the boot clock, like
+/// `async_io::Timer`.
+///
+/// As a future it completes at its next deadline with the instant the
+/// deadline was scheduled at. As a stream it yields that instant at every
+/// deadline, which repeats for interval timers. A timer without further
+/// deadlines stays pending.
+///
+/// The timer is created and registered with the reactor on the first poll.
+///
+/// # Panics
+///
+/// Polling panics if the timer can't be created, e.g. when the process ran
+/// out of file descriptors.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::async_io::Timer;
+/// use boot_time::{Duration, Instant};
+///
+/// # async_io::block_on(async {
+/// let start = Instant::now();
+/// Timer::after(Duration::from_millis(10)).await;
+/// assert!(start.elapsed() >= Duration::from_millis(10));
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct Timer {
+    when: Option<Instant>,
+    period: Duration,
+    fd: Option<Async<TimerFd>>,
+    // The deadline the timer descriptor is armed for
+    armed: Option<Instant>,
+}
+
+impl Timer {
+    /// Creates a timer that never fires.
+    #[must_use]
+    pub fn never() -> Timer {
+        Timer {
+            when: None,
+            period: Duration::MAX,
+            fd: None,
+            armed: None,
+        }
+    }
+
+    /// Creates a timer that fires once after `duration`.
+    #[must_use]
+    pub fn after(duration: Duration) -> Timer {
+        Timer::at(Deadline::from_timeout(duration))
+    }
+
+    /// Creates a timer that fires once at `deadline`.
+    #[must_use]
+    pub fn at(deadline: impl Into<Deadline>) -> Timer {
+        let mut timer = Timer::never();
+        timer.set_at(deadline);
+        timer
+    }
+
+    /// Creates a timer that fires every `period`, starting a period from
+    /// now.
+    #[must_use]
+    pub fn interval(period: Duration) -> Timer {
+        Timer::interval_at(Deadline::from_timeout(period), period)
+    }
+
+    /// Creates a timer that fires at `start` and then every `period`.
+    #[must_use]
+    pub fn interval_at(start: impl Into<Deadline>, period: Duration) -> Timer {
+        let mut timer = Timer::never();
+        timer.set_interval_at(start, period);
+        timer
+    }
+
+    /// Returns `true` if the timer will fire, i.e. it wasn't created by
+    /// [`Timer::never`] and hasn't fired its last deadline.
+    #[must_use]
+    pub fn will_fire(&self) -> bool {
+        self.when.is_some()
+    }
+
+    /// Makes the timer fire once after `duration`, cancelling its previous
+    /// deadlines.
+    pub fn set_after(&mut self, duration: Duration) {
+        self.set_at(Deadline::from_timeout(duration));
+    }
+
+    /// Makes the timer fire once at `deadline`, cancelling its previous
+    /// deadlines.
+    pub fn set_at(&mut self, deadline: impl Into<Deadline>) {
+        self.when = Some(deadline.into().instant());
+        self.period = Duration::MAX;
+    }
+
+    /// Makes the timer fire every `period`, starting a period from now.
+    pub fn set_interval(&mut self, period: Duration) {
+        self.set_interval_at(Deadline::from_timeout(period), period);
+    }
+
+    /// Makes the timer fire at `start` and then every `period`.
+    pub fn set_interval_at(&mut self, start: impl Into<Deadline>, period: Duration) {
+        self.when = Some(start.into().instant());
+        self.period = period;
+    }
+
+    fn poll_fire(&mut self, cx: &mut Context<'_>) -> Poll<Instant> {
+        loop {
+            let Some(when) = self.when else {
+                return Poll::Pending;
+            };
+            if Instant::now() >= when {
+                // Ticks missed by a late poll fire immediately, like
+                // `async_io::Timer` does
+                self.when = when.checked_add(self.period);
+                return Poll::Ready(when);
+            }
+            let fd = match self.arm(when) {
+                Ok(fd) => fd,
+                Err(e) => panic!("failed to arm the boot-time timer: {e}"),
+            };
+            if let Err(e) = std::task::ready!(fd.poll_readable(cx)) {
+                panic!("failed to wait for the boot-time timer: {e}");
+            }
+            // Clears the readiness; the descriptor is non-blocking
+            let _ = fd.get_ref().read_expirations();
+        }
+    }
+
+    fn arm(&mut self, when: Instant) -> io::Result<&Async<TimerFd>> {
+        let fd = match self.fd.take() {
+            Some(fd) => fd,
+            None => Async::new(TimerFd::new(TimerClock::Boot)?)?,
+        };
+        let fd = self.fd.insert(fd);
+        if self.armed != Some(when) {
+            fd.get_ref().set_deadline(when)?;
+            self.armed = Some(when);
+        }
+        Ok(fd)
+    }
+}
+
+impl Future for Timer {
+    type Output = Instant;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Instant> {
+        self.get_mut().poll_fire(cx)
+    }
+}
+
+impl Stream for Timer {
+    type Item = Instant;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Instant>> {
+        self.get_mut().poll_fire(cx).map(Some)
+    }
+}
@@ -0,0 +1,226 @@
+//! Suspend-aware sleeps, timeouts and intervals for async-std
+//!
+//! async-std's timers measure with `CLOCK_MONOTONIC`, which stops while the
+//! system is suspended. The futures of this module measure with the clock
+//! of [`Instant`] instead. They are driven by the `async-io` reactor, which
+//! async-std runs on, through the [`Timer`] of the
+//! [`async_io`](crate::async_io) module, so they don't need Tokio.
+use core::fmt;
+use core::future::{poll_fn, Future};
+use core::pin::{pin, Pin};
+use core::task::{ready, Context, Poll};
+use core::time::Duration;
+use std::error::Error;
+use std::io;
+
+use futures_core::Stream;
+
+use crate::async_io::Timer;
+use crate::{Deadline, Instant, MissedTickBehavior};
+
+/// Waits until `duration` has elapsed on the boot clock, like
+/// `async_std::task::sleep`.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::Duration;
+///
+/// # async_io::block_on(async {
+/// boot_time::async_std::sleep(Duration::from_millis(10)).await;
+/// # });
+/// ```
+pub async fn sleep(duration: Duration) {
+    Timer::after(duration).await;
+}
+
+/// Waits until `deadline` on the boot clock.
+pub async fn sleep_until(deadline: impl Into<Deadline>) {
+    Timer::at(deadline).await;
+}
+
+/// Requires `future` to complete within `duration` of the boot clock, like
+/// `async_std::future::timeout`.
+///
+/// # Errors
+///
+/// Returns [`TimeoutError`] if the duration elapses first.
+pub async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, TimeoutError> {
+    timeout_at(Deadline::from_timeout(duration), future).await
+}
+
+/// Requires `future` to complete by `deadline` on the boot clock.
+///
+/// `future` is dropped if the deadline passes first. A future that is ready
+/// on the first poll completes even if the deadline has passed.
+///
+/// # Errors
+///
+/// Returns [`TimeoutError`] if the deadline passes first.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::async_std::{sleep, timeout_at};
+/// use boot_time::{Duration, Instant};
+///
+/// # async_io::block_on(async {
+/// let deadline = Instant::now() + Duration::from_millis(10);
+/// assert_eq!(timeout_at(deadline, async { 1 }).await, Ok(1));
+/// assert!(timeout_at(deadline, sleep(Duration::from_secs(60))).await.is_err());
+/// # });
+/// ```
+pub async fn timeout_at<F: Future>(
+    deadline: impl Into<Deadline>,
+    future: F,
+) -> Result<F::Output, TimeoutError> {
+    let mut future = pin!(future);
+    let mut timer = Timer::at(deadline);
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        Pin::new(&mut timer).poll(cx).map(|_| Err(TimeoutError(())))
+    })
+    .await
+}
+
+/// The error of [`timeout`] and [`timeout_at`] if the future didn't
+/// complete in time.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TimeoutError(());
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("future has timed out")
+    }
+}
+
+impl Error for TimeoutError {}
+
+impl From<TimeoutError> for io::Error {
+    fn from(e: TimeoutError) -> io::Error {
+        io::Error::new(io::ErrorKind::TimedOut, e)
+    }
+}
+
+/// Returns an [`Interval`] whose first tick completes immediately and the
+/// following ones every `period`.
+///
+/// # Panics
+///
+/// Panics if `period` is zero.
+#[must_use]
+pub fn interval(period: Duration) -> Interval {
+    Interval::from(crate::interval(period))
+}
+
+/// Returns an [`Interval`] whose first tick completes at `start` and the
+/// following ones every `period`.
+///
+/// # Panics
+///
+/// Panics if `period` is zero.
+#[must_use]
+pub fn interval_at(start: impl Into<Deadline>, period: Duration) -> Interval {
+    Interval::from(crate::interval_at(start.into().instant(), period))
+}
+
+/// Asynchronous ticks at `start + n * period` on the clock of [`Instant`].
+///
+/// It schedules ticks like the blocking [`crate::Interval`], which it can be
+/// created from, so ticks missed while the system was suspended are handled
+/// by its [`MissedTickBehavior`]. Unlike `async_std::stream::interval`, it
+/// yields the scheduled instant of each tick.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::Duration;
+///
+/// # async_io::block_on(async {
+/// let mut interval = boot_time::async_std::interval(Duration::from_millis(10));
+/// let first = interval.tick().await;
+/// let second = interval.tick().await;
+/// assert_eq!(second - first, Duration::from_millis(10));
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct Interval {
+    inner: crate::Interval,
+    timer: Timer,
+}
+
+impl Interval {
+    /// Completes at the next tick and returns its scheduled instant.
+    ///
+    /// The future is cancel safe: dropping it before it completes doesn't
+    /// consume a tick.
+    pub async fn tick(&mut self) -> Instant {
+        poll_fn(|cx| self.poll_tick(cx)).await
+    }
+
+    /// Polls for the next tick, returning its scheduled instant once it is
+    /// due.
+    pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<Instant> {
+        self.timer.set_at(self.inner.next_tick());
+        ready!(Pin::new(&mut self.timer).poll(cx));
+        Poll::Ready(self.inner.advance(Instant::now()))
+    }
+
+    /// Reschedules the next tick a period from now.
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    /// Returns how many further ticks were already due when the last tick
+    /// was returned, see [`crate::Interval::missed_ticks`].
+    #[must_use]
+    pub fn missed_ticks(&self) -> u64 {
+        self.inner.missed_ticks()
+    }
+
+    /// Returns the instant of the next tick.
+    #[must_use]
+    pub fn next_tick(&self) -> Instant {
+        self.inner.next_tick()
+    }
+
+    /// Returns the period of the interval.
+    #[must_use]
+    pub fn period(&self) -> Duration {
+        self.inner.period()
+    }
+
+    /// Returns how missed ticks are caught up with.
+    #[must_use]
+    pub fn missed_tick_behavior(&self) -> MissedTickBehavior {
+        self.inner.missed_tick_behavior()
+    }
+
+    /// Sets how missed ticks are caught up with.
+    pub fn set_missed_tick_behavior(&mut self, behavior: MissedTickBehavior) {
+        self.inner.set_missed_tick_behavior(behavior);
+    }
+}
+
+impl From<crate::Interval> for Interval {
+    fn from(inner: crate::Interval) -> Interval {
+        Interval {
+            timer: Timer::at(inner.next_tick()),
+            inner,
+        }
+    }
+}
+
+impl Stream for Interval {
+    type Item = Instant;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Instant>> {
+        self.get_mut().poll_tick(cx).map(Some)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
@@ -0,0 +1,100 @@
+//! Lock-free storage for [`Instant`]
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+use std::fmt;
+
+use crate::sys;
+use crate::Instant;
+
+/// An [`Instant`] which can be safely shared between threads.
+///
+/// It is backed by an [`AtomicU64`] which stores the instant as nanoseconds
+/// since the clock epoch (mach ticks on Darwin). Each method takes an
+/// [`Ordering`] argument which has the same meaning as for [`AtomicU64`].
+///
+/// # Panics
+///
+/// Methods that store an instant panic if it precedes the clock epoch or is
+/// more than `u64::MAX` nanoseconds after it. Instants derived from
+/// [`Instant::now`] are always representable.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{AtomicInstant, Instant};
+/// use std::sync::atomic::Ordering;
+///
+/// let last_heartbeat = AtomicInstant::new(Instant::now());
+///
+/// // on every heartbeat, from any thread
+/// last_heartbeat.fetch_max(Instant::now(), Ordering::Relaxed);
+///
+/// println!("idle for {:?}", last_heartbeat.elapsed(Ordering::Relaxed));
+/// ```
+pub struct AtomicInstant(AtomicU64);
+
+impl AtomicInstant {
+    /// Creates a new atomic instant.
+    pub fn new(instant: Instant) -> AtomicInstant {
+        AtomicInstant(AtomicU64::new(into_bits(instant)))
+    }
+
+    /// Loads the stored instant.
+    pub fn load(&self, order: Ordering) -> Instant {
+        from_bits(self.0.load(order))
+    }
+
+    /// Stores an instant.
+    pub fn store(&self, instant: Instant, order: Ordering) {
+        self.0.store(into_bits(instant), order)
+    }
+
+    /// Stores an instant, returning the previous one.
+    pub fn swap(&self, instant: Instant, order: Ordering) -> Instant {
+        from_bits(self.0.swap(into_bits(instant), order))
+    }
+
+    /// Stores the later of the current and the passed instant, returning the
+    /// previous one.
+    ///
+    /// This keeps the stored value monotonic when several threads record
+    /// instants concurrently.
+    pub fn fetch_max(&self, instant: Instant, order: Ordering) -> Instant {
+        from_bits(self.0.fetch_max(into_bits(instant), order))
+    }
+
+    /// Returns the amount of time elapsed since the stored instant.
+    ///
+    /// See [`Instant::elapsed`].
+    pub fn elapsed(&self, order: Ordering) -> Duration {
+        self.load(order).elapsed()
+    }
+
+    /// Consumes the atomic and returns the contained instant.
+    pub fn into_inner(self) -> Instant {
+        from_bits(self.0.into_inner())
+    }
+}
+
+impl From<Instant> for AtomicInstant {
+    fn from(instant: Instant) -> AtomicInstant {
+        AtomicInstant::new(instant)
+    }
+}
+
+impl fmt::Debug for AtomicInstant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.load(Ordering::Relaxed), f)
+    }
+}
+
+fn into_bits(instant: Instant) -> u64 {
+    instant
+        .0
+        .into_bits()
+        .expect("instant is not representable as nanoseconds since clock epoch")
+}
+
+fn from_bits(bits: u64) -> Instant {
+    Instant(sys::Instant::from_bits(bits))
+}
@@ -0,0 +1,249 @@
+//! Identifiers of the current boot
+use core::fmt;
+use std::io;
+
+/// An identifier of a system boot.
+///
+/// Instants are only comparable within the boot they were taken in. Storing
+/// the boot identifier next to a serialized instant tells whether it still
+/// is.
+///
+/// | Platform              | Source                                   |
+/// |-----------------------|------------------------------------------|
+/// | Linux, Android        | `/proc/sys/kernel/random/boot_id`        |
+/// | Apple platforms       | `kern.bootsessionuuid`, or `kern.boottime` |
+/// | FreeBSD, NetBSD, OpenBSD, DragonFly BSD | `kern.boottime`        |
+/// | Windows 8+            | The `BootId` boot counter in the registry |
+///
+/// `kern.boottime` moves when the system clock is stepped, so on the BSDs a
+/// clock adjustment makes the identifier of the same boot differ.
+///
+/// # Examples
+///
+/// ```no_run
+/// use boot_time::{boot_id, BootId, Instant};
+///
+/// let deadline = Instant::now();
+/// let stored = (boot_id()?.to_bytes(), deadline);
+/// // later, e.g. read back from shared memory
+/// let (boot, deadline) = stored;
+/// if BootId::from_bytes(boot) == boot_id()? {
+///     println!("{:?} left", deadline.saturating_duration_since(Instant::now()));
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BootId([u8; 16]);
+
+impl BootId {
+    /// Creates an identifier from the bytes returned by [`BootId::to_bytes`].
+    pub const fn from_bytes(bytes: [u8; 16]) -> BootId {
+        BootId(bytes)
+    }
+
+    /// Returns the identifier as bytes, e.g. to store it.
+    pub const fn to_bytes(self) -> [u8; 16] {
+        self.0
+    }
+}
+
+// Formatted like a UUID
+impl fmt::Display for BootId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, b) in self.0.iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                f.write_str("-")?;
+            }
+            write!(f, "{b:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for BootId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BootId({self})")
+    }
+}
+
+/// Returns the identifier of the current boot.
+///
+/// It is read on every call, so it changes if the process is restored on
+/// another boot, e.g. by checkpoint/restore.
+///
+/// # Errors
+///
+/// Returns an error if the identifier can't be read, and an error of kind
+/// [`io::ErrorKind::Unsupported`] on other platforms.
+pub fn boot_id() -> io::Result<BootId> {
+    imp::boot_id()
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple"))]
+fn parse_uuid(s: &str) -> Option<BootId> {
+    let hex: Vec<u8> = s.trim().bytes().filter(|&b| b != b'-').collect();
+    if hex.len() != 32 {
+        return None;
+    }
+    let mut bytes = [0; 16];
+    for (byte, pair) in bytes.iter_mut().zip(hex.chunks(2)) {
+        *byte = u8::from_str_radix(core::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(BootId(bytes))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", windows))]
+fn invalid_data(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("malformed {what}"))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod imp {
+    use super::{invalid_data, parse_uuid, BootId};
+    use std::{fs, io};
+
+    pub(super) fn boot_id() -> io::Result<BootId> {
+        let s = fs::read_to_string("/proc/sys/kernel/random/boot_id")?;
+        parse_uuid(&s).ok_or_else(|| invalid_data("boot_id"))
+    }
+}
+
+#[cfg(any(
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly",
+))]
+mod imp {
+    use super::BootId;
+    use std::io;
+    use std::mem;
+    use std::ptr;
+
+    pub(super) fn boot_id() -> io::Result<BootId> {
+        #[cfg(target_vendor = "apple")]
+        if let Some(id) = boot_session_uuid() {
+            return Ok(id);
+        }
+        let mut mib = [libc::CTL_KERN, libc::KERN_BOOTTIME];
+        let mut tv: libc::timeval = unsafe { mem::zeroed() };
+        let mut len = mem::size_of::<libc::timeval>();
+        let r = unsafe {
+            libc::sysctl(
+                mib.as_mut_ptr(),
+                mib.len() as _,
+                (&mut tv as *mut libc::timeval).cast(),
+                &mut len,
+                ptr::null_mut(),
+                0,
+            )
+        };
+        if r != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&(tv.tv_sec as i64).to_be_bytes());
+        bytes[8..].copy_from_slice(&(tv.tv_usec as i64).to_be_bytes());
+        Ok(BootId(bytes))
+    }
+
+    // A random UUID generated at boot, available since macOS 10.10
+    #[cfg(target_vendor = "apple")]
+    fn boot_session_uuid() -> Option<BootId> {
+        let mut buf = [0u8; 64];
+        let mut len = buf.len();
+        let r = unsafe {
+            libc::sysctlbyname(
+                c"kern.bootsessionuuid".as_ptr(),
+                buf.as_mut_ptr().cast(),
+                &mut len,
+                ptr::null_mut(),
+                0,
+            )
+        };
+        if r != 0 {
+            return None;
+        }
+        let s = core::ffi::CStr::from_bytes_until_nul(&buf[..len]).ok()?;
+        super::parse_uuid(s.to_str().ok()?)
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::{invalid_data, BootId};
+    use std::ffi::{c_char, c_void};
+    use std::io;
+    use std::mem;
+    use std::ptr;
+
+    #[allow(clippy::upper_case_acronyms)]
+    type HKEY = *mut c_void;
+
+    const HKEY_LOCAL_MACHINE: HKEY = 0x8000_0002_u32 as i32 as isize as HKEY;
+    const RRF_RT_REG_DWORD: u32 = 0x10;
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        fn RegGetValueA(
+            hkey: HKEY,
+            lpSubKey: *const c_char,
+            lpValue: *const c_char,
+            dwFlags: u32,
+            pdwType: *mut u32,
+            pvData: *mut c_void,
+            pcbData: *mut u32,
+        ) -> i32;
+    }
+
+    // The counter is incremented on every boot, so it is unique on the
+    // machine. It exists since Windows 8.
+    pub(super) fn boot_id() -> io::Result<BootId> {
+        let mut count = 0u32;
+        let mut len = mem::size_of::<u32>() as u32;
+        let r = unsafe {
+            RegGetValueA(
+                HKEY_LOCAL_MACHINE,
+                c"SYSTEM\\CurrentControlSet\\Control\\Session Manager\\Memory Management\\PrefetchParameters"
+                    .as_ptr(),
+                c"BootId".as_ptr(),
+                RRF_RT_REG_DWORD,
+                ptr::null_mut(),
+                (&mut count as *mut u32).cast(),
+                &mut len,
+            )
+        };
+        if r != 0 {
+            return Err(io::Error::from_raw_os_error(r));
+        }
+        if len != mem::size_of::<u32>() as u32 {
+            return Err(invalid_data("BootId"));
+        }
+        let mut bytes = [0; 16];
+        bytes[12..].copy_from_slice(&count.to_be_bytes());
+        Ok(BootId(bytes))
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly",
+    windows,
+)))]
+mod imp {
+    use super::BootId;
+    use std::io;
+
+    pub(super) fn boot_id() -> io::Result<BootId> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the boot identifier is not available on this platform",
+        ))
+    }
+}
@@ -0,0 +1,270 @@
+//! Instants parameterized over the clock they were taken from
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+use core::time::Duration;
+use std::fmt;
+
+use crate::sys;
+
+/// A source of [`TypedInstant`]s.
+///
+/// Timing code can be written generically over the clock, so that tests
+/// substitute a fake clock for one of the system clocks.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{Clock, TypedInstant};
+/// use std::cell::Cell;
+/// use std::time::Duration;
+///
+/// thread_local!(static NOW: Cell<Duration> = Cell::new(Duration::ZERO));
+///
+/// struct FakeClock;
+///
+/// impl Clock for FakeClock {
+///     fn now() -> Duration {
+///         NOW.get()
+///     }
+/// }
+///
+/// let start = TypedInstant::<FakeClock>::now();
+/// NOW.set(Duration::from_secs(5));
+/// assert_eq!(start.elapsed(), Duration::from_secs(5));
+/// ```
+pub trait Clock {
+    /// Returns the time elapsed since the epoch of the clock.
+    ///
+    /// The returned value must never decrease.
+    fn now() -> Duration;
+}
+
+/// The clock behind [`Instant`](crate::Instant), which counts suspended time
+/// where the platform supports it.
+#[derive(Copy, Clone, Debug)]
+pub struct BootClock;
+
+impl Clock for BootClock {
+    fn now() -> Duration {
+        sys::Instant::now()
+            .as_nanos()
+            .map_or(Duration::ZERO, Duration::from_nanos)
+    }
+}
+
+/// The monotonic clock of the platform, which stops while the system is
+/// suspended where the platform makes that distinction.
+///
+/// It is `CLOCK_MONOTONIC` on UNIX, `mach_absolute_time` on Apple platforms
+/// and `QueryPerformanceCounter` on Windows. Platforms with a single clock
+/// read the clock of [`BootClock`].
+#[derive(Copy, Clone, Debug)]
+pub struct MonotonicClock;
+
+impl Clock for MonotonicClock {
+    fn now() -> Duration {
+        sys::monotonic_now()
+    }
+}
+
+/// A monotonic clock that isn't slewed by NTP frequency adjustments, for
+/// benchmarks.
+///
+/// It is `CLOCK_MONOTONIC_RAW` on Linux and Android. Elsewhere it reads the
+/// same clock as [`MonotonicClock`], which isn't slewed on Apple platforms,
+/// Windows, illumos, Solaris, Haiku and Fuchsia.
+#[derive(Copy, Clone, Debug)]
+pub struct RawMonotonicClock;
+
+impl Clock for RawMonotonicClock {
+    fn now() -> Duration {
+        sys::raw_monotonic_now()
+    }
+}
+
+/// A measurement of the clock `C`.
+///
+/// Instants of different clocks have distinct types, so they can't be mixed
+/// up. The arithmetic follows [`Instant`](crate::Instant): subtraction
+/// saturates to zero and the operators panic on overflow.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{MonotonicClock, TypedInstant};
+///
+/// let start = TypedInstant::<MonotonicClock>::now();
+/// println!("took {:?}", start.elapsed());
+/// ```
+pub struct TypedInstant<C> {
+    // time since the clock epoch
+    t: Duration,
+    clock: PhantomData<fn() -> C>,
+}
+
+/// A measurement of [`RawMonotonicClock`], for benchmarks that must not be
+/// affected by NTP frequency slewing.
+///
+/// It can't be mixed up with [`Instant`](crate::Instant), which counts
+/// suspended time and is slewed on Linux.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::RawInstant;
+///
+/// let start = RawInstant::now();
+/// // benchmarked code
+/// println!("took {:?}", start.elapsed());
+/// ```
+pub type RawInstant = TypedInstant<RawMonotonicClock>;
+
+impl<C: Clock> TypedInstant<C> {
+    /// Returns an instant corresponding to "now".
+    #[must_use]
+    pub fn now() -> TypedInstant<C> {
+        TypedInstant::from_duration_since_epoch(C::now())
+    }
+
+    /// Returns the amount of time elapsed since this instant.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        TypedInstant::now().duration_since(*self)
+    }
+}
+
+impl<C> TypedInstant<C> {
+    /// Creates an instant the given time after the clock epoch.
+    pub const fn from_duration_since_epoch(t: Duration) -> TypedInstant<C> {
+        TypedInstant {
+            t,
+            clock: PhantomData,
+        }
+    }
+
+    /// Returns the time elapsed from the clock epoch to this instant.
+    #[must_use]
+    pub const fn duration_since_epoch(&self) -> Duration {
+        self.t
+    }
+
+    /// Returns the amount of time elapsed from another instant to this one,
+    /// or zero duration if that instant is later than this one.
+    #[must_use]
+    pub fn duration_since(&self, earlier: TypedInstant<C>) -> Duration {
+        self.saturating_duration_since(earlier)
+    }
+
+    /// Returns the amount of time elapsed from another instant to this one,
+    /// or `None` if that instant is later than this one.
+    #[must_use]
+    pub fn checked_duration_since(&self, earlier: TypedInstant<C>) -> Option<Duration> {
+        self.t.checked_sub(earlier.t)
+    }
+
+    /// Returns the amount of time elapsed from another instant to this one,
+    /// or zero duration if that instant is later than this one.
+    #[must_use]
+    pub fn saturating_duration_since(&self, earlier: TypedInstant<C>) -> Duration {
+        self.t.saturating_sub(earlier.t)
+    }
+
+    /// Returns `Some(t)` where `t` is the time `self + duration` if `t` can be
+    /// represented, `None` otherwise.
+    pub fn checked_add(&self, duration: Duration) -> Option<TypedInstant<C>> {
+        self.t
+            .checked_add(duration)
+            .map(TypedInstant::from_duration_since_epoch)
+    }
+
+    /// Returns `Some(t)` where `t` is the time `self - duration` if `t` can be
+    /// represented, `None` otherwise.
+    pub fn checked_sub(&self, duration: Duration) -> Option<TypedInstant<C>> {
+        self.t
+            .checked_sub(duration)
+            .map(TypedInstant::from_duration_since_epoch)
+    }
+}
+
+impl<C> Clone for TypedInstant<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C> Copy for TypedInstant<C> {}
+
+impl<C> PartialEq for TypedInstant<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.t == other.t
+    }
+}
+
+impl<C> Eq for TypedInstant<C> {}
+
+impl<C> PartialOrd for TypedInstant<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C> Ord for TypedInstant<C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.t.cmp(&other.t)
+    }
+}
+
+impl<C> Hash for TypedInstant<C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.t.hash(state);
+    }
+}
+
+impl<C> Add<Duration> for TypedInstant<C> {
+    type Output = TypedInstant<C>;
+
+    fn add(self, other: Duration) -> TypedInstant<C> {
+        self.checked_add(other)
+            .expect("overflow when adding duration to instant")
+    }
+}
+
+impl<C> AddAssign<Duration> for TypedInstant<C> {
+    fn add_assign(&mut self, other: Duration) {
+        *self = *self + other;
+    }
+}
+
+impl<C> Sub<Duration> for TypedInstant<C> {
+    type Output = TypedInstant<C>;
+
+    fn sub(self, other: Duration) -> TypedInstant<C> {
+        self.checked_sub(other)
+            .expect("overflow when subtracting duration from instant")
+    }
+}
+
+impl<C> SubAssign<Duration> for TypedInstant<C> {
+    fn sub_assign(&mut self, other: Duration) {
+        *self = *self - other;
+    }
+}
+
+impl<C> Sub<TypedInstant<C>> for TypedInstant<C> {
+    type Output = Duration;
+
+    fn sub(self, other: TypedInstant<C>) -> Duration {
+        self.duration_since(other)
+    }
+}
+
+impl<C> fmt::Debug for TypedInstant<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedInstant")
+            .field("clock", &core::any::type_name::<C>())
+            .field("since_epoch", &self.t)
+            .finish()
+    }
+}
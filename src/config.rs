@@ -0,0 +1,176 @@
+//! Process-wide clock configuration
+use crate::ClockSource;
+use std::error::Error;
+use std::fmt;
+use std::sync::OnceLock;
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Process-wide configuration of the clock behind [`Instant`](crate::Instant).
+///
+/// The configuration is installed once with [`Config::init`] at startup,
+/// before the first [`Instant`](crate::Instant) is taken. The clock is
+/// selected with the default configuration otherwise.
+///
+/// ```
+/// use boot_time::{ClockSource, Config};
+///
+/// fn log_clock(source: ClockSource) {
+///     eprintln!("timing with {source:?}");
+/// }
+///
+/// Config::new()
+///     .suspend_aware(false)
+///     .on_clock_selected(log_clock)
+///     .init()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Config {
+    suspend_aware: bool,
+    on_clock_selected: Option<fn(ClockSource)>,
+    fallback: FallbackPolicy,
+}
+
+/// What [`Config::init`] does if a clock counting suspended time is preferred
+/// but unavailable, e.g. because a container rejects `CLOCK_BOOTTIME`.
+#[derive(Copy, Clone, Debug, Default)]
+pub enum FallbackPolicy {
+    /// Fail [`Config::init`]. [`Instant`](crate::Instant) still reads the
+    /// fallback clock, so the error is meant to be fatal.
+    Strict,
+    /// Silently use a clock that stops while the system is suspended.
+    #[default]
+    Fallback,
+    /// Call the function with the fallback clock, which is used if the
+    /// function returns.
+    Custom(fn(ClockSource)),
+}
+
+impl Config {
+    /// Returns the default configuration.
+    pub const fn new() -> Config {
+        Config {
+            suspend_aware: true,
+            on_clock_selected: None,
+            fallback: FallbackPolicy::Fallback,
+        }
+    }
+
+    /// Prefers a clock counting suspended time if `true`, the default, or a
+    /// clock that stops while the system is suspended otherwise.
+    ///
+    /// The preference is honored on UNIX. It is the runtime counterpart of the
+    /// `force-monotonic` feature, which takes precedence.
+    pub const fn suspend_aware(mut self, suspend_aware: bool) -> Config {
+        self.suspend_aware = suspend_aware;
+        self
+    }
+
+    /// Sets a function that is called with the selected clock during
+    /// [`Config::init`], e.g. to log it.
+    pub const fn on_clock_selected(mut self, hook: fn(ClockSource)) -> Config {
+        self.on_clock_selected = Some(hook);
+        self
+    }
+
+    /// Sets what happens if the preferred clock counting suspended time is
+    /// unavailable, see [`FallbackPolicy`].
+    pub const fn fallback_policy(mut self, policy: FallbackPolicy) -> Config {
+        self.fallback = policy;
+        self
+    }
+
+    /// Installs the configuration and selects the clock.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a configuration was installed already, or if the
+    /// clock was already selected with the default configuration.
+    ///
+    /// With [`FallbackPolicy::Strict`] it also returns an error if the
+    /// selected clock doesn't count suspended time although it was preferred.
+    pub fn init(self) -> Result<(), InitError> {
+        let config = self.clone();
+        CONFIG
+            .set(self)
+            .map_err(|_| InitError(InitErrorKind::AlreadySet))?;
+        let source = crate::clock_source();
+        if let Some(hook) = config.on_clock_selected {
+            hook(source);
+        }
+        config.check_fallback(source)
+    }
+
+    // Applies the fallback policy to the selected clock
+    pub(crate) fn check_fallback(&self, source: ClockSource) -> Result<(), InitError> {
+        let preferred = self.suspend_aware && !cfg!(feature = "force-monotonic");
+        if !preferred || source.is_suspend_aware() {
+            return Ok(());
+        }
+        match self.fallback {
+            FallbackPolicy::Strict => Err(InitError(InitErrorKind::Fallback(source))),
+            FallbackPolicy::Fallback => Ok(()),
+            FallbackPolicy::Custom(f) => {
+                f(source);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config::new()
+    }
+}
+
+// Returns the installed configuration. The default one is installed on first
+// use, so that later `Config::init` calls fail instead of being ignored.
+#[allow(dead_code)] // not all platforms select the clock at runtime
+pub(crate) fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::new)
+}
+
+#[allow(dead_code)] // not all platforms select the clock at runtime
+pub(crate) fn prefer_suspend_aware() -> bool {
+    get().suspend_aware && !cfg!(feature = "force-monotonic")
+}
+
+/// The error returned by [`Config::init`] if the configuration can't be
+/// installed anymore or the clock violates the [`FallbackPolicy`].
+#[derive(Debug)]
+pub struct InitError(InitErrorKind);
+
+#[derive(Debug)]
+enum InitErrorKind {
+    AlreadySet,
+    Fallback(ClockSource),
+}
+
+impl InitError {
+    /// Returns the selected clock if it was rejected by
+    /// [`FallbackPolicy::Strict`].
+    pub fn fallback_source(&self) -> Option<ClockSource> {
+        match self.0 {
+            InitErrorKind::AlreadySet => None,
+            InitErrorKind::Fallback(source) => Some(source),
+        }
+    }
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            InitErrorKind::AlreadySet => f.write_str("the clock configuration was already set"),
+            InitErrorKind::Fallback(source) => {
+                write!(
+                    f,
+                    "{source:?} doesn't count the time the system was suspended"
+                )
+            }
+        }
+    }
+}
+
+impl Error for InitError {}
@@ -0,0 +1,137 @@
+//! Countdowns that can be paused
+use core::time::Duration;
+
+use crate::{Deadline, Instant};
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+enum State {
+    Running { resumed: Instant },
+    Paused,
+}
+
+/// A countdown that only counts down while it runs, e.g. the time limit of a
+/// game level that stops in the pause menu.
+///
+/// The countdown accumulates the active time on the clock of [`Instant`],
+/// so time the system spends suspended while the countdown runs counts, and
+/// time it spends paused doesn't.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{Countdown, Duration};
+///
+/// let mut countdown = Countdown::new(Duration::from_millis(20));
+/// countdown.pause();
+/// std::thread::sleep(Duration::from_millis(30));
+/// assert!(!countdown.has_elapsed());
+/// countdown.resume();
+/// boot_time::sleep_until(countdown.deadline().unwrap());
+/// assert!(countdown.has_elapsed());
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Countdown {
+    duration: Duration,
+    // The active time before the countdown was last resumed
+    elapsed: Duration,
+    state: State,
+}
+
+impl Countdown {
+    /// Starts a countdown of `duration`.
+    #[must_use]
+    pub fn new(duration: Duration) -> Countdown {
+        Countdown {
+            duration,
+            elapsed: Duration::ZERO,
+            state: State::Running {
+                resumed: Instant::now(),
+            },
+        }
+    }
+
+    /// Returns a paused countdown of `duration`, which starts counting down
+    /// when resumed.
+    #[must_use]
+    pub fn paused(duration: Duration) -> Countdown {
+        Countdown {
+            duration,
+            elapsed: Duration::ZERO,
+            state: State::Paused,
+        }
+    }
+
+    /// Stops counting down. Does nothing if the countdown is paused.
+    pub fn pause(&mut self) {
+        self.elapsed = self.elapsed();
+        self.state = State::Paused;
+    }
+
+    /// Continues counting down. Does nothing if the countdown runs.
+    pub fn resume(&mut self) {
+        if self.state == State::Paused {
+            self.state = State::Running {
+                resumed: Instant::now(),
+            };
+        }
+    }
+
+    /// Restarts the countdown from its full duration, keeping it running or
+    /// paused.
+    pub fn reset(&mut self) {
+        self.elapsed = Duration::ZERO;
+        if let State::Running { resumed } = &mut self.state {
+            *resumed = Instant::now();
+        }
+    }
+
+    /// Returns `true` if the countdown is paused.
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.state == State::Paused
+    }
+
+    /// Returns the duration the countdown started from.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns the time the countdown has been running.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        match self.state {
+            State::Running { resumed } => self.elapsed.saturating_add(resumed.elapsed()),
+            State::Paused => self.elapsed,
+        }
+    }
+
+    /// Returns the time left to count down, or zero duration if the
+    /// countdown has elapsed.
+    #[must_use]
+    pub fn remaining(&self) -> Duration {
+        self.duration.saturating_sub(self.elapsed())
+    }
+
+    /// Returns `true` if the countdown has counted down its full duration.
+    #[must_use]
+    pub fn has_elapsed(&self) -> bool {
+        self.elapsed() >= self.duration
+    }
+
+    /// Returns the deadline the countdown elapses at if it keeps running, or
+    /// `None` while it is paused. A paused countdown has to be resumed
+    /// before waiting for it.
+    #[must_use]
+    pub fn deadline(&self) -> Option<Deadline> {
+        let State::Running { resumed } = self.state else {
+            return None;
+        };
+        let left = self.duration.saturating_sub(self.elapsed);
+        Some(Deadline::at(
+            resumed
+                .checked_add(left)
+                .unwrap_or_else(Instant::far_future),
+        ))
+    }
+}
@@ -0,0 +1,53 @@
+//! CPU time clocks
+use core::time::Duration;
+
+use crate::clock::{Clock, TypedInstant};
+use crate::sys;
+
+/// The CPU time consumed by all threads of the process.
+///
+/// It is `CLOCK_PROCESS_CPUTIME_ID` on UNIX and the sum of the kernel and user
+/// time reported by `GetProcessTimes` on Windows.
+#[derive(Copy, Clone, Debug)]
+pub struct ProcessCpuClock;
+
+impl Clock for ProcessCpuClock {
+    fn now() -> Duration {
+        sys::process_cpu_now()
+    }
+}
+
+/// The CPU time consumed by the calling thread.
+///
+/// It is `CLOCK_THREAD_CPUTIME_ID` on UNIX and the sum of the kernel and user
+/// time reported by `GetThreadTimes` on Windows.
+#[derive(Copy, Clone, Debug)]
+pub struct ThreadCpuClock;
+
+impl Clock for ThreadCpuClock {
+    fn now() -> Duration {
+        sys::thread_cpu_now()
+    }
+}
+
+/// A measurement of the CPU time consumed by the process.
+///
+/// # Examples
+///
+/// Comparing the CPU time with the wall time of a computation:
+///
+/// ```
+/// use boot_time::{Instant, ProcessCpuInstant};
+///
+/// let wall = Instant::now();
+/// let cpu = ProcessCpuInstant::now();
+/// let sum: u64 = (0..1_000_000).sum();
+/// println!("{sum}: {:?} on the CPU, {:?} elapsed", cpu.elapsed(), wall.elapsed());
+/// ```
+pub type ProcessCpuInstant = TypedInstant<ProcessCpuClock>;
+
+/// A measurement of the CPU time consumed by the calling thread.
+///
+/// Instants are only meaningful on the thread that took them: the time
+/// between instants of different threads is unrelated to either thread.
+pub type ThreadCpuInstant = TypedInstant<ThreadCpuClock>;
@@ -0,0 +1,30 @@
+/// Registers the clock of an otherwise unsupported target.
+///
+/// With the `custom` feature targets without a built-in backend read the time
+/// from a function registered with this macro instead of reexporting
+/// `std::time::Instant`. The function returns the nanoseconds elapsed since a
+/// fixed point in the past, usually boot, and must never go backwards.
+///
+/// The macro must be invoked exactly once in the final binary. Linking fails
+/// if a custom clock is required but no function was registered.
+///
+/// ```ignore (requires an unsupported target)
+/// fn rtos_now() -> u64 {
+///     // read the tick counter of the RTOS and convert ticks to nanoseconds
+///     # 0
+/// }
+///
+/// boot_time::register_custom_clock!(rtos_now);
+/// ```
+#[macro_export]
+macro_rules! register_custom_clock {
+    ($now:path) => {
+        const _: () = {
+            #[unsafe(no_mangle)]
+            extern "Rust" fn __boot_time_custom_now() -> u64 {
+                let now: fn() -> u64 = $now;
+                now()
+            }
+        };
+    };
+}
@@ -0,0 +1,95 @@
+//! Deadlines on the boot clock
+use core::time::Duration;
+
+use crate::Instant;
+
+/// A point in time by which an operation has to complete.
+///
+/// Passing a deadline through a chain of calls, rather than a timeout each
+/// call restarts, keeps the total wait bounded: computing the deadline once
+/// and asking it for the [remaining](Deadline::remaining) time before each
+/// blocking step avoids stacking timeouts.
+///
+/// The sleep and timer functions of this crate accept a `Deadline` or an
+/// [`Instant`].
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{Deadline, Duration};
+///
+/// let deadline = Deadline::from_timeout(Duration::from_millis(20));
+/// // resolve the host name
+/// assert!(deadline.remaining() <= Duration::from_millis(20));
+/// // connect, waiting for at most `deadline.remaining()`
+/// boot_time::sleep_until(deadline);
+/// assert!(deadline.has_elapsed());
+/// assert_eq!(deadline.checked_remaining(), None);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Deadline(Instant);
+
+impl Deadline {
+    /// Returns a deadline `timeout` from now. A timeout too large to
+    /// represent gives a deadline that never elapses.
+    #[must_use]
+    pub fn from_timeout(timeout: Duration) -> Deadline {
+        Deadline(
+            Instant::now()
+                .checked_add(timeout)
+                .unwrap_or_else(Instant::far_future),
+        )
+    }
+
+    /// Returns a deadline at `instant`.
+    #[must_use]
+    pub fn at(instant: Instant) -> Deadline {
+        Deadline(instant)
+    }
+
+    /// Returns a deadline that never elapses in practice.
+    #[must_use]
+    pub fn never() -> Deadline {
+        Deadline(Instant::far_future())
+    }
+
+    /// Returns the instant of the deadline.
+    #[must_use]
+    pub fn instant(&self) -> Instant {
+        self.0
+    }
+
+    /// Returns the time left until the deadline, or zero duration if it
+    /// has elapsed.
+    #[must_use]
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(Instant::now())
+    }
+
+    /// Returns the time left until the deadline, or `None` if it has
+    /// elapsed.
+    #[must_use]
+    pub fn checked_remaining(&self) -> Option<Duration> {
+        self.0
+            .checked_duration_since(Instant::now())
+            .filter(|d| !d.is_zero())
+    }
+
+    /// Returns `true` if the deadline has been reached.
+    #[must_use]
+    pub fn has_elapsed(&self) -> bool {
+        Instant::now() >= self.0
+    }
+}
+
+impl From<Instant> for Deadline {
+    fn from(instant: Instant) -> Deadline {
+        Deadline(instant)
+    }
+}
+
+impl From<Deadline> for Instant {
+    fn from(deadline: Deadline) -> Instant {
+        deadline.0
+    }
+}
@@ -0,0 +1,134 @@
+//! Futures on the boot clock for any executor
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use core::time::Duration;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::{Deadline, Instant, Scheduler, TimerHandle};
+
+// The thread waking the delays of all executors
+pub(crate) fn driver() -> &'static Scheduler {
+    static DRIVER: OnceLock<Scheduler> = OnceLock::new();
+    DRIVER.get_or_init(|| match Scheduler::new() {
+        Ok(scheduler) => scheduler,
+        Err(e) => panic!("failed to start the boot-time timer thread: {e}"),
+    })
+}
+
+#[derive(Debug)]
+struct Registration {
+    handle: TimerHandle,
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+/// A future that completes at a deadline of the boot clock, on any executor.
+///
+/// Unlike the timers of the `tokio` and `async_io` modules it needs no
+/// reactor: the deadlines of all delays are kept by one background thread,
+/// which wakes their tasks when they pass. It is a [`Scheduler`], so on Linux
+/// and Android the thread sleeps on a boot clock
+/// [`TimerFd`](crate::TimerFd), and elsewhere a delay due during a suspend
+/// completes up to a second after resume.
+///
+/// The thread is started by the first delay that has to wait.
+///
+/// # Panics
+///
+/// Polling panics if the thread can't be started.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{Delay, Duration};
+///
+/// async fn retry_later() {
+///     Delay::new(Duration::from_secs(5)).await;
+///     // retry
+/// }
+/// ```
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Delay {
+    deadline: Instant,
+    registration: Option<Registration>,
+}
+
+impl Delay {
+    /// Creates a delay that completes after `duration`.
+    pub fn new(duration: Duration) -> Delay {
+        Delay::until(Deadline::from_timeout(duration))
+    }
+
+    /// Creates a delay that completes at `deadline`.
+    pub fn until(deadline: impl Into<Deadline>) -> Delay {
+        Delay {
+            deadline: deadline.into().instant(),
+            registration: None,
+        }
+    }
+
+    /// Returns the instant the delay completes at.
+    #[must_use]
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Moves the deadline to `deadline`, which may be earlier or later, even
+    /// if the delay has completed.
+    pub fn reset(&mut self, deadline: impl Into<Deadline>) {
+        self.deadline = deadline.into().instant();
+        if let Some(registration) = &self.registration {
+            // The wake-up ran already
+            if !registration.handle.reset(self.deadline) {
+                self.registration = None;
+            }
+        }
+    }
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        if Instant::now() >= this.deadline {
+            return Poll::Ready(());
+        }
+        match &this.registration {
+            Some(registration) => {
+                let mut waker = registration.waker.lock().unwrap_or_else(|e| e.into_inner());
+                match &mut *waker {
+                    Some(waker) => waker.clone_from(cx.waker()),
+                    None => *waker = Some(cx.waker().clone()),
+                }
+            }
+            None => {
+                let waker = Arc::new(Mutex::new(Some(cx.waker().clone())));
+                let handle = driver().schedule_at(this.deadline, {
+                    let waker = Arc::clone(&waker);
+                    move || {
+                        let waker = waker.lock().unwrap_or_else(|e| e.into_inner()).take();
+                        if let Some(waker) = waker {
+                            waker.wake();
+                        }
+                    }
+                });
+                this.registration = Some(Registration { handle, waker });
+            }
+        }
+        // The wake-up may have run with a previous waker
+        if Instant::now() >= this.deadline {
+            return Poll::Ready(());
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Delay {
+    fn drop(&mut self) {
+        if let Some(registration) = &self.registration {
+            registration.handle.cancel();
+        }
+    }
+}
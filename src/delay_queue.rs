@@ -0,0 +1,227 @@
+//! A blocking queue of items that become available at deadlines
+use core::fmt;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, Weak};
+
+use crate::sleep::MAX_CHUNK;
+use crate::timer_handle::TimerControl;
+use crate::{Deadline, Instant, TimerHandle};
+
+// Items with equal deadlines are kept in insertion order
+type Key = (Instant, u64);
+
+struct Queue<T> {
+    items: BTreeMap<Key, T>,
+    seq: u64,
+}
+
+impl<T> Queue<T> {
+    fn insert(&mut self, deadline: Instant, item: T) -> Key {
+        let key = (deadline, self.seq);
+        self.seq += 1;
+        self.items.insert(key, item);
+        key
+    }
+}
+
+struct Inner<T> {
+    queue: Mutex<Queue<T>>,
+    changed: Condvar,
+}
+
+impl<T> Inner<T> {
+    fn lock(&self) -> MutexGuard<'_, Queue<T>> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A queue of items that can be taken once their deadline has passed, for
+/// job schedulers running in plain threads.
+///
+/// Deadlines are on the clock of [`Instant`], so an item due during a
+/// suspend is available right after resume. All methods take `&self`, so the
+/// queue can be shared between producer threads and a consumer waiting with
+/// [`DelayQueue::wait_next`].
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{DelayQueue, Duration, Instant};
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let queue = Arc::new(DelayQueue::new());
+/// let producer = Arc::clone(&queue);
+/// thread::spawn(move || {
+///     producer.insert("retry", Instant::now() + Duration::from_millis(20));
+///     producer.insert("ping", Instant::now() + Duration::from_millis(10));
+/// });
+/// assert_eq!(queue.wait_next(), "ping");
+/// assert_eq!(queue.wait_next(), "retry");
+/// ```
+pub struct DelayQueue<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T: Send + 'static> DelayQueue<T> {
+    /// Inserts `item` to become available at `deadline`. Items with equal
+    /// deadlines are taken in insertion order.
+    ///
+    /// The returned handle removes the item or moves it to another deadline
+    /// until it is taken. A removed item is dropped.
+    pub fn insert(&self, item: T, deadline: impl Into<Deadline>) -> TimerHandle {
+        let key = self.lock().insert(deadline.into().instant(), item);
+        self.inner.changed.notify_all();
+        TimerHandle::new(ItemControl {
+            inner: Arc::downgrade(&self.inner),
+            key: Mutex::new(key),
+        })
+    }
+}
+
+impl<T> DelayQueue<T> {
+    /// Creates an empty queue.
+    #[must_use]
+    pub fn new() -> DelayQueue<T> {
+        DelayQueue {
+            inner: Arc::new(Inner {
+                queue: Mutex::new(Queue {
+                    items: BTreeMap::new(),
+                    seq: 0,
+                }),
+                changed: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Removes and returns the item with the earliest deadline if that has
+    /// passed.
+    pub fn pop_expired(&self) -> Option<T> {
+        let mut queue = self.lock();
+        Self::pop_if_expired(&mut queue, Instant::now())
+    }
+
+    /// Blocks until an item's deadline passes, then removes and returns it.
+    ///
+    /// The thread wakes at least every second to re-check the clock, if the
+    /// platform's condition variables measure with a clock that stops during
+    /// suspend, so an item due during a suspend is returned at most a second
+    /// after resume.
+    pub fn wait_next(&self) -> T {
+        let mut queue = self.lock();
+        loop {
+            let now = Instant::now();
+            if let Some(item) = Self::pop_if_expired(&mut queue, now) {
+                return item;
+            }
+            let changed = &self.inner.changed;
+            queue = match queue.items.first_key_value() {
+                Some((&(deadline, _), _)) => {
+                    let mut timeout = deadline - now;
+                    if crate::clock_source().is_suspend_aware() {
+                        timeout = timeout.min(MAX_CHUNK);
+                    }
+                    changed
+                        .wait_timeout(queue, timeout)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0
+                }
+                None => changed.wait(queue).unwrap_or_else(|e| e.into_inner()),
+            };
+        }
+    }
+
+    /// Returns the earliest deadline in the queue.
+    #[must_use]
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.lock()
+            .items
+            .keys()
+            .next()
+            .map(|&(deadline, _)| deadline)
+    }
+
+    /// Returns the number of items in the queue, expired or not.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().items.len()
+    }
+
+    /// Returns `true` if the queue holds no items.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lock().items.is_empty()
+    }
+
+    fn pop_if_expired(queue: &mut Queue<T>, now: Instant) -> Option<T> {
+        let entry = queue.items.first_entry()?;
+        if entry.key().0 > now {
+            return None;
+        }
+        Some(entry.remove())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Queue<T>> {
+        self.inner.lock()
+    }
+}
+
+impl<T> Default for DelayQueue<T> {
+    fn default() -> DelayQueue<T> {
+        DelayQueue::new()
+    }
+}
+
+impl<T> fmt::Debug for DelayQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DelayQueue")
+            .field("len", &self.len())
+            .field("next_deadline", &self.next_deadline())
+            .finish()
+    }
+}
+
+// Controls an item through its `TimerHandle`. The key changes when the item
+// is moved to another deadline.
+struct ItemControl<T> {
+    inner: Weak<Inner<T>>,
+    key: Mutex<Key>,
+}
+
+impl<T> ItemControl<T> {
+    fn key(&self) -> MutexGuard<'_, Key> {
+        self.key.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<T: Send> TimerControl for ItemControl<T> {
+    fn cancel(&self) -> bool {
+        let key = self.key();
+        let Some(inner) = self.inner.upgrade() else {
+            return false;
+        };
+        let item = inner.lock().items.remove(&key);
+        item.is_some()
+    }
+
+    fn reset(&self, deadline: Instant) -> bool {
+        let mut key = self.key();
+        let Some(inner) = self.inner.upgrade() else {
+            return false;
+        };
+        let mut queue = inner.lock();
+        let Some(item) = queue.items.remove(&key) else {
+            return false;
+        };
+        *key = queue.insert(deadline, item);
+        inner.changed.notify_all();
+        true
+    }
+
+    fn is_armed(&self) -> bool {
+        let key = self.key();
+        self.inner
+            .upgrade()
+            .is_some_and(|inner| inner.lock().items.contains_key(&key))
+    }
+}
@@ -0,0 +1,151 @@
+//! Detection of clock discontinuities after checkpoint/restore or migration
+use core::time::Duration;
+use std::sync::Mutex;
+
+use crate::{boot_id, sys, BootId, Instant};
+
+// Decreases of the suspended time below this are measurement noise, since the
+// two clocks it is derived from can't be read atomically
+const MAX_NOISE: Duration = Duration::from_millis(100);
+
+// How often the boot identifier is read at most. It comes from a file on
+// Linux, which is too slow to read on every check.
+const BOOT_ID_INTERVAL: Duration = Duration::from_secs(1);
+
+struct State {
+    generation: u64,
+    boot_id: Option<BootId>,
+    // When the boot identifier was read
+    boot_id_read: Instant,
+    last: Instant,
+    suspended: Duration,
+    stale: Option<Stale>,
+}
+
+// The instants the last discontinuity invalidated
+#[derive(Copy, Clone)]
+struct Stale {
+    // When it was noticed, in the new clock
+    since: Instant,
+    // The last reading of the old clock. Instants between `since` and it may
+    // come from either clock.
+    last: Instant,
+}
+
+static STATE: Mutex<Option<State>> = Mutex::new(None);
+
+/// Returns the clock generation, which increases whenever a clock
+/// discontinuity is noticed.
+///
+/// After a checkpoint/restore (e.g. CRIU) or a live migration of a virtual
+/// machine the process may run on a different boot or host, where the clocks
+/// have unrelated values. [`Instant`]s taken before are meaningless then.
+///
+/// A discontinuity is noticed when, since the previous call,
+///
+/// - the [`boot_id`] changed,
+/// - [`Instant::now`] went backwards, or
+/// - the [total suspended time](crate::total_suspended_time) decreased.
+///
+/// The boot identifier is read at most once a second, unless the clock went
+/// backwards.
+///
+/// A restore onto a host whose clocks happen to be ahead, without a boot
+/// identifier, goes unnoticed. Services that keep deadlines for a long time
+/// should call it periodically, or compare generations before trusting stored
+/// instants.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{clock_generation, Duration, Instant};
+///
+/// let generation = clock_generation();
+/// let deadline = Instant::now() + Duration::from_secs(30);
+/// // ...
+/// if clock_generation() != generation {
+///     // the deadline refers to another clock, recompute it
+/// }
+/// ```
+pub fn clock_generation() -> u64 {
+    check().0
+}
+
+// Samples the clocks, bumps the generation on a discontinuity and returns
+// the generation and the instants the last discontinuity invalidated
+fn check() -> (u64, Option<Stale>) {
+    let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+    let suspended = sys::suspended_time();
+    match &mut *state {
+        Some(s) => {
+            // A backward jump of the clock is a reason to look right away
+            let boot_id = if now
+                .checked_duration_since(s.boot_id_read)
+                .is_none_or(|d| d >= BOOT_ID_INTERVAL)
+            {
+                s.boot_id_read = now;
+                boot_id().ok()
+            } else {
+                None
+            };
+            let restored = (boot_id.is_some() && s.boot_id.is_some() && boot_id != s.boot_id)
+                || now < s.last
+                || s.suspended.saturating_sub(suspended) > MAX_NOISE;
+            if restored {
+                s.generation += 1;
+                s.stale = Some(Stale {
+                    since: now,
+                    last: s.last,
+                });
+            }
+            s.boot_id = boot_id.or(s.boot_id);
+            s.last = now;
+            s.suspended = suspended;
+            (s.generation, s.stale)
+        }
+        None => {
+            *state = Some(State {
+                generation: 0,
+                boot_id: boot_id().ok(),
+                boot_id_read: now,
+                last: now,
+                suspended,
+                stale: None,
+            });
+            (0, None)
+        }
+    }
+}
+
+impl Instant {
+    /// Returns `false` if this instant can't have been derived from the
+    /// current clock, e.g. a deadline stored before a checkpoint/restore.
+    ///
+    /// That is the case if it precedes the last discontinuity noticed by
+    /// [`clock_generation`], which this method checks for as well. Since a
+    /// discontinuity may move the clock backwards, instants up to the last
+    /// reading of the old clock are reported invalid even if they were
+    /// derived from the new clock. Instants in the future, such as deadlines
+    /// computed from [`Instant::now`], are valid otherwise.
+    ///
+    /// Instants derived from the old clock that lie past its last reading by
+    /// this crate, e.g. far deadlines, can't be told apart from instants of
+    /// the new clock and are reported valid. So are instants of earlier
+    /// generations, which are only checked against the last discontinuity.
+    /// Compare [`clock_generation`]s to rule them out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::{Duration, Instant};
+    ///
+    /// let deadline = Instant::now() + Duration::from_secs(30);
+    /// assert!(deadline.is_valid());
+    /// ```
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        let (_, stale) = check();
+        stale.is_none_or(|s| *self >= s.since && *self > s.last)
+    }
+}
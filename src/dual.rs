@@ -0,0 +1,136 @@
+//! Instants of the boot and monotonic clocks sampled together
+use core::time::Duration;
+
+use crate::{Instant, MonotonicClock, TypedInstant};
+
+/// An [`Instant`] and a [`MonotonicClock`] instant sampled back-to-back.
+///
+/// The boot clock counts suspended time while the monotonic clock stops, so
+/// comparing both tells how long the system was suspended between two
+/// `DualInstant`s. On platforms that don't distinguish the clocks, or when
+/// [`Instant`] falls back to a monotonic clock, the suspended time is zero.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::DualInstant;
+///
+/// let start = DualInstant::now();
+/// // handle a request
+/// let end = DualInstant::now();
+/// println!(
+///     "request took {:?}, of which {:?} suspended",
+///     end.duration_since(start),
+///     end.suspended_duration_since(start),
+/// );
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct DualInstant {
+    boot: Instant,
+    monotonic: TypedInstant<MonotonicClock>,
+}
+
+impl DualInstant {
+    /// Returns the instants of both clocks corresponding to "now".
+    #[must_use]
+    pub fn now() -> DualInstant {
+        DualInstant {
+            boot: Instant::now(),
+            monotonic: TypedInstant::now(),
+        }
+    }
+
+    /// Returns the instant of the clock counting suspended time.
+    #[must_use]
+    pub fn boot(&self) -> Instant {
+        self.boot
+    }
+
+    /// Returns the instant of the clock that stops while the system is
+    /// suspended.
+    #[must_use]
+    pub fn monotonic(&self) -> TypedInstant<MonotonicClock> {
+        self.monotonic
+    }
+
+    /// Returns the amount of time elapsed from another instant to this one,
+    /// including suspended time, or zero duration if that instant is later
+    /// than this one.
+    #[must_use]
+    pub fn duration_since(&self, earlier: DualInstant) -> Duration {
+        self.boot.duration_since(earlier.boot)
+    }
+
+    /// Returns how much of the time elapsed from another instant to this one
+    /// the system was suspended, or zero duration if that instant is later
+    /// than this one.
+    ///
+    /// The clocks are sampled one after the other, so the result may be off by
+    /// the time between the samples.
+    #[must_use]
+    pub fn suspended_duration_since(&self, earlier: DualInstant) -> Duration {
+        self.duration_since(earlier)
+            .saturating_sub(self.monotonic.duration_since(earlier.monotonic))
+    }
+
+    /// Returns the time elapsed from another instant to this one split into
+    /// the time the system was awake and suspended, or zero durations if that
+    /// instant is later than this one.
+    #[must_use]
+    pub fn split_duration_since(&self, earlier: DualInstant) -> ElapsedSplit {
+        let total = self.duration_since(earlier);
+        let suspended = self.suspended_duration_since(earlier);
+        ElapsedSplit {
+            awake: total - suspended,
+            suspended,
+        }
+    }
+
+    /// Returns the amount of time elapsed since this instant, including
+    /// suspended time.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        DualInstant::now().duration_since(*self)
+    }
+
+    /// Returns how much of the time elapsed since this instant the system was
+    /// suspended.
+    #[must_use]
+    pub fn suspended_elapsed(&self) -> Duration {
+        DualInstant::now().suspended_duration_since(*self)
+    }
+
+    /// Returns the time elapsed since this instant split into the time the
+    /// system was awake and suspended.
+    ///
+    /// ```
+    /// use boot_time::DualInstant;
+    ///
+    /// let start = DualInstant::now();
+    /// // handle a request
+    /// let split = start.elapsed_split();
+    /// println!("processing took {:?}, latency was {:?}", split.awake, split.total());
+    /// ```
+    #[must_use]
+    pub fn elapsed_split(&self) -> ElapsedSplit {
+        DualInstant::now().split_duration_since(*self)
+    }
+}
+
+/// An elapsed time split into the time the system was awake and suspended,
+/// returned by [`DualInstant::elapsed_split`].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct ElapsedSplit {
+    /// The time the system was running.
+    pub awake: Duration,
+    /// The time the system was suspended.
+    pub suspended: Duration,
+}
+
+impl ElapsedSplit {
+    /// Returns the whole elapsed time.
+    #[must_use]
+    pub fn total(&self) -> Duration {
+        self.awake + self.suspended
+    }
+}
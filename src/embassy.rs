@@ -0,0 +1,92 @@
+//! The `embassy-time` driver on the boot clock
+use core::task::Waker;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use embassy_time_driver::{Driver, TICK_HZ};
+
+use crate::{Instant, TimerHandle};
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+struct Entry {
+    id: u64,
+    waker: Waker,
+    at: Instant,
+    handle: TimerHandle,
+}
+
+// Wakes tasks through the thread of `Delay`. A task waiting on several
+// timers is only woken at the earliest, like with the queues of embassy.
+struct BootTimeDriver {
+    entries: Mutex<Vec<Entry>>,
+    next_id: AtomicU64,
+}
+
+embassy_time_driver::time_driver_impl!(static DRIVER: BootTimeDriver = BootTimeDriver {
+    entries: Mutex::new(Vec::new()),
+    next_id: AtomicU64::new(0),
+});
+
+// Ticks count from the first reading, so they don't overflow for the clocks
+// that count from the epoch
+fn epoch() -> Instant {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    *EPOCH.get_or_init(Instant::now)
+}
+
+fn instant(ticks: u64) -> Instant {
+    let nanos = u128::from(ticks) * NANOS_PER_SEC / u128::from(TICK_HZ);
+    u64::try_from(nanos)
+        .ok()
+        .and_then(|nanos| epoch().checked_add(core::time::Duration::from_nanos(nanos)))
+        .unwrap_or_else(Instant::far_future)
+}
+
+impl BootTimeDriver {
+    fn wake(&self, id: u64) {
+        let entry = {
+            let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            let i = entries.iter().position(|entry| entry.id == id);
+            i.map(|i| entries.swap_remove(i))
+        };
+        if let Some(entry) = entry {
+            entry.waker.wake();
+        }
+    }
+}
+
+impl Driver for BootTimeDriver {
+    fn now(&self) -> u64 {
+        let nanos = epoch().elapsed().as_nanos();
+        u64::try_from(nanos * u128::from(TICK_HZ) / NANOS_PER_SEC).unwrap_or(u64::MAX)
+    }
+
+    fn schedule_wake(&self, at: u64, waker: &Waker) {
+        let at = instant(at);
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(i) = entries
+            .iter()
+            .position(|entry| entry.waker.will_wake(waker))
+        {
+            let entry = &mut entries[i];
+            if entry.at <= at {
+                return;
+            }
+            if entry.handle.reset(at) {
+                entry.at = at;
+                return;
+            }
+            // The wake-up is running
+            entries.swap_remove(i);
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let handle = crate::delay::driver().schedule_at(at, move || DRIVER.wake(id));
+        entries.push(Entry {
+            id,
+            waker: waker.clone(),
+            at,
+            handle,
+        });
+    }
+}
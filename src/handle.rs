@@ -0,0 +1,184 @@
+//! Instants of arbitrary POSIX clocks
+use core::cmp::Ordering;
+use core::ops::{Add, Sub};
+use core::time::Duration;
+use std::io;
+
+use crate::sys;
+
+/// A POSIX clock identified by a `clockid_t`.
+///
+/// It reads clocks that [`Instant`](crate::Instant) doesn't cover, e.g. the
+/// CPU clock of another process returned by `clock_getcpuclockid` or a PTP
+/// hardware clock opened as a dynamic clock.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::ClockHandle;
+///
+/// let clock = ClockHandle::from_raw(libc::CLOCK_MONOTONIC);
+/// let start = clock.now().unwrap();
+/// println!("{:?}", clock.now().unwrap().duration_since(start));
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ClockHandle(libc::clockid_t);
+
+impl ClockHandle {
+    /// Creates a handle for the clock id.
+    ///
+    /// Reading a clock id the system doesn't know fails with an error.
+    pub const fn from_raw(clock: libc::clockid_t) -> ClockHandle {
+        ClockHandle(clock)
+    }
+
+    /// Returns the clock id.
+    pub const fn as_raw(self) -> libc::clockid_t {
+        self.0
+    }
+
+    /// Reads the clock.
+    ///
+    /// # Errors
+    ///
+    /// Returns the OS error if the clock can't be read, e.g. because the
+    /// clock id is invalid or the process the CPU clock belongs to exited,
+    /// and an error of kind [`io::ErrorKind::InvalidData`] if the clock is
+    /// before its epoch.
+    pub fn now(self) -> io::Result<ClockInstant> {
+        Ok(ClockInstant {
+            clock: self,
+            t: sys::read_clock(self.0)?,
+        })
+    }
+}
+
+/// A measurement of a [`ClockHandle`].
+///
+/// Instants are only comparable with instants of the same clock: they are
+/// never equal to and not ordered relative to instants of other clocks, and
+/// the methods measuring the time between two instants panic or return `None`
+/// for them.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ClockInstant {
+    clock: ClockHandle,
+    // time since the clock epoch
+    t: Duration,
+}
+
+impl ClockInstant {
+    /// Returns the clock this instant was taken from.
+    pub fn clock(&self) -> ClockHandle {
+        self.clock
+    }
+
+    /// Returns the time elapsed from the clock epoch to this instant.
+    #[must_use]
+    pub fn duration_since_epoch(&self) -> Duration {
+        self.t
+    }
+
+    /// Returns the amount of time elapsed from another instant to this one,
+    /// or zero duration if that instant is later than this one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the instants were taken from different clocks.
+    #[must_use]
+    pub fn duration_since(&self, earlier: ClockInstant) -> Duration {
+        self.saturating_duration_since(earlier)
+    }
+
+    /// Returns the amount of time elapsed from another instant to this one,
+    /// or `None` if that instant is later than this one or was taken from a
+    /// different clock.
+    #[must_use]
+    pub fn checked_duration_since(&self, earlier: ClockInstant) -> Option<Duration> {
+        if self.clock != earlier.clock {
+            return None;
+        }
+        self.t.checked_sub(earlier.t)
+    }
+
+    /// Returns the amount of time elapsed from another instant to this one,
+    /// or zero duration if that instant is later than this one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the instants were taken from different clocks.
+    #[must_use]
+    pub fn saturating_duration_since(&self, earlier: ClockInstant) -> Duration {
+        assert_eq!(
+            self.clock, earlier.clock,
+            "instants of different clocks are not comparable"
+        );
+        self.t.saturating_sub(earlier.t)
+    }
+
+    /// Returns the amount of time elapsed since this instant.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the clock can't be read anymore, see
+    /// [`ClockHandle::now`].
+    pub fn elapsed(&self) -> io::Result<Duration> {
+        Ok(self.clock.now()?.duration_since(*self))
+    }
+
+    /// Returns `Some(t)` where `t` is the time `self + duration` if `t` can be
+    /// represented, `None` otherwise.
+    pub fn checked_add(&self, duration: Duration) -> Option<ClockInstant> {
+        Some(ClockInstant {
+            clock: self.clock,
+            t: self.t.checked_add(duration)?,
+        })
+    }
+
+    /// Returns `Some(t)` where `t` is the time `self - duration` if `t` can be
+    /// represented, `None` otherwise.
+    pub fn checked_sub(&self, duration: Duration) -> Option<ClockInstant> {
+        Some(ClockInstant {
+            clock: self.clock,
+            t: self.t.checked_sub(duration)?,
+        })
+    }
+}
+
+impl PartialOrd for ClockInstant {
+    fn partial_cmp(&self, other: &ClockInstant) -> Option<Ordering> {
+        if self.clock == other.clock {
+            Some(self.t.cmp(&other.t))
+        } else {
+            None
+        }
+    }
+}
+
+impl Add<Duration> for ClockInstant {
+    type Output = ClockInstant;
+
+    fn add(self, other: Duration) -> ClockInstant {
+        self.checked_add(other)
+            .expect("overflow when adding duration to instant")
+    }
+}
+
+impl Sub<Duration> for ClockInstant {
+    type Output = ClockInstant;
+
+    fn sub(self, other: Duration) -> ClockInstant {
+        self.checked_sub(other)
+            .expect("overflow when subtracting duration from instant")
+    }
+}
+
+impl Sub<ClockInstant> for ClockInstant {
+    type Output = Duration;
+
+    /// # Panics
+    ///
+    /// Panics if the instants were taken from different clocks.
+    fn sub(self, other: ClockInstant) -> Duration {
+        self.duration_since(other)
+    }
+}
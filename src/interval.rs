@@ -0,0 +1,180 @@
+//! Blocking periodic ticks on the boot clock
+use core::time::Duration;
+
+use crate::Instant;
+
+/// How a periodic timer catches up with ticks it missed, because the system
+/// was suspended or the ticks weren't handled in time.
+///
+/// It is shared by [`Interval`], [`PeriodicTimer`](crate::PeriodicTimer) and,
+/// on Linux and Android, `PosixTimer`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[non_exhaustive]
+pub enum MissedTickBehavior {
+    /// Returns all missed ticks immediately, then continues at the original
+    /// schedule.
+    #[default]
+    Burst,
+    /// Returns one tick immediately and schedules the following ones a
+    /// period after it, shifting the schedule.
+    Delay,
+    /// Returns one tick immediately and drops the others, continuing at the
+    /// next tick of the original schedule.
+    Skip,
+}
+
+/// Returns an [`Interval`] whose first tick completes immediately and the
+/// following ones every `period`.
+///
+/// # Panics
+///
+/// Panics if `period` is zero.
+///
+/// # Examples
+///
+/// A daemon polling every 5 minutes, which polls once rather than a dozen
+/// times after the laptop slept for an hour:
+///
+/// ```no_run
+/// use boot_time::{Duration, MissedTickBehavior};
+///
+/// let mut interval = boot_time::interval(Duration::from_secs(300));
+/// interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+/// loop {
+///     interval.tick();
+///     // poll
+/// }
+/// ```
+#[must_use]
+pub fn interval(period: Duration) -> Interval {
+    interval_at(Instant::now(), period)
+}
+
+/// Returns an [`Interval`] whose first tick completes at `start` and the
+/// following ones every `period`.
+///
+/// # Panics
+///
+/// Panics if `period` is zero.
+#[must_use]
+pub fn interval_at(start: Instant, period: Duration) -> Interval {
+    assert!(!period.is_zero(), "`period` must be non-zero");
+    Interval {
+        next: start,
+        period,
+        missed_tick_behavior: MissedTickBehavior::default(),
+        missed_ticks: 0,
+    }
+}
+
+/// Blocking ticks at `start + n * period` on the clock of [`Instant`],
+/// created by [`interval`] or [`interval_at`].
+///
+/// Each tick is scheduled from the start rather than from the previous tick,
+/// so the phase doesn't drift however late `tick` is called. The ticks count
+/// the time the system was suspended, so after a long suspend some ticks are
+/// due at once. [`MissedTickBehavior`] selects how
+/// they are returned.
+///
+/// It is an endless iterator of the scheduled tick instants.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::Duration;
+///
+/// let ticks: Vec<_> = boot_time::interval(Duration::from_millis(2)).take(3).collect();
+/// assert_eq!(ticks[2] - ticks[0], Duration::from_millis(4));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Interval {
+    next: Instant,
+    period: Duration,
+    missed_tick_behavior: MissedTickBehavior,
+    missed_ticks: u64,
+}
+
+impl Interval {
+    /// Blocks until the next tick and returns the instant it was scheduled
+    /// at.
+    pub fn tick(&mut self) -> Instant {
+        crate::sleep_until(self.next);
+        self.advance(Instant::now())
+    }
+
+    // Completes the due tick at `now` and schedules the next one
+    pub(crate) fn advance(&mut self, now: Instant) -> Instant {
+        let tick = self.next;
+        self.next = self.next_after(tick, now);
+        let late = now.saturating_duration_since(tick).as_nanos() / self.period.as_nanos();
+        self.missed_ticks = u64::try_from(late).unwrap_or(u64::MAX);
+        tick
+    }
+
+    fn next_after(&self, tick: Instant, now: Instant) -> Instant {
+        let next = tick
+            .checked_add(self.period)
+            .unwrap_or_else(Instant::far_future);
+        if now <= next {
+            return next;
+        }
+        match self.missed_tick_behavior {
+            MissedTickBehavior::Burst => next,
+            MissedTickBehavior::Delay => now.saturating_add(self.period),
+            MissedTickBehavior::Skip => {
+                let missed = (now - tick).as_nanos() / self.period.as_nanos();
+                u32::try_from(missed + 1)
+                    .ok()
+                    .and_then(|n| self.period.checked_mul(n))
+                    .and_then(|d| tick.checked_add(d))
+                    .unwrap_or_else(|| now.saturating_add(self.period))
+            }
+        }
+    }
+
+    /// Reschedules the next tick a period from now.
+    pub fn reset(&mut self) {
+        self.next = Instant::now().saturating_add(self.period);
+    }
+
+    /// Returns how many further ticks were already due when the last tick
+    /// was returned, e.g. because the system was suspended.
+    ///
+    /// With [`MissedTickBehavior::Burst`] they are returned next, otherwise
+    /// they were dropped.
+    #[must_use]
+    pub fn missed_ticks(&self) -> u64 {
+        self.missed_ticks
+    }
+
+    /// Returns the instant of the next tick.
+    #[must_use]
+    pub fn next_tick(&self) -> Instant {
+        self.next
+    }
+
+    /// Returns the period of the interval.
+    #[must_use]
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Returns how missed ticks are caught up with.
+    #[must_use]
+    pub fn missed_tick_behavior(&self) -> MissedTickBehavior {
+        self.missed_tick_behavior
+    }
+
+    /// Sets how missed ticks are caught up with.
+    pub fn set_missed_tick_behavior(&mut self, behavior: MissedTickBehavior) {
+        self.missed_tick_behavior = behavior;
+    }
+}
+
+impl Iterator for Interval {
+    type Item = Instant;
+
+    fn next(&mut self) -> Option<Instant> {
+        Some(self.tick())
+    }
+}
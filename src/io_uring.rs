@@ -0,0 +1,182 @@
+//! Boot clock timeouts for io_uring
+//!
+//! io_uring measures timeouts with `CLOCK_MONOTONIC` unless a submission
+//! sets `IORING_TIMEOUT_BOOTTIME`, available since Linux 5.15. A
+//! [`UringTimeout`] holds a deadline as the absolute timespec and flags on
+//! the clock of [`Instant`], and builds the timeout entries of the
+//! `io-uring` crate from it, so servers built on it, or on runtimes that
+//! accept its entries, can attach suspend-aware deadlines to submissions.
+//!
+//! Adapters for `tokio-uring` and `monoio` are out of scope: neither exposes
+//! a way to submit raw entries, and their own timeouts are measured on
+//! `CLOCK_MONOTONIC`.
+use core::fmt;
+use core::marker::PhantomData;
+use core::time::Duration;
+
+use ::io_uring::types::{TimeoutFlags, Timespec};
+use ::io_uring::{opcode, squeue};
+
+use crate::timerfd::{deadline_timespec, instant_clock_id};
+use crate::{Deadline, Instant};
+
+/// A deadline in the form of io_uring timeouts.
+///
+/// The entries point to the timespec of the `UringTimeout`, which the kernel
+/// reads when the entry is submitted, so they borrow it. Pushing an entry
+/// copies it into the submission queue, and the `UringTimeout` has to stay
+/// alive and in place until the queue is submitted.
+///
+/// # Examples
+///
+/// A read that is cancelled if it doesn't complete within 5 seconds,
+/// including time the system spends suspended:
+///
+/// ```no_run
+/// use boot_time::io_uring::UringTimeout;
+/// use boot_time::Duration;
+/// use io_uring::{opcode, squeue, types, IoUring};
+/// use std::os::fd::AsRawFd;
+///
+/// let mut ring = IoUring::new(8)?;
+/// let file = std::fs::File::open("/dev/ttyS0")?;
+/// let mut buf = [0; 64];
+/// let timeout = UringTimeout::after(Duration::from_secs(5));
+/// let read = opcode::Read::new(types::Fd(file.as_raw_fd()), buf.as_mut_ptr(), 64)
+///     .build()
+///     .flags(squeue::Flags::IO_LINK)
+///     .user_data(1);
+/// let link_timeout = timeout.link_timeout().user_data(2);
+/// unsafe {
+///     ring.submission().push(&read).unwrap();
+///     ring.submission().push(link_timeout.entry()).unwrap();
+/// }
+/// ring.submit_and_wait(2)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct UringTimeout {
+    deadline: Instant,
+    timespec: Timespec,
+    flags: TimeoutFlags,
+}
+
+impl UringTimeout {
+    /// Returns the timeout for `deadline`.
+    #[must_use]
+    pub fn at(deadline: impl Into<Deadline>) -> UringTimeout {
+        let deadline = deadline.into().instant();
+        let t = deadline_timespec(deadline);
+        let timespec = Timespec::new().sec(t.tv_sec as u64).nsec(t.tv_nsec as u32);
+        // Absolute timeouts without a clock flag are on `CLOCK_MONOTONIC`
+        let flags = if instant_clock_id() == libc::CLOCK_BOOTTIME {
+            TimeoutFlags::ABS | TimeoutFlags::BOOTTIME
+        } else {
+            TimeoutFlags::ABS
+        };
+        UringTimeout {
+            deadline,
+            timespec,
+            flags,
+        }
+    }
+
+    /// Returns the timeout for a deadline `duration` from now.
+    #[must_use]
+    pub fn after(duration: Duration) -> UringTimeout {
+        UringTimeout::at(Deadline::from_timeout(duration))
+    }
+
+    /// Returns the deadline of the timeout.
+    #[must_use]
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Returns the absolute time of the deadline on the clock selected by
+    /// [`UringTimeout::flags`].
+    #[must_use]
+    pub fn timespec(&self) -> &Timespec {
+        &self.timespec
+    }
+
+    /// Returns `IORING_TIMEOUT_ABS`, with `IORING_TIMEOUT_BOOTTIME` unless
+    /// [`Instant`] fell back to `CLOCK_MONOTONIC`.
+    #[must_use]
+    pub fn flags(&self) -> TimeoutFlags {
+        self.flags
+    }
+
+    /// Returns an `IORING_OP_TIMEOUT` that completes with `-ETIME` at the
+    /// deadline.
+    #[must_use]
+    pub fn timeout(&self) -> UringEntry<'_> {
+        UringEntry::new(
+            opcode::Timeout::new(&self.timespec)
+                .flags(self.flags)
+                .build(),
+        )
+    }
+
+    /// Returns an `IORING_OP_LINK_TIMEOUT` that cancels the previous,
+    /// linked submission at the deadline.
+    #[must_use]
+    pub fn link_timeout(&self) -> UringEntry<'_> {
+        UringEntry::new(
+            opcode::LinkTimeout::new(&self.timespec)
+                .flags(self.flags)
+                .build(),
+        )
+    }
+
+    /// Returns an entry that moves the timeout submitted with `user_data` to
+    /// this deadline.
+    #[must_use]
+    pub fn update(&self, user_data: u64) -> UringEntry<'_> {
+        UringEntry::new(
+            opcode::TimeoutUpdate::new(user_data, &self.timespec)
+                .flags(self.flags)
+                .build(),
+        )
+    }
+}
+
+/// A submission entry built by a [`UringTimeout`], which it borrows.
+#[derive(Clone)]
+pub struct UringEntry<'a> {
+    entry: squeue::Entry,
+    _timeout: PhantomData<&'a UringTimeout>,
+}
+
+impl UringEntry<'_> {
+    fn new(entry: squeue::Entry) -> Self {
+        UringEntry {
+            entry,
+            _timeout: PhantomData,
+        }
+    }
+
+    /// Sets the submission flags, e.g. `IO_LINK`.
+    #[must_use]
+    pub fn flags(self, flags: squeue::Flags) -> Self {
+        UringEntry::new(self.entry.flags(flags))
+    }
+
+    /// Sets the user data reported by the completion.
+    #[must_use]
+    pub fn user_data(self, user_data: u64) -> Self {
+        UringEntry::new(self.entry.user_data(user_data))
+    }
+
+    /// Returns the entry to push to a submission queue.
+    #[must_use]
+    pub fn entry(&self) -> &squeue::Entry {
+        &self.entry
+    }
+}
+
+impl fmt::Debug for UringEntry<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.entry.fmt(f)
+    }
+}
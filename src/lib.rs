@@ -1,12 +1,51 @@
 //! Temporal quantification that takes into account the time a system spent suspended.
 //!
-//! Note: Some systems like FreeBSD, DragonFlyBSD, NetBSD, AIX, Fuchsia,
-//! Emscripten don't support CLOCK_BOOTIME.
+//! Note: Some systems like FreeBSD, DragonFlyBSD, NetBSD, AIX, Emscripten
+//! don't support CLOCK_BOOTIME.
 //!
-//! For compatibility CLOCK_MONOTONIC is used as a fallback.
+//! For compatibility CLOCK_MONOTONIC is used as a fallback. [`SUSPEND_AWARE`] is
+//! `false` on such platforms. Linux kernels older than 2.6.39 and containers
+//! that reject CLOCK_BOOTTIME fall back to CLOCK_MONOTONIC at runtime as well.
 //!
-//! Windows QueryPerformanceCounter includes suspended time.  So for Windows and
-//! unsupported platforms `std::time::Instant` is just reexported.
+//! With the `linux-raw` feature Linux and Android on x86_64, aarch64 and
+//! riscv64 read the clock through the vDSO or a raw system call instead of
+//! libc. The crate makes no calls into libc there then, and leaves out the
+//! timers and power events that need it unless the `linux-libc` feature is
+//! enabled as well.
+//!
+//! On Apple platforms `mach_continuous_time` is used. Systems older than
+//! macOS 10.12, iOS 10, tvOS 10 and watchOS 3 lack it and fall back to
+//! `mach_absolute_time`, which stops while the system is asleep.
+//!
+//! The `force-monotonic` feature makes UNIX platforms use CLOCK_MONOTONIC
+//! (`mach_absolute_time` on Apple platforms) crate-wide, for measurements
+//! that must not include suspended time. [`SUSPEND_AWARE`] is `false` then.
+//! [`Config`] sets the same preference at runtime. With
+//! [`FallbackPolicy::Strict`] it reports at startup when no clock counting
+//! suspended time is available.
+//!
+//! On Windows 10 and later the interrupt time (`QueryInterruptTimePrecise`)
+//! is used, which includes suspended time. On Windows 7 and 8
+//! `QueryPerformanceCounter` is corrected by the suspended time derived from
+//! `GetTickCount64` and `QueryUnbiasedInterruptTime`, which is precise to a timer
+//! interrupt, unless the system uses Modern Standby, during which
+//! `QueryPerformanceCounter` keeps counting. Plain `QueryPerformanceCounter`
+//! is the last resort.
+//!
+//! In browsers (`wasm32-unknown-unknown` with the `web` feature)
+//! `performance.now()` is used. Its precision is clamped by the browser.
+//!
+//! In Fortanix SGX enclaves the time is provided by the untrusted host through
+//! the `insecure_time` usercall. The host may report arbitrary values, so only
+//! monotonicity is enforced and elapsed times must not be trusted.
+//!
+//! For unsupported platforms `std::time::Instant` is just reexported. With the
+//! `custom` feature they use the clock registered with
+//! `register_custom_clock!` instead.
+//!
+//! The [`power`] module notifies about system suspend and resume. With the
+//! `android-wakelock` and `android-alarm` features the `android` module keeps
+//! Android devices awake until a deadline passes and wakes them for it.
 //!
 //! # Examples
 //!
@@ -26,15 +65,188 @@
 
 pub use core::time::Duration;
 
+mod config;
+#[cfg(feature = "custom")]
+mod custom;
+mod source;
+
+pub use self::config::{Config, FallbackPolicy, InitError};
+pub use self::source::ClockSource;
+
 cfg_if::cfg_if! {
-    if #[cfg(unix)] {
+    if #[cfg(any(
+        unix,
+        windows,
+        target_os = "wasi",
+        all(target_vendor = "fortanix", target_env = "sgx"),
+        target_os = "uefi",
+        target_os = "xous",
+        all(target_arch = "wasm32", target_os = "unknown", feature = "web"),
+        feature = "custom",
+    ))] {
         mod time;
         mod sys;
         mod sys_common;
+        mod signed;
+        mod clock;
+        #[cfg(any(
+            windows,
+            all(unix, not(any(target_env = "newlib", target_os = "redox", target_os = "nuttx"))),
+        ))]
+        mod cpu;
+        mod dual;
+        mod boot_id;
+        mod discontinuity;
+        mod deadline;
+        mod countdown;
+        mod timer_handle;
+        mod sleep;
+        mod slack;
+        mod interval;
+        mod delay_queue;
+        mod periodic;
+        mod scheduler;
+        mod delay;
+        #[cfg(feature = "embassy-time-driver")]
+        mod embassy;
+        mod alarm;
+        #[cfg(all(
+            any(target_os = "linux", target_os = "android"),
+            any(not(feature = "linux-raw"), feature = "linux-libc"),
+        ))]
+        mod posix_timer;
+        #[cfg(all(
+            any(target_os = "linux", target_os = "android"),
+            any(not(feature = "linux-raw"), feature = "linux-libc"),
+        ))]
+        mod timerfd;
+        pub mod power;
+        #[cfg(all(
+            target_os = "android",
+            any(feature = "android-wakelock", feature = "android-alarm"),
+        ))]
+        pub mod android;
+        #[cfg(all(feature = "tokio", any(target_os = "linux", target_os = "android")))]
+        pub mod tokio;
+        #[cfg(all(feature = "async-io", any(target_os = "linux", target_os = "android")))]
+        pub mod async_io;
+        #[cfg(all(feature = "async-std", any(target_os = "linux", target_os = "android")))]
+        pub mod async_std;
+        #[cfg(all(
+            feature = "io-uring",
+            target_os = "linux",
+            any(
+                target_arch = "x86_64",
+                target_arch = "aarch64",
+                target_arch = "riscv64",
+                target_arch = "loongarch64",
+                target_arch = "powerpc64",
+            ),
+        ))]
+        pub mod io_uring;
+        #[cfg(all(unix, feature = "libc"))]
+        mod handle;
+        #[cfg(target_has_atomic = "64")]
+        mod atomic;
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        mod tai;
 
         pub use self::time::Instant;
+        pub use self::signed::SignedDuration;
+        pub use self::clock::{
+            BootClock, Clock, MonotonicClock, RawInstant, RawMonotonicClock, TypedInstant,
+        };
+        #[cfg(any(
+            windows,
+            all(unix, not(any(target_env = "newlib", target_os = "redox", target_os = "nuttx"))),
+        ))]
+        pub use self::cpu::{ProcessCpuClock, ProcessCpuInstant, ThreadCpuClock, ThreadCpuInstant};
+        pub use self::dual::{DualInstant, ElapsedSplit};
+        pub use self::boot_id::{boot_id, BootId};
+        pub use self::discontinuity::clock_generation;
+        pub use self::deadline::Deadline;
+        pub use self::countdown::Countdown;
+        pub use self::timer_handle::TimerHandle;
+        pub use self::sleep::{sleep, sleep_until, sleep_until_precise, SpinSleeper};
+        pub use self::slack::{set_thread_timer_slack, thread_timer_slack};
+        pub use self::interval::{interval, interval_at, Interval, MissedTickBehavior};
+        pub use self::delay_queue::DelayQueue;
+        pub use self::periodic::PeriodicTimer;
+        pub use self::scheduler::Scheduler;
+        pub use self::delay::Delay;
+        pub use self::alarm::{sleep_until_wall, WakeAlarm, WakeAlarmWarning};
+        #[cfg(all(
+            any(target_os = "linux", target_os = "android"),
+            any(not(feature = "linux-raw"), feature = "linux-libc"),
+        ))]
+        pub use self::posix_timer::PosixTimer;
+        #[cfg(all(
+            any(target_os = "linux", target_os = "android"),
+            any(not(feature = "linux-raw"), feature = "linux-libc"),
+        ))]
+        pub use self::timerfd::{TimerClock, TimerFd};
+        #[cfg(all(unix, feature = "libc"))]
+        pub use self::handle::{ClockHandle, ClockInstant};
+        #[cfg(target_has_atomic = "64")]
+        pub use self::atomic::AtomicInstant;
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        pub use self::tai::TaiTime;
+
+        /// `true` if [`Instant`] is expected to count the time the system was
+        /// suspended on the target platform.
+        ///
+        /// It is `false` on platforms that fall back to a clock which stops
+        /// while the system is suspended, e.g. `CLOCK_MONOTONIC` on FreeBSD,
+        /// NetBSD or AIX.
+        ///
+        /// ```
+        /// if !boot_time::SUSPEND_AWARE {
+        ///     eprintln!("timeouts don't account for suspended time");
+        /// }
+        /// ```
+        pub const SUSPEND_AWARE: bool = sys::SUSPEND_AWARE;
+
+        /// Returns the clock [`Instant`] reads on the running system.
+        ///
+        /// The clock is selected once, on first use.
+        pub fn clock_source() -> ClockSource {
+            sys::clock_source()
+        }
+
+        /// Returns the total time the system spent suspended since boot.
+        ///
+        /// It is the difference of a clock that counts suspended time and one
+        /// that doesn't, e.g. `CLOCK_BOOTTIME` and `CLOCK_MONOTONIC`. The two
+        /// are read back to back, so the result may jitter by the time between
+        /// the reads, or by a timer interrupt on Windows.
+        ///
+        /// It is zero on platforms without a clock that counts suspended
+        /// time, e.g. FreeBSD or NetBSD. It doesn't depend on the clock
+        /// [`Instant`] reads, so it is available with `force-monotonic` too.
+        ///
+        /// ```
+        /// let suspended = boot_time::total_suspended_time();
+        /// println!("slept for {} seconds since boot", suspended.as_secs());
+        /// ```
+        pub fn total_suspended_time() -> Duration {
+            sys::suspended_time()
+        }
     } else {
         pub use std::time::Instant;
+
+        /// `true` if [`Instant`] is expected to count the time the system was
+        /// suspended on the target platform.
+        pub const SUSPEND_AWARE: bool = false;
+
+        /// Returns the clock [`Instant`] reads on the running system.
+        pub fn clock_source() -> ClockSource {
+            ClockSource::StdFallback
+        }
+
+        /// Returns the total time the system spent suspended since boot.
+        pub fn total_suspended_time() -> Duration {
+            Duration::ZERO
+        }
     }
 }
 
@@ -0,0 +1,141 @@
+//! Periodic callbacks on the boot clock
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::sleep::MAX_CHUNK;
+use crate::{Instant, Interval, MissedTickBehavior};
+
+#[derive(Default, Debug)]
+struct Stop {
+    stopped: Mutex<bool>,
+    changed: Condvar,
+    missed_ticks: AtomicU64,
+}
+
+/// Calls a closure at the ticks of an [`Interval`] on a background thread.
+///
+/// Like the blocking [`Interval::tick`], ticks are scheduled at
+/// `start + n * period` rather than a period after the previous call, so the
+/// phase doesn't drift however long the callback takes. Ticks missed while
+/// the system was suspended, or while the callback ran, are handled by the
+/// interval's [`MissedTickBehavior`](crate::MissedTickBehavior).
+///
+/// The thread stops when the timer is stopped or dropped, after the running
+/// callback returns.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{Duration, PeriodicTimer};
+/// use std::sync::mpsc;
+///
+/// let (tx, rx) = mpsc::channel();
+/// let interval = boot_time::interval(Duration::from_millis(5));
+/// let timer = PeriodicTimer::spawn(interval, move |tick| {
+///     let _ = tx.send(tick);
+/// })?;
+/// let first = rx.recv().unwrap();
+/// let second = rx.recv().unwrap();
+/// assert_eq!(second - first, Duration::from_millis(5));
+/// timer.stop();
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct PeriodicTimer {
+    stop: Arc<Stop>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl PeriodicTimer {
+    /// Starts a thread calling `callback` with the scheduled instant of each
+    /// tick of `interval`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the thread can't be spawned.
+    pub fn spawn<F>(interval: Interval, callback: F) -> io::Result<PeriodicTimer>
+    where
+        F: FnMut(Instant) + Send + 'static,
+    {
+        let stop = Arc::new(Stop::default());
+        let thread = thread::Builder::new()
+            .name("boot-time-periodic".to_owned())
+            .spawn({
+                let stop = Arc::clone(&stop);
+                move || run(&stop, interval, callback)
+            })?;
+        Ok(PeriodicTimer {
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Returns how many ticks were missed in total, see
+    /// [`Interval::missed_ticks`].
+    #[must_use]
+    pub fn missed_ticks(&self) -> u64 {
+        self.stop.missed_ticks.load(Ordering::Relaxed)
+    }
+
+    /// Stops the timer and waits for a running callback to return.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        *self.stop.stopped.lock().unwrap_or_else(|e| e.into_inner()) = true;
+        self.stop.changed.notify_all();
+        if let Some(thread) = self.thread.take() {
+            // The callback may drop the timer itself
+            if thread.thread().id() != thread::current().id() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+impl Drop for PeriodicTimer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn run<F: FnMut(Instant)>(stop: &Stop, mut interval: Interval, mut callback: F) {
+    // Missed ticks already counted that a burst still has to return
+    let mut pending = 0u64;
+    let mut stopped = stop.stopped.lock().unwrap_or_else(|e| e.into_inner());
+    while !*stopped {
+        let now = Instant::now();
+        let next = interval.next_tick();
+        if now >= next {
+            drop(stopped);
+            let tick = interval.advance(now);
+            // A burst tick is one of the pending ones, the rest are newly late
+            let missed = interval.missed_ticks();
+            stop.missed_ticks.fetch_add(
+                missed.saturating_sub(pending.saturating_sub(1)),
+                Ordering::Relaxed,
+            );
+            pending = match interval.missed_tick_behavior() {
+                MissedTickBehavior::Burst => missed,
+                _ => 0,
+            };
+            callback(tick);
+            stopped = stop.stopped.lock().unwrap_or_else(|e| e.into_inner());
+            continue;
+        }
+        // Condition variables measure with a clock that may stop during
+        // suspend
+        let mut timeout = next - now;
+        if crate::clock_source().is_suspend_aware() {
+            timeout = timeout.min(MAX_CHUNK);
+        }
+        stopped = stop
+            .changed
+            .wait_timeout(stopped, timeout)
+            .unwrap_or_else(|e| e.into_inner())
+            .0;
+    }
+}
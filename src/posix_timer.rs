@@ -0,0 +1,316 @@
+//! Kernel timers on the clock of `Instant`
+use core::fmt;
+use core::time::Duration;
+use std::ffi::{c_int, c_void};
+use std::io;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::timerfd::{deadline_timespec, duration_timespec, instant_clock_id};
+use crate::{Deadline, Instant, MissedTickBehavior};
+
+const SIGEV_SIGNAL: c_int = 0;
+const SIGEV_THREAD: c_int = 2;
+
+type Callback = Arc<Registration>;
+
+struct Registration {
+    callback: Box<dyn Fn() + Send + Sync>,
+    // Set once the timer exists and reset before it is deleted. The lock is
+    // held while a callback thread uses the id, so it can't go stale.
+    timer: Mutex<TimerId>,
+    // The behavior and the period of the timer
+    policy: Mutex<(MissedTickBehavior, Duration)>,
+}
+
+struct TimerId(libc::timer_t);
+
+// Timer ids are valid in every thread of the process
+unsafe impl Send for TimerId {}
+
+impl Registration {
+    fn timer(&self) -> MutexGuard<'_, TimerId> {
+        self.timer.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// `struct sigevent` with the members for SIGEV_THREAD, which the `libc` crate
+// hides in a private union. It is 64 bytes on Linux and Android.
+#[repr(C)]
+struct SigEvent {
+    value: libc::sigval,
+    signo: c_int,
+    notify: c_int,
+    function: Option<extern "C" fn(libc::sigval)>,
+    attributes: *mut libc::pthread_attr_t,
+    _pad: [u8; 64 - 3 * mem::size_of::<usize>() - 2 * mem::size_of::<c_int>()],
+}
+
+// Callbacks are looked up by id rather than passed by pointer, since a
+// callback thread may still start after the timer was deleted
+static CALLBACKS: Mutex<Vec<(usize, Callback)>> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+fn callbacks() -> MutexGuard<'static, Vec<(usize, Callback)>> {
+    CALLBACKS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+extern "C" fn trampoline(value: libc::sigval) {
+    let id = value.sival_ptr as usize;
+    let callback = callbacks()
+        .iter()
+        .find(|(i, _)| *i == id)
+        .map(|(_, callback)| callback.clone());
+    let Some(reg) = callback else {
+        return;
+    };
+    (reg.callback)();
+    let overruns = {
+        let timer = reg.timer();
+        // The timer was deleted while the callback ran
+        if timer.0.is_null() {
+            return;
+        }
+        let overruns = unsafe { libc::timer_getoverrun(timer.0) };
+        if overruns <= 0 {
+            return;
+        }
+        let (behavior, period) = *reg.policy.lock().unwrap_or_else(|e| e.into_inner());
+        match behavior {
+            MissedTickBehavior::Burst => overruns,
+            // Restarts the period from now
+            MissedTickBehavior::Delay if !period.is_zero() => {
+                let mut spec: libc::itimerspec = unsafe { mem::zeroed() };
+                spec.it_value = duration_timespec(period);
+                spec.it_interval = duration_timespec(period);
+                unsafe { libc::timer_settime(timer.0, 0, &spec, ptr::null_mut()) };
+                return;
+            }
+            _ => return,
+        }
+    };
+    // The lock is released, the callback may drop the timer
+    for _ in 0..overruns {
+        (reg.callback)();
+    }
+}
+
+/// A POSIX per-process timer (`timer_create`) on the clock of [`Instant`].
+///
+/// The kernel tracks the deadline, so it fires on time after resume even
+/// though no thread sleeps. It is created with the clock [`Instant`] reads,
+/// `CLOCK_BOOTTIME` unless it fell back to `CLOCK_MONOTONIC`.
+///
+/// Expirations are delivered either as a signal or by calling a callback on
+/// a thread the C library starts (`SIGEV_THREAD`). If an expiration happens
+/// before the previous one was delivered, e.g. while the system was
+/// suspended, the kernel counts it as an overrun, see
+/// [`PosixTimer::overrun_count`]. Callback timers handle overruns according
+/// to their [`MissedTickBehavior`], [`Skip`](MissedTickBehavior::Skip) by
+/// default.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{Duration, Instant, PosixTimer};
+/// use std::sync::mpsc;
+///
+/// let (tx, rx) = mpsc::channel();
+/// let timer = PosixTimer::with_callback(move || {
+///     let _ = tx.send(());
+/// })?;
+/// timer.set(Instant::now() + Duration::from_millis(10))?;
+/// rx.recv().unwrap();
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct PosixTimer {
+    id: libc::timer_t,
+    callback: Option<(usize, Callback)>,
+}
+
+// Timer ids are valid in every thread of the process
+unsafe impl Send for PosixTimer {}
+unsafe impl Sync for PosixTimer {}
+
+impl PosixTimer {
+    /// Creates a disarmed timer that sends `signal` to the process on every
+    /// expiration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the signal is invalid or the process has too many
+    /// timers.
+    pub fn with_signal(signal: c_int) -> io::Result<PosixTimer> {
+        let mut event = sig_event(SIGEV_SIGNAL);
+        event.signo = signal;
+        PosixTimer::create(event, None)
+    }
+
+    /// Creates a disarmed timer that calls `callback` on every expiration.
+    ///
+    /// The callback runs on a thread started by the C library and may run
+    /// concurrently with itself if expirations follow each other closely.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the process has too many timers.
+    pub fn with_callback<F>(callback: F) -> io::Result<PosixTimer>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let reg = Arc::new(Registration {
+            callback: Box::new(callback),
+            timer: Mutex::new(TimerId(ptr::null_mut())),
+            policy: Mutex::new((MissedTickBehavior::Skip, Duration::ZERO)),
+        });
+        callbacks().push((id, Arc::clone(&reg)));
+        let mut event = sig_event(SIGEV_THREAD);
+        event.value.sival_ptr = id as *mut c_void;
+        event.function = Some(trampoline);
+        let timer =
+            PosixTimer::create(event, Some((id, reg))).inspect_err(|_| remove_callback(id))?;
+        if let Some((_, reg)) = &timer.callback {
+            reg.timer().0 = timer.id;
+        }
+        Ok(timer)
+    }
+
+    fn create(mut event: SigEvent, callback: Option<(usize, Callback)>) -> io::Result<PosixTimer> {
+        let mut id = ptr::null_mut();
+        let r = unsafe {
+            libc::timer_create(
+                instant_clock_id(),
+                (&mut event as *mut SigEvent).cast(),
+                &mut id,
+            )
+        };
+        if r != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(PosixTimer { id, callback })
+    }
+
+    /// Arms the timer to fire once at `deadline`, replacing the previous
+    /// setting. A deadline in the past fires immediately.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the kernel rejects the deadline.
+    pub fn set(&self, deadline: impl Into<Deadline>) -> io::Result<()> {
+        self.settime(deadline.into().instant(), Duration::ZERO)
+    }
+
+    /// Arms the timer to fire at `first` and then every `period`, replacing
+    /// the previous setting.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the kernel rejects the deadline or the period.
+    pub fn set_periodic(&self, first: Instant, period: Duration) -> io::Result<()> {
+        self.settime(first, period)
+    }
+
+    /// Sets how a callback timer handles expirations it missed, because the
+    /// previous callback was still running or the system was suspended.
+    ///
+    /// [`Burst`](MissedTickBehavior::Burst) calls the callback once per
+    /// missed expiration, [`Delay`](MissedTickBehavior::Delay) restarts a
+    /// periodic timer a period from the late callback, and
+    /// [`Skip`](MissedTickBehavior::Skip), the default, keeps the schedule
+    /// without catching up. Timers delivering signals always skip.
+    pub fn set_missed_tick_behavior(&self, behavior: MissedTickBehavior) {
+        if let Some((_, reg)) = &self.callback {
+            reg.policy.lock().unwrap_or_else(|e| e.into_inner()).0 = behavior;
+        }
+    }
+
+    /// Disarms the timer.
+    ///
+    /// # Errors
+    ///
+    /// Doesn't fail for a valid timer.
+    pub fn cancel(&self) -> io::Result<()> {
+        let spec: libc::itimerspec = unsafe { mem::zeroed() };
+        self.settime_spec(0, &spec)
+    }
+
+    /// Returns how many expirations were missed before the last delivered
+    /// one, because the previous signal or callback was still pending.
+    ///
+    /// # Errors
+    ///
+    /// Doesn't fail for a valid timer.
+    pub fn overrun_count(&self) -> io::Result<u32> {
+        let r = unsafe { libc::timer_getoverrun(self.id) };
+        if r < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(r as u32)
+    }
+
+    fn settime(&self, deadline: Instant, period: Duration) -> io::Result<()> {
+        if let Some((_, reg)) = &self.callback {
+            reg.policy.lock().unwrap_or_else(|e| e.into_inner()).1 = period;
+        }
+        let mut spec: libc::itimerspec = unsafe { mem::zeroed() };
+        spec.it_value = deadline_timespec(deadline);
+        spec.it_interval = duration_timespec(period);
+        self.settime_spec(libc::TIMER_ABSTIME, &spec)
+    }
+
+    fn settime_spec(&self, flags: c_int, spec: &libc::itimerspec) -> io::Result<()> {
+        let r = unsafe { libc::timer_settime(self.id, flags, spec, ptr::null_mut()) };
+        if r != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for PosixTimer {
+    // A callback thread may still run after the timer is deleted, so the id
+    // is reset under the lock first
+    fn drop(&mut self) {
+        match &self.callback {
+            Some((id, reg)) => {
+                let mut timer = reg.timer();
+                timer.0 = ptr::null_mut();
+                unsafe { libc::timer_delete(self.id) };
+                drop(timer);
+                remove_callback(*id);
+            }
+            None => {
+                unsafe { libc::timer_delete(self.id) };
+            }
+        }
+    }
+}
+
+impl fmt::Debug for PosixTimer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PosixTimer")
+            .field("id", &self.id)
+            .field("callback", &self.callback.is_some())
+            .finish()
+    }
+}
+
+fn remove_callback(id: usize) {
+    callbacks().retain(|(i, _)| *i != id);
+}
+
+fn sig_event(notify: c_int) -> SigEvent {
+    SigEvent {
+        value: libc::sigval {
+            sival_ptr: ptr::null_mut(),
+        },
+        signo: 0,
+        notify,
+        function: None,
+        attributes: ptr::null_mut(),
+        _pad: [0; 64 - 3 * mem::size_of::<usize>() - 2 * mem::size_of::<c_int>()],
+    }
+}
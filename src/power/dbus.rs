@@ -0,0 +1,657 @@
+//! A minimal D-Bus client for the system bus
+//!
+//! It supports what the power module needs: calling methods with string
+//! arguments, receiving signals and file descriptors. Messages are sent in
+//! little-endian byte order and received in either.
+use std::collections::VecDeque;
+use std::env;
+use std::io::{self, Write};
+use std::mem;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::net::UnixStream;
+use std::ptr;
+
+const SYSTEM_BUS_SOCKET: &str = "/var/run/dbus/system_bus_socket";
+
+// Message types
+const METHOD_CALL: u8 = 1;
+const ERROR: u8 = 3;
+const SIGNAL: u8 = 4;
+
+// Header field codes
+const PATH: u8 = 1;
+const INTERFACE: u8 = 2;
+const MEMBER: u8 = 3;
+const ERROR_NAME: u8 = 4;
+const REPLY_SERIAL: u8 = 5;
+const DESTINATION: u8 = 6;
+const SIGNATURE: u8 = 8;
+const UNIX_FDS: u8 = 9;
+
+// Messages larger than this are rejected by the bus daemon as well
+const MAX_MESSAGE_LEN: usize = 128 * 1024 * 1024;
+// The bus daemon passes at most this many descriptors per message
+const MAX_FDS: usize = 16;
+
+pub(super) struct Connection {
+    stream: UnixStream,
+    // Received bytes from `pos` on are not consumed yet
+    buf: Vec<u8>,
+    pos: usize,
+    // Received descriptors not yet claimed by a message
+    fds: VecDeque<OwnedFd>,
+    serial: u32,
+}
+
+impl Connection {
+    /// Connects and authenticates to the system bus.
+    pub(super) fn system() -> io::Result<Connection> {
+        let mut conn = Connection::new(UnixStream::connect(system_bus_path())?);
+        conn.authenticate()?;
+        conn.call(
+            "org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus",
+            "Hello",
+            &[],
+        )?;
+        Ok(conn)
+    }
+
+    fn new(stream: UnixStream) -> Connection {
+        Connection {
+            stream,
+            buf: Vec::new(),
+            pos: 0,
+            fds: VecDeque::new(),
+            serial: 0,
+        }
+    }
+
+    fn authenticate(&mut self) -> io::Result<()> {
+        let uid = unsafe { libc::getuid() }.to_string();
+        let hex: String = uid.bytes().map(|b| format!("{b:02x}")).collect();
+        self.stream.write_all(b"\0")?;
+        self.stream
+            .write_all(format!("AUTH EXTERNAL {hex}\r\n").as_bytes())?;
+        let line = self.read_line()?;
+        if !line.starts_with("OK ") {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("D-Bus authentication failed: {}", line.trim_end()),
+            ));
+        }
+        // Without the agreement replies carrying descriptors are rejected,
+        // other calls still work
+        self.stream.write_all(b"NEGOTIATE_UNIX_FD\r\n")?;
+        self.read_line()?;
+        self.stream.write_all(b"BEGIN\r\n")
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        loop {
+            if let Some(i) = self.buf[self.pos..].iter().position(|&b| b == b'\n') {
+                let line = String::from_utf8_lossy(&self.buf[self.pos..self.pos + i + 1]);
+                let line = line.into_owned();
+                self.pos += i + 1;
+                return Ok(line);
+            }
+            self.fill()?;
+        }
+    }
+
+    fn read_exact(&mut self, out: &mut [u8]) -> io::Result<()> {
+        while self.buf.len() - self.pos < out.len() {
+            self.fill()?;
+        }
+        out.copy_from_slice(&self.buf[self.pos..self.pos + out.len()]);
+        self.pos += out.len();
+        Ok(())
+    }
+
+    // Receives more bytes and the descriptors sent along with them. Reading
+    // with `read` would silently close the descriptors.
+    fn fill(&mut self) -> io::Result<()> {
+        self.buf.drain(..self.pos);
+        self.pos = 0;
+        let len = self.buf.len();
+        self.buf.resize(len + 4096, 0);
+        let mut iov = libc::iovec {
+            iov_base: self.buf[len..].as_mut_ptr().cast(),
+            iov_len: 4096,
+        };
+        let mut cmsg = [0u64; cmsg_space_words()];
+        let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = cmsg.as_mut_ptr().cast();
+        msg.msg_controllen = mem::size_of_val(&cmsg) as _;
+        let n = loop {
+            let n =
+                unsafe { libc::recvmsg(self.stream.as_raw_fd(), &mut msg, libc::MSG_CMSG_CLOEXEC) };
+            if n >= 0 {
+                break n as usize;
+            }
+            let e = io::Error::last_os_error();
+            if e.kind() != io::ErrorKind::Interrupted {
+                self.buf.truncate(len);
+                return Err(e);
+            }
+        };
+        self.buf.truncate(len + n);
+        unsafe {
+            let mut hdr = libc::CMSG_FIRSTHDR(&msg);
+            while !hdr.is_null() {
+                if (*hdr).cmsg_level == libc::SOL_SOCKET && (*hdr).cmsg_type == libc::SCM_RIGHTS {
+                    let data = libc::CMSG_DATA(hdr).cast::<libc::c_int>();
+                    let count = ((*hdr).cmsg_len as usize - libc::CMSG_LEN(0) as usize)
+                        / mem::size_of::<libc::c_int>();
+                    for i in 0..count {
+                        let fd = ptr::read_unaligned(data.add(i));
+                        self.fds.push_back(OwnedFd::from_raw_fd(fd));
+                    }
+                }
+                hdr = libc::CMSG_NXTHDR(&msg, hdr);
+            }
+        }
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(())
+    }
+
+    /// Calls a method with string arguments and waits for the reply. Other
+    /// messages received in the meantime are dropped.
+    pub(super) fn call(
+        &mut self,
+        destination: &str,
+        path: &str,
+        interface: &str,
+        member: &str,
+        args: &[&str],
+    ) -> io::Result<Message> {
+        self.serial += 1;
+        let serial = self.serial;
+        let msg = encode_call(serial, destination, path, interface, member, args);
+        self.stream.write_all(&msg)?;
+        loop {
+            let reply = self.read()?;
+            if reply.reply_serial != Some(serial) {
+                continue;
+            }
+            return match reply.kind {
+                ERROR => Err(io::Error::other(format!(
+                    "D-Bus call {interface}.{member} failed: {}",
+                    reply.error_name.as_deref().unwrap_or("unknown error")
+                ))),
+                _ => Ok(reply),
+            };
+        }
+    }
+
+    /// Subscribes to the signals matching the rule.
+    pub(super) fn add_match(&mut self, rule: &str) -> io::Result<()> {
+        self.call(
+            "org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus",
+            "AddMatch",
+            &[rule],
+        )?;
+        Ok(())
+    }
+
+    /// Returns `true` if a service owns the bus name.
+    pub(super) fn name_has_owner(&mut self, name: &str) -> io::Result<bool> {
+        let reply = self.call(
+            "org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus",
+            "NameHasOwner",
+            &[name],
+        )?;
+        reply.bool_arg().ok_or_else(invalid_message)
+    }
+
+    /// Reads the next message.
+    pub(super) fn read(&mut self) -> io::Result<Message> {
+        let mut fixed = [0; 16];
+        self.read_exact(&mut fixed)?;
+        let big_endian = match fixed[0] {
+            b'l' => false,
+            b'B' => true,
+            _ => return Err(invalid_message()),
+        };
+        let u32_at = |i: usize| read_u32(&fixed[i..i + 4], big_endian);
+        let body_len = u32_at(4) as usize;
+        let fields_len = u32_at(12) as usize;
+        let header_len = 16 + align(fields_len, 8);
+        if header_len + body_len > MAX_MESSAGE_LEN {
+            return Err(invalid_message());
+        }
+        let mut buf = vec![0; header_len + body_len];
+        buf[..16].copy_from_slice(&fixed);
+        self.read_exact(&mut buf[16..])?;
+
+        let mut msg = Message {
+            kind: fixed[1],
+            interface: None,
+            member: None,
+            error_name: None,
+            reply_serial: None,
+            signature: String::new(),
+            body: buf[header_len..].to_vec(),
+            big_endian,
+            fds: Vec::new(),
+        };
+        let mut pos = 16;
+        let end = 16 + fields_len;
+        while pos < end {
+            pos = align(pos, 8);
+            let code = *buf.get(pos).ok_or_else(invalid_message)?;
+            let sig_len = *buf.get(pos + 1).ok_or_else(invalid_message)? as usize;
+            let sig = buf
+                .get(pos + 2..pos + 2 + sig_len)
+                .ok_or_else(invalid_message)?;
+            pos += 3 + sig_len;
+            match sig {
+                b"s" | b"o" => {
+                    pos = align(pos, 4);
+                    let len = read_u32(
+                        buf.get(pos..pos + 4).ok_or_else(invalid_message)?,
+                        big_endian,
+                    );
+                    let value = buf
+                        .get(pos + 4..pos + 4 + len as usize)
+                        .ok_or_else(invalid_message)?;
+                    let value = String::from_utf8_lossy(value).into_owned();
+                    pos += 5 + len as usize;
+                    match code {
+                        INTERFACE => msg.interface = Some(value),
+                        MEMBER => msg.member = Some(value),
+                        ERROR_NAME => msg.error_name = Some(value),
+                        _ => {}
+                    }
+                }
+                b"g" => {
+                    let len = *buf.get(pos).ok_or_else(invalid_message)? as usize;
+                    let value = buf
+                        .get(pos + 1..pos + 1 + len)
+                        .ok_or_else(invalid_message)?;
+                    if code == SIGNATURE {
+                        msg.signature = String::from_utf8_lossy(value).into_owned();
+                    }
+                    pos += 2 + len;
+                }
+                b"u" => {
+                    pos = align(pos, 4);
+                    let value = read_u32(
+                        buf.get(pos..pos + 4).ok_or_else(invalid_message)?,
+                        big_endian,
+                    );
+                    match code {
+                        REPLY_SERIAL => msg.reply_serial = Some(value),
+                        UNIX_FDS => {
+                            let n = (value as usize).min(self.fds.len());
+                            msg.fds = self.fds.drain(..n).collect();
+                        }
+                        _ => {}
+                    }
+                    pos += 4;
+                }
+                _ => return Err(invalid_message()),
+            }
+        }
+        Ok(msg)
+    }
+}
+
+pub(super) struct Message {
+    kind: u8,
+    interface: Option<String>,
+    member: Option<String>,
+    error_name: Option<String>,
+    reply_serial: Option<u32>,
+    signature: String,
+    body: Vec<u8>,
+    big_endian: bool,
+    fds: Vec<OwnedFd>,
+}
+
+impl Message {
+    /// Returns `true` if the message is the signal `interface.member`.
+    pub(super) fn is_signal(&self, interface: &str, member: &str) -> bool {
+        self.kind == SIGNAL
+            && self.interface.as_deref() == Some(interface)
+            && self.member.as_deref() == Some(member)
+    }
+
+    /// Returns the body if it is a single boolean.
+    pub(super) fn bool_arg(&self) -> Option<bool> {
+        if self.signature != "b" {
+            return None;
+        }
+        Some(read_u32(self.body.get(..4)?, self.big_endian) != 0)
+    }
+
+    /// Returns the descriptor if the body is a single one.
+    pub(super) fn into_fd_arg(self) -> Option<OwnedFd> {
+        if self.signature != "h" {
+            return None;
+        }
+        let index = read_u32(self.body.get(..4)?, self.big_endian);
+        self.fds.into_iter().nth(index as usize)
+    }
+}
+
+// The control buffer for `MAX_FDS` descriptors in `u64` words, which aligns
+// it for `cmsghdr`
+const fn cmsg_space_words() -> usize {
+    let bytes = unsafe { libc::CMSG_SPACE((MAX_FDS * mem::size_of::<libc::c_int>()) as u32) };
+    (bytes as usize).div_ceil(8)
+}
+
+fn system_bus_path() -> String {
+    env::var("DBUS_SYSTEM_BUS_ADDRESS")
+        .ok()
+        .and_then(|addr| {
+            // The first of the `;`-separated addresses with a socket path
+            addr.split(';').find_map(|addr| {
+                addr.strip_prefix("unix:")?
+                    .split(',')
+                    .find_map(|kv| kv.strip_prefix("path="))
+                    .map(str::to_owned)
+            })
+        })
+        .unwrap_or_else(|| SYSTEM_BUS_SOCKET.to_owned())
+}
+
+fn encode_call(
+    serial: u32,
+    destination: &str,
+    path: &str,
+    interface: &str,
+    member: &str,
+    args: &[&str],
+) -> Vec<u8> {
+    let mut body = Vec::new();
+    for arg in args {
+        put_str(&mut body, arg);
+    }
+    let signature = "s".repeat(args.len());
+
+    let mut msg = vec![b'l', METHOD_CALL, 0, 1];
+    msg.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    msg.extend_from_slice(&serial.to_le_bytes());
+    // Header fields, an array of (code, variant) structs
+    msg.extend_from_slice(&[0; 4]);
+    let start = msg.len();
+    put_field(&mut msg, PATH, "o", path);
+    put_field(&mut msg, DESTINATION, "s", destination);
+    put_field(&mut msg, INTERFACE, "s", interface);
+    put_field(&mut msg, MEMBER, "s", member);
+    if !args.is_empty() {
+        pad(&mut msg, 8);
+        msg.extend_from_slice(&[SIGNATURE, 1, b'g', 0]);
+        msg.push(signature.len() as u8);
+        msg.extend_from_slice(signature.as_bytes());
+        msg.push(0);
+    }
+    let fields_len = (msg.len() - start) as u32;
+    msg[12..16].copy_from_slice(&fields_len.to_le_bytes());
+    pad(&mut msg, 8);
+    msg.extend_from_slice(&body);
+    msg
+}
+
+fn put_field(msg: &mut Vec<u8>, code: u8, sig: &str, value: &str) {
+    pad(msg, 8);
+    msg.extend_from_slice(&[code, 1, sig.as_bytes()[0], 0]);
+    put_str(msg, value);
+}
+
+fn put_str(buf: &mut Vec<u8>, s: &str) {
+    pad(buf, 4);
+    buf.extend_from_slice(&(s.len() as u32).to_le_bytes());
+    buf.extend_from_slice(s.as_bytes());
+    buf.push(0);
+}
+
+fn pad(buf: &mut Vec<u8>, n: usize) {
+    buf.resize(align(buf.len(), n), 0);
+}
+
+fn align(pos: usize, n: usize) -> usize {
+    pos.next_multiple_of(n)
+}
+
+fn read_u32(bytes: &[u8], big_endian: bool) -> u32 {
+    let bytes = bytes.try_into().unwrap();
+    if big_endian {
+        u32::from_be_bytes(bytes)
+    } else {
+        u32::from_le_bytes(bytes)
+    }
+}
+
+fn invalid_message() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed D-Bus message")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    const METHOD_RETURN: u8 = 2;
+
+    enum Field<'a> {
+        Str(u8, &'a str),
+        Sig(u8, &'a str),
+        U32(u8, u32),
+        // A field with a raw variant signature and no value
+        Raw(u8, &'a [u8]),
+    }
+
+    fn put_u32(buf: &mut Vec<u8>, value: u32, big_endian: bool) {
+        if big_endian {
+            buf.extend_from_slice(&value.to_be_bytes());
+        } else {
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    // Encodes a message in either byte order
+    fn message(big_endian: bool, kind: u8, fields: &[Field<'_>], body: &[u8]) -> Vec<u8> {
+        let mut msg = vec![if big_endian { b'B' } else { b'l' }, kind, 0, 1];
+        put_u32(&mut msg, body.len() as u32, big_endian);
+        put_u32(&mut msg, 1, big_endian);
+        msg.extend_from_slice(&[0; 4]);
+        for field in fields {
+            pad(&mut msg, 8);
+            match *field {
+                Field::Str(code, value) => {
+                    msg.extend_from_slice(&[code, 1, b's', 0]);
+                    put_u32(&mut msg, value.len() as u32, big_endian);
+                    msg.extend_from_slice(value.as_bytes());
+                    msg.push(0);
+                }
+                Field::Sig(code, value) => {
+                    msg.extend_from_slice(&[code, 1, b'g', 0]);
+                    msg.push(value.len() as u8);
+                    msg.extend_from_slice(value.as_bytes());
+                    msg.push(0);
+                }
+                Field::U32(code, value) => {
+                    msg.extend_from_slice(&[code, 1, b'u', 0]);
+                    put_u32(&mut msg, value, big_endian);
+                }
+                Field::Raw(code, sig) => {
+                    msg.extend_from_slice(&[code, sig.len() as u8]);
+                    msg.extend_from_slice(sig);
+                    msg.push(0);
+                }
+            }
+        }
+        let fields_len = msg.len() as u32 - 16;
+        let fields_len = if big_endian {
+            fields_len.to_be_bytes()
+        } else {
+            fields_len.to_le_bytes()
+        };
+        msg[12..16].copy_from_slice(&fields_len);
+        pad(&mut msg, 8);
+        msg.extend_from_slice(body);
+        msg
+    }
+
+    fn pair() -> (UnixStream, Connection) {
+        let (a, b) = UnixStream::pair().unwrap();
+        (a, Connection::new(b))
+    }
+
+    // Sends `bytes` along with `fd` in one message
+    fn send_with_fd(stream: &UnixStream, bytes: &[u8], fd: libc::c_int) {
+        let mut iov = libc::iovec {
+            iov_base: bytes.as_ptr() as *mut _,
+            iov_len: bytes.len(),
+        };
+        let mut cmsg = [0u64; cmsg_space_words()];
+        let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = cmsg.as_mut_ptr().cast();
+        msg.msg_controllen = unsafe { libc::CMSG_SPACE(mem::size_of::<libc::c_int>() as u32) } as _;
+        unsafe {
+            let hdr = libc::CMSG_FIRSTHDR(&msg);
+            (*hdr).cmsg_level = libc::SOL_SOCKET;
+            (*hdr).cmsg_type = libc::SCM_RIGHTS;
+            (*hdr).cmsg_len = libc::CMSG_LEN(mem::size_of::<libc::c_int>() as u32) as _;
+            ptr::write_unaligned(libc::CMSG_DATA(hdr).cast::<libc::c_int>(), fd);
+            let n = libc::sendmsg(stream.as_raw_fd(), &msg, 0);
+            assert_eq!(n, bytes.len() as isize);
+        }
+    }
+
+    #[test]
+    fn call_round_trip() {
+        let (mut peer, mut conn) = pair();
+        let call = encode_call(
+            3,
+            "org.example",
+            "/org/example",
+            "org.example.I",
+            "M",
+            &["a", "bc"],
+        );
+        peer.write_all(&call).unwrap();
+        let msg = conn.read().unwrap();
+        assert_eq!(msg.kind, METHOD_CALL);
+        assert_eq!(msg.interface.as_deref(), Some("org.example.I"));
+        assert_eq!(msg.member.as_deref(), Some("M"));
+        assert_eq!(msg.signature, "ss");
+        assert!(!msg.big_endian);
+        // Two strings, the second aligned to 4 bytes
+        assert_eq!(msg.body, b"\x01\0\0\0a\0\0\0\x02\0\0\0bc\0");
+    }
+
+    #[test]
+    fn big_endian_signal() {
+        let (mut peer, mut conn) = pair();
+        let signal = message(
+            true,
+            SIGNAL,
+            &[
+                Field::Str(PATH, "/org/freedesktop/login1"),
+                Field::Str(INTERFACE, "org.freedesktop.login1.Manager"),
+                Field::Str(MEMBER, "PrepareForSleep"),
+                Field::Sig(SIGNATURE, "b"),
+            ],
+            &1u32.to_be_bytes(),
+        );
+        peer.write_all(&signal).unwrap();
+        let msg = conn.read().unwrap();
+        assert!(msg.big_endian);
+        assert!(msg.is_signal("org.freedesktop.login1.Manager", "PrepareForSleep"));
+        assert_eq!(msg.bool_arg(), Some(true));
+    }
+
+    #[test]
+    fn malformed_header_field() {
+        let (mut peer, mut conn) = pair();
+        let msg = message(false, SIGNAL, &[Field::Raw(PATH, b"ai")], &[]);
+        peer.write_all(&msg).unwrap();
+        let e = conn.read().err().unwrap();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+
+        // A signature running past the header
+        let (mut peer, mut conn) = pair();
+        let mut msg = message(false, SIGNAL, &[Field::Raw(PATH, b"s")], &[]);
+        msg[17] = 200;
+        peer.write_all(&msg).unwrap();
+        let e = conn.read().err().unwrap();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn reply_with_fd() {
+        let (peer, mut conn) = pair();
+        let (passed, mut other) = UnixStream::pair().unwrap();
+        let reply = message(
+            false,
+            METHOD_RETURN,
+            &[
+                Field::U32(REPLY_SERIAL, 1),
+                Field::Sig(SIGNATURE, "h"),
+                Field::U32(UNIX_FDS, 1),
+            ],
+            &0u32.to_le_bytes(),
+        );
+        send_with_fd(&peer, &reply, passed.as_raw_fd());
+        drop(passed);
+        let msg = conn.read().unwrap();
+        assert_eq!(msg.reply_serial, Some(1));
+        let fd = msg.into_fd_arg().unwrap();
+        UnixStream::from(fd).write_all(b"x").unwrap();
+        let mut buf = [0];
+        other.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"x");
+    }
+
+    #[test]
+    fn call_error_reply() {
+        let (peer, mut conn) = pair();
+        // An unrelated signal is skipped while waiting for the reply
+        let signal = message(
+            false,
+            SIGNAL,
+            &[
+                Field::Str(INTERFACE, "org.example.I"),
+                Field::Str(MEMBER, "S"),
+            ],
+            &[],
+        );
+        let error = message(
+            false,
+            ERROR,
+            &[
+                Field::Str(ERROR_NAME, "org.freedesktop.DBus.Error.AccessDenied"),
+                Field::U32(REPLY_SERIAL, 1),
+            ],
+            &[],
+        );
+        (&peer).write_all(&signal).unwrap();
+        (&peer).write_all(&error).unwrap();
+        let e = conn
+            .call("org.example", "/org/example", "org.example.I", "M", &[])
+            .err()
+            .unwrap();
+        assert!(e.to_string().contains("AccessDenied"));
+
+        let mut peer = Connection::new(peer);
+        let call = peer.read().unwrap();
+        assert_eq!(call.kind, METHOD_CALL);
+        assert_eq!(call.member.as_deref(), Some("M"));
+        assert_eq!(call.signature, "");
+    }
+}
@@ -0,0 +1,197 @@
+use super::dbus::Connection;
+use super::{PowerEvent, SleepState, Standby, MIN_SUSPEND};
+use crate::sys;
+use std::env;
+use std::fs;
+use std::io;
+use std::os::fd::OwnedFd;
+use std::thread;
+use std::time::Duration;
+
+const LOGIND: &str = "org.freedesktop.login1";
+const LOGIND_PATH: &str = "/org/freedesktop/login1";
+const LOGIND_MANAGER: &str = "org.freedesktop.login1.Manager";
+
+// How often the poll thread checks whether the system was suspended or the
+// process frozen
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+// Oversleeping by less than this is scheduling latency rather than freezing
+const MIN_FREEZE: Duration = Duration::from_secs(5);
+
+pub(super) fn start(dispatch: fn(PowerEvent)) -> io::Result<()> {
+    match logind() {
+        Ok(conn) => {
+            spawn(move || listen(conn, dispatch))?;
+            spawn(move || poll(dispatch, false))
+        }
+        Err(_) => spawn(move || poll(dispatch, true)),
+    }
+}
+
+fn spawn<F: FnOnce() + Send + 'static>(f: F) -> io::Result<()> {
+    thread::Builder::new()
+        .name("boot-time-power".to_owned())
+        .spawn(f)
+        .map(drop)
+}
+
+// Subscribes to `PrepareForSleep`, which logind emits with `true` before the
+// system suspends and with `false` after it resumed
+fn logind() -> io::Result<Connection> {
+    let mut conn = Connection::system()?;
+    if !conn.name_has_owner(LOGIND)? {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "systemd-logind is not running",
+        ));
+    }
+    conn.add_match(&format!(
+        "type='signal',sender='{LOGIND}',interface='{LOGIND_MANAGER}',member='PrepareForSleep'"
+    ))?;
+    Ok(conn)
+}
+
+fn listen(mut conn: Connection, dispatch: fn(PowerEvent)) {
+    let mut before = Sample::now();
+    // The thread exits if the bus connection breaks, which ends the iterators
+    while let Ok(msg) = conn.read() {
+        if !msg.is_signal(LOGIND_MANAGER, "PrepareForSleep") {
+            continue;
+        }
+        match msg.bool_arg() {
+            Some(true) => {
+                before = Sample::now();
+                dispatch(PowerEvent::Suspend(SleepState::Unknown));
+            }
+            Some(false) => dispatch(PowerEvent::Resume(before.sleep_state(&Sample::now()))),
+            None => {}
+        }
+    }
+}
+
+// What the kernel tells about past sleeps
+struct Sample {
+    suspend_count: Option<u64>,
+    suspended: Duration,
+    monotonic: Duration,
+}
+
+impl Sample {
+    fn now() -> Sample {
+        Sample {
+            suspend_count: suspend_count().ok(),
+            suspended: sys::suspended_time(),
+            monotonic: sys::monotonic_now(),
+        }
+    }
+
+    // Only suspends to RAM or idle increment the suspend count, while
+    // hibernation adds suspended time as well
+    fn sleep_state(&self, after: &Sample) -> SleepState {
+        let (Some(before_count), Some(after_count)) = (self.suspend_count, after.suspend_count)
+        else {
+            return SleepState::Unknown;
+        };
+        if after_count > before_count {
+            match standby() {
+                Ok(Standby::Modern) => SleepState::LowPowerIdle,
+                Ok(Standby::Classic) => SleepState::SuspendToRam,
+                Err(_) => SleepState::Unknown,
+            }
+        } else if after.suspended.saturating_sub(self.suspended) >= MIN_SUSPEND {
+            SleepState::Hibernate
+        } else {
+            SleepState::Unknown
+        }
+    }
+}
+
+// logind holds the lock until the descriptor is closed
+#[derive(Debug)]
+pub(super) struct Inhibitor {
+    _fd: OwnedFd,
+}
+
+pub(super) fn inhibit(reason: &str) -> io::Result<Inhibitor> {
+    let mut conn = Connection::system()?;
+    let who = env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.file_name()?.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "boot-time".to_owned());
+    let reply = conn.call(
+        LOGIND,
+        LOGIND_PATH,
+        LOGIND_MANAGER,
+        "Inhibit",
+        &["sleep", &who, reason, "block"],
+    )?;
+    reply
+        .into_fd_arg()
+        .map(|fd| Inhibitor { _fd: fd })
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "logind returned no inhibitor descriptor",
+            )
+        })
+}
+
+pub(super) fn suspend_count() -> io::Result<u64> {
+    match fs::read_to_string("/sys/power/suspend_stats/success") {
+        Ok(s) => s.trim().parse().map_err(invalid_data),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            // Lines of `name: value` pairs
+            let stats = fs::read_to_string("/sys/kernel/debug/suspend_stats")?;
+            let value = stats
+                .lines()
+                .find_map(|line| line.trim().strip_prefix("success:"))
+                .ok_or_else(|| invalid_data("no success count in suspend_stats"))?;
+            value.trim().parse().map_err(invalid_data)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// The selected state is in brackets, e.g. `s2idle [deep]`
+pub(super) fn standby() -> io::Result<Standby> {
+    let states = fs::read_to_string("/sys/power/mem_sleep")?;
+    let selected = states
+        .split_whitespace()
+        .find_map(|state| state.strip_prefix('[')?.strip_suffix(']'))
+        .ok_or_else(|| invalid_data("no selected state in mem_sleep"))?;
+    match selected {
+        "s2idle" => Ok(Standby::Modern),
+        _ => Ok(Standby::Classic),
+    }
+}
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+// Without logind the suspend can only be noticed after the fact. Sleeping
+// measures CLOCK_MONOTONIC, so the thread wakes up shortly after resume and
+// sees the suspended time grow.
+//
+// A frozen cgroup stops the process but not the clocks, so the thread wakes
+// up late after the cgroup is thawed, without any suspended time. The freeze
+// itself can't be observed from inside the cgroup.
+fn poll(dispatch: fn(PowerEvent), report_resume: bool) {
+    let mut last = Sample::now();
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let now = Sample::now();
+        let overslept = now
+            .monotonic
+            .saturating_sub(last.monotonic)
+            .saturating_sub(POLL_INTERVAL);
+        if now.suspended.saturating_sub(last.suspended) >= MIN_SUSPEND {
+            if report_resume {
+                dispatch(PowerEvent::Resume(last.sleep_state(&now)));
+            }
+        } else if overslept >= MIN_FREEZE {
+            dispatch(PowerEvent::Thawed(overslept));
+        }
+        last = now;
+    }
+}
@@ -0,0 +1,176 @@
+use super::{PowerEvent, SleepState, Standby};
+use std::ffi::{c_char, c_void, CString};
+use std::io;
+use std::ptr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{mpsc, OnceLock};
+use std::thread;
+
+#[allow(non_camel_case_types)]
+type io_connect_t = u32;
+#[allow(non_camel_case_types)]
+type io_object_t = u32;
+#[allow(non_camel_case_types)]
+type io_service_t = u32;
+type IONotificationPortRef = *mut c_void;
+type IOServiceInterestCallback = unsafe extern "C" fn(
+    refcon: *mut c_void,
+    service: io_service_t,
+    message: u32,
+    arg: *mut c_void,
+);
+type CFRunLoopRef = *mut c_void;
+type CFRunLoopSourceRef = *mut c_void;
+type CFStringRef = *const c_void;
+type CFAllocatorRef = *const c_void;
+#[allow(non_camel_case_types)]
+type IOPMAssertionID = u32;
+
+// iokit_common_msg(message), see IOKit/IOMessage.h
+const CAN_SYSTEM_SLEEP: u32 = 0xe000_0270;
+const SYSTEM_WILL_SLEEP: u32 = 0xe000_0280;
+const SYSTEM_HAS_POWERED_ON: u32 = 0xe000_0300;
+const IOPM_ASSERTION_LEVEL_ON: u32 = 255;
+const CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IORegisterForSystemPower(
+        refcon: *mut c_void,
+        the_port_ref: *mut IONotificationPortRef,
+        callback: IOServiceInterestCallback,
+        notifier: *mut io_object_t,
+    ) -> io_connect_t;
+    fn IOAllowPowerChange(kernel_port: io_connect_t, notification_id: isize) -> i32;
+    fn IONotificationPortGetRunLoopSource(notify: IONotificationPortRef) -> CFRunLoopSourceRef;
+    fn IOPMAssertionCreateWithName(
+        assertion_type: CFStringRef,
+        level: u32,
+        name: CFStringRef,
+        id: *mut IOPMAssertionID,
+    ) -> i32;
+    fn IOPMAssertionRelease(id: IOPMAssertionID) -> i32;
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    static kCFRunLoopDefaultMode: CFStringRef;
+    fn CFRunLoopGetCurrent() -> CFRunLoopRef;
+    fn CFRunLoopAddSource(rl: CFRunLoopRef, source: CFRunLoopSourceRef, mode: CFStringRef);
+    fn CFRunLoopRun();
+    fn CFStringCreateWithCString(
+        alloc: CFAllocatorRef,
+        c_str: *const c_char,
+        encoding: u32,
+    ) -> CFStringRef;
+    fn CFRelease(cf: *const c_void);
+}
+
+// The connection to the root power domain, which acknowledges sleep
+static ROOT_PORT: AtomicU32 = AtomicU32::new(0);
+static DISPATCH: OnceLock<fn(PowerEvent)> = OnceLock::new();
+
+// The notifications are delivered on the run loop of the thread that
+// registered for them, so the thread registers and then runs its run loop
+pub(super) fn start(dispatch: fn(PowerEvent)) -> io::Result<()> {
+    let _ = DISPATCH.set(dispatch);
+    let (tx, rx) = mpsc::channel();
+    thread::Builder::new()
+        .name("boot-time-power".to_owned())
+        .spawn(move || unsafe {
+            let mut port = ptr::null_mut();
+            let mut notifier = 0;
+            let root =
+                IORegisterForSystemPower(ptr::null_mut(), &mut port, callback, &mut notifier);
+            if root == 0 {
+                let _ = tx.send(Err(io::Error::other("IORegisterForSystemPower failed")));
+                return;
+            }
+            ROOT_PORT.store(root, Ordering::Release);
+            CFRunLoopAddSource(
+                CFRunLoopGetCurrent(),
+                IONotificationPortGetRunLoopSource(port),
+                kCFRunLoopDefaultMode,
+            );
+            let _ = tx.send(Ok(()));
+            CFRunLoopRun();
+        })?;
+    rx.recv()
+        .unwrap_or_else(|_| Err(io::Error::other("power listener thread exited")))
+}
+
+unsafe extern "C" fn callback(_: *mut c_void, _: io_service_t, message: u32, arg: *mut c_void) {
+    let root = ROOT_PORT.load(Ordering::Acquire);
+    let dispatch = DISPATCH.get().copied().unwrap_or(drop);
+    match message {
+        // Idle sleep may be vetoed, but it isn't
+        CAN_SYSTEM_SLEEP => {
+            IOAllowPowerChange(root, arg as isize);
+        }
+        // The system waits for the acknowledgement up to 30 seconds
+        SYSTEM_WILL_SLEEP => {
+            dispatch(PowerEvent::Suspend(SleepState::Unknown));
+            IOAllowPowerChange(root, arg as isize);
+        }
+        SYSTEM_HAS_POWERED_ON => dispatch(PowerEvent::Resume(SleepState::Unknown)),
+        _ => {}
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct Inhibitor(IOPMAssertionID);
+
+// `PreventUserIdleSystemSleep` keeps the system awake until the assertion is
+// released, but closing the lid still sleeps
+pub(super) fn inhibit(reason: &str) -> io::Result<Inhibitor> {
+    let reason =
+        CString::new(reason).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    unsafe {
+        let kind = cf_string(c"PreventUserIdleSystemSleep".as_ptr())?;
+        let name = match cf_string(reason.as_ptr()) {
+            Ok(name) => name,
+            Err(e) => {
+                CFRelease(kind);
+                return Err(e);
+            }
+        };
+        let mut id = 0;
+        let r = IOPMAssertionCreateWithName(kind, IOPM_ASSERTION_LEVEL_ON, name, &mut id);
+        CFRelease(name);
+        CFRelease(kind);
+        if r != 0 {
+            return Err(io::Error::other(format!(
+                "IOPMAssertionCreateWithName failed: {r:#x}"
+            )));
+        }
+        Ok(Inhibitor(id))
+    }
+}
+
+impl Drop for Inhibitor {
+    fn drop(&mut self) {
+        unsafe { IOPMAssertionRelease(self.0) };
+    }
+}
+
+unsafe fn cf_string(s: *const c_char) -> io::Result<CFStringRef> {
+    let cf = CFStringCreateWithCString(ptr::null(), s, CF_STRING_ENCODING_UTF8);
+    if cf.is_null() {
+        return Err(io::Error::from(io::ErrorKind::OutOfMemory));
+    }
+    Ok(cf)
+}
+
+pub(super) fn standby() -> io::Result<Standby> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the standby kind is not available on this platform",
+    ))
+}
+
+pub(super) fn suspend_count() -> io::Result<u64> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the suspend count is not available on this platform",
+    ))
+}
@@ -0,0 +1,408 @@
+//! Notifications about system suspend and resume
+//!
+//! Timers that measure with [`Instant`](crate::Instant) keep the right
+//! deadlines across suspend, but code that caches state derived from the
+//! passage of time, e.g. leases or connection pools, needs to know when the
+//! system woke up to re-validate it.
+//!
+//! The events are read by a background thread, which is started on the first
+//! subscription.
+//!
+//! | Platform                | Source                                                |
+//! |-------------------------|-------------------------------------------------------|
+//! | Linux with logind       | `PrepareForSleep` signal of systemd-logind over D-Bus |
+//! | Linux without logind    | Polling the suspended time, [`PowerEvent::Resume`] only |
+//! | Android                 | Polling the suspended time, [`PowerEvent::Resume`] only |
+//! | macOS                   | `IORegisterForSystemPower`                            |
+//! | Windows 8+              | `PowerRegisterSuspendResumeNotification`              |
+//!
+//! Without logind, Linux offers no notification of resume that could be
+//! polled, so the fallback is a thread that samples the suspended time once a
+//! second rather than a descriptor for `epoll`. Events of either source are
+//! delivered to [`Events`] and callbacks.
+//!
+//! On Linux the background thread also notices when the process was frozen,
+//! e.g. by `docker pause` or the cgroup freezer, and reports
+//! [`PowerEvent::Thawed`]. Unlike a suspend, the clocks keep running while the
+//! process is frozen, so [`Instant`] deadlines pass unnoticed.
+//!
+//! Elsewhere subscribing fails with [`io::ErrorKind::Unsupported`].
+//!
+//! Only Linux tells the [`SleepState`] on resume, by comparing the suspend
+//! count and the suspended time before and after. Other platforms report
+//! [`SleepState::Unknown`].
+//!
+//! A [`SuspendInhibitor`] keeps the system from suspending, through a
+//! systemd-logind inhibitor lock on Linux, a power assertion on macOS and
+//! `SetThreadExecutionState` on Windows.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use boot_time::power::{self, PowerEvent};
+//!
+//! for event in power::subscribe()? {
+//!     if let PowerEvent::Resume(_) = event {
+//!         // re-validate leases
+//!     }
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+use crate::{sys, DualInstant, Instant};
+use std::io;
+use std::marker::PhantomData;
+use std::mem;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+cfg_if::cfg_if! {
+    if #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        any(not(feature = "linux-raw"), feature = "linux-libc"),
+    ))] {
+        mod dbus;
+        mod linux;
+        use self::linux as imp;
+    } else if #[cfg(target_os = "macos")] {
+        mod macos;
+        use self::macos as imp;
+    } else if #[cfg(windows)] {
+        mod windows;
+        use self::windows as imp;
+    } else {
+        mod unsupported;
+        use self::unsupported as imp;
+    }
+}
+
+/// The kind of sleep the system enters on suspend, returned by [`standby`].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum Standby {
+    /// Classic sleep (ACPI S3), which stops the CPUs and most timers.
+    Classic,
+    /// Low-power idle (S0ix): Modern Standby on Windows, `s2idle` on Linux.
+    /// The system is formally running and may wake up periodically.
+    Modern,
+}
+
+/// A change of the system power state.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum PowerEvent {
+    /// The system is about to suspend. The state is known only if the
+    /// platform announces it in advance.
+    Suspend(SleepState),
+    /// The system resumed from the given state.
+    Resume(SleepState),
+    /// The process didn't run for about the given time while the system was
+    /// awake, most likely because its cgroup was frozen or its virtual
+    /// machine paused. It is reported after the process runs again.
+    Thawed(Duration),
+}
+
+/// The state the system sleeps in.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum SleepState {
+    /// The platform doesn't tell the state.
+    Unknown,
+    /// Suspend to RAM (ACPI S3).
+    SuspendToRam,
+    /// Low-power idle (S0ix), e.g. `s2idle` on Linux.
+    LowPowerIdle,
+    /// Suspend to disk (ACPI S4). The system was powered off and memory was
+    /// restored from disk, so the downtime is usually long.
+    Hibernate,
+}
+
+// Increments of the suspended time below this are measurement noise, since
+// the two clocks it is derived from can't be read atomically
+const MIN_SUSPEND: Duration = Duration::from_millis(100);
+
+type Callback = Box<dyn FnMut(PowerEvent) + Send>;
+
+struct Registry {
+    started: bool,
+    subscribers: Vec<Sender<PowerEvent>>,
+    callbacks: Vec<Callback>,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    started: false,
+    subscribers: Vec::new(),
+    callbacks: Vec::new(),
+});
+
+fn registry() -> std::sync::MutexGuard<'static, Registry> {
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Starts the platform listener on first use. A failed start is retried by the
+// next subscription.
+fn start(registry: &mut Registry) -> io::Result<()> {
+    if !registry.started {
+        imp::start(dispatch)?;
+        registry.started = true;
+    }
+    Ok(())
+}
+
+// Called by the platform listener for every event
+fn dispatch(event: PowerEvent) {
+    let mut callbacks = {
+        let mut registry = registry();
+        registry.subscribers.retain(|tx| tx.send(event).is_ok());
+        mem::take(&mut registry.callbacks)
+    };
+    // Callbacks run without the lock, so they may subscribe themselves
+    for callback in &mut callbacks {
+        callback(event);
+    }
+    let mut registry = registry();
+    callbacks.append(&mut registry.callbacks);
+    registry.callbacks = callbacks;
+}
+
+/// Returns an iterator over the power events from now on.
+///
+/// The iterator blocks until the next event. It ends only if the platform
+/// listener fails.
+///
+/// # Errors
+///
+/// Returns an error if the platform has no power events or the listener
+/// can't be started.
+pub fn subscribe() -> io::Result<Events> {
+    let mut registry = registry();
+    start(&mut registry)?;
+    let (tx, rx) = mpsc::channel();
+    registry.subscribers.push(tx);
+    Ok(Events { rx })
+}
+
+/// Calls `callback` on the listener thread for every power event from now on.
+///
+/// The callback should return quickly, it delays the delivery of the event
+/// to other subscribers.
+///
+/// # Errors
+///
+/// Returns an error if the platform has no power events or the listener
+/// can't be started.
+pub fn on_event<F>(callback: F) -> io::Result<()>
+where
+    F: FnMut(PowerEvent) + Send + 'static,
+{
+    let mut registry = registry();
+    start(&mut registry)?;
+    registry.callbacks.push(Box::new(callback));
+    Ok(())
+}
+
+/// Calls `callback` on the listener thread right after every resume from now
+/// on.
+///
+/// Use it to re-arm OS timers, which may fire late after a resume if they
+/// were armed on a clock that stops while the system is suspended.
+///
+/// # Errors
+///
+/// Returns an error if the platform has no power events or the listener
+/// can't be started.
+///
+/// # Examples
+///
+/// ```no_run
+/// use boot_time::power;
+///
+/// power::on_resume(|| eprintln!("resumed"))?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn on_resume<F>(mut callback: F) -> io::Result<()>
+where
+    F: FnMut() + Send + 'static,
+{
+    on_event(move |event| {
+        if let PowerEvent::Resume(_) = event {
+            callback();
+        }
+    })
+}
+
+/// Returns the kind of sleep the system enters on suspend.
+///
+/// It is read from `/sys/power/mem_sleep` on Linux and from the `AoAc`
+/// power capability on Windows.
+///
+/// # Errors
+///
+/// Returns an error if the sleep state can't be read, and an error of kind
+/// [`io::ErrorKind::Unsupported`] on other platforms.
+pub fn standby() -> io::Result<Standby> {
+    imp::standby()
+}
+
+/// Returns the number of times the system resumed from suspend since boot.
+///
+/// It is read from `/sys/power/suspend_stats/success` on Linux 5.8 and
+/// later, or the `suspend_stats` file of debugfs on older kernels.
+///
+/// # Errors
+///
+/// Returns an error if the kernel doesn't expose the counter, and an error of
+/// kind [`io::ErrorKind::Unsupported`] on other platforms.
+pub fn suspend_count() -> io::Result<u64> {
+    imp::suspend_count()
+}
+
+/// Prevents the system from suspending until it is dropped.
+///
+/// Use it around critical sections whose deadlines must not be stretched by
+/// a suspend. The system may still suspend when the user requests it
+/// explicitly, e.g. by closing the lid on macOS, and on low battery.
+///
+/// The guard must be dropped on the thread that created it, since on Windows
+/// the execution state belongs to a thread.
+///
+/// # Examples
+///
+/// ```no_run
+/// use boot_time::power::SuspendInhibitor;
+///
+/// let _inhibitor = SuspendInhibitor::new("finishing the upload")?;
+/// // the system stays awake until `_inhibitor` is dropped
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct SuspendInhibitor {
+    _inner: imp::Inhibitor,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl SuspendInhibitor {
+    /// Prevents the system from suspending, giving `reason` to the user.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the platform can't inhibit suspend, or if
+    /// systemd-logind isn't running or denies the lock on Linux.
+    pub fn new(reason: &str) -> io::Result<SuspendInhibitor> {
+        Ok(SuspendInhibitor {
+            _inner: imp::inhibit(reason)?,
+            _not_send: PhantomData,
+        })
+    }
+}
+
+// The suspended time at the last observed increase, and when it was observed
+struct Observation {
+    suspended: Duration,
+    since: Option<Instant>,
+}
+
+static OBSERVATION: Mutex<Option<Observation>> = Mutex::new(None);
+
+/// Returns whether the system may have been suspended since `instant`.
+///
+/// The time spent suspended since boot is sampled on every call, so an
+/// increase is noticed at the next call after the resume. A suspend that
+/// happened before `instant` but wasn't observed until after it is still
+/// reported, so the answer may be a false positive but is never a false
+/// negative. Suspends shorter than 100 milliseconds are ignored.
+///
+/// In particular, the first call on a system that was suspended since boot
+/// reports `true` for every earlier instant. Keep a [`DualInstant`] instead
+/// and use [`was_suspended_since_dual`] for an exact answer.
+///
+/// It always returns `false` on platforms that can't tell suspended time
+/// apart, see [`ClockSource`](crate::ClockSource).
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{power, Instant};
+///
+/// let validated = Instant::now();
+/// // ...
+/// if power::was_suspended_since(validated) {
+///     // re-validate the lease
+/// }
+/// ```
+pub fn was_suspended_since(instant: Instant) -> bool {
+    let mut observation = OBSERVATION.lock().unwrap_or_else(|e| e.into_inner());
+    let suspended = sys::suspended_time();
+    let now = Instant::now();
+    match &mut *observation {
+        Some(o) => {
+            if suspended.saturating_sub(o.suspended) >= MIN_SUSPEND {
+                *o = Observation {
+                    suspended,
+                    since: Some(now),
+                };
+            }
+        }
+        // Whether earlier suspends happened before `instant` is unknown
+        None => {
+            *observation = Some(Observation {
+                suspended,
+                since: (suspended >= MIN_SUSPEND).then_some(now),
+            })
+        }
+    }
+    observation
+        .as_ref()
+        .and_then(|o| o.since)
+        .is_some_and(|since| instant < since)
+}
+
+/// Returns whether the system was suspended since `instant`.
+///
+/// Unlike [`was_suspended_since`] it compares the clocks sampled in
+/// `instant` with their current readings, so it has no false positives.
+/// Suspends shorter than 100 milliseconds are ignored.
+///
+/// It always returns `false` on platforms that can't tell suspended time
+/// apart, see [`ClockSource`](crate::ClockSource).
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{power, DualInstant};
+///
+/// let validated = DualInstant::now();
+/// // ...
+/// if power::was_suspended_since_dual(validated) {
+///     // re-validate the lease
+/// }
+/// ```
+pub fn was_suspended_since_dual(instant: DualInstant) -> bool {
+    instant.suspended_elapsed() >= MIN_SUSPEND
+}
+
+/// A blocking iterator over [`PowerEvent`]s, returned by [`subscribe`].
+#[derive(Debug)]
+pub struct Events {
+    rx: Receiver<PowerEvent>,
+}
+
+impl Events {
+    /// Returns the next event if one is pending, without blocking.
+    pub fn try_next(&mut self) -> Option<PowerEvent> {
+        self.rx.try_recv().ok()
+    }
+
+    /// Waits for the next event for at most `timeout`.
+    pub fn next_timeout(&mut self, timeout: Duration) -> Option<PowerEvent> {
+        self.rx.recv_timeout(timeout).ok()
+    }
+}
+
+impl Iterator for Events {
+    type Item = PowerEvent;
+
+    fn next(&mut self) -> Option<PowerEvent> {
+        self.rx.recv().ok()
+    }
+}
@@ -0,0 +1,33 @@
+use super::{PowerEvent, Standby};
+use std::io;
+
+pub(super) fn start(_dispatch: fn(PowerEvent)) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "power events are not supported on this platform",
+    ))
+}
+
+pub(super) fn standby() -> io::Result<Standby> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the standby kind is not available on this platform",
+    ))
+}
+
+pub(super) fn suspend_count() -> io::Result<u64> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the suspend count is not available on this platform",
+    ))
+}
+
+#[derive(Debug)]
+pub(super) enum Inhibitor {}
+
+pub(super) fn inhibit(_reason: &str) -> io::Result<Inhibitor> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "suspend can't be inhibited on this platform",
+    ))
+}
@@ -0,0 +1,131 @@
+use super::{PowerEvent, SleepState, Standby};
+use crate::sys;
+use std::ffi::{c_char, c_void};
+use std::io;
+use std::mem;
+use std::ptr;
+use std::sync::OnceLock;
+
+#[allow(clippy::upper_case_acronyms)]
+type HMODULE = *mut c_void;
+#[allow(clippy::upper_case_acronyms)]
+type HANDLE = *mut c_void;
+#[allow(clippy::upper_case_acronyms)]
+type FARPROC = Option<RawProc>;
+type RawProc = unsafe extern "system" fn() -> isize;
+type DeviceNotifyCallbackRoutine =
+    unsafe extern "system" fn(context: *mut c_void, kind: u32, setting: *mut c_void) -> u32;
+type PowerRegisterSuspendResumeNotificationFn =
+    unsafe extern "system" fn(flags: u32, recipient: HANDLE, registration: *mut HANDLE) -> u32;
+
+const DEVICE_NOTIFY_CALLBACK: u32 = 2;
+const ES_CONTINUOUS: u32 = 0x8000_0000;
+const ES_SYSTEM_REQUIRED: u32 = 0x1;
+const PBT_APMSUSPEND: u32 = 0x4;
+// Sent on every resume, unlike PBT_APMRESUMESUSPEND, which requires user input
+const PBT_APMRESUMEAUTOMATIC: u32 = 0x12;
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn LoadLibraryA(lpLibFileName: *const c_char) -> HMODULE;
+    fn GetProcAddress(hModule: HMODULE, lpProcName: *const c_char) -> FARPROC;
+    fn SetThreadExecutionState(esFlags: u32) -> u32;
+}
+
+#[repr(C)]
+struct DeviceNotifySubscribeParameters {
+    callback: DeviceNotifyCallbackRoutine,
+    context: *mut c_void,
+}
+
+// The parameters must stay valid while the registration exists, which is
+// until the process exits
+struct Subscription(DeviceNotifySubscribeParameters);
+
+unsafe impl Send for Subscription {}
+unsafe impl Sync for Subscription {}
+
+static DISPATCH: OnceLock<fn(PowerEvent)> = OnceLock::new();
+static SUBSCRIPTION: Subscription = Subscription(DeviceNotifySubscribeParameters {
+    callback,
+    context: ptr::null_mut(),
+});
+
+// The callback is called on a system thread, so no window or message loop is
+// needed. `PowerRegisterSuspendResumeNotification` exists since Windows 8.
+pub(super) fn start(dispatch: fn(PowerEvent)) -> io::Result<()> {
+    let _ = DISPATCH.set(dispatch);
+    let register = unsafe {
+        let module = LoadLibraryA(c"powrprof.dll".as_ptr());
+        if module.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let Some(f) = GetProcAddress(module, c"PowerRegisterSuspendResumeNotification".as_ptr())
+        else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "power events require Windows 8 or later",
+            ));
+        };
+        mem::transmute::<RawProc, PowerRegisterSuspendResumeNotificationFn>(f)
+    };
+    let params = &SUBSCRIPTION.0 as *const DeviceNotifySubscribeParameters;
+    let mut registration = ptr::null_mut();
+    let r = unsafe { register(DEVICE_NOTIFY_CALLBACK, params as HANDLE, &mut registration) };
+    if r != 0 {
+        return Err(io::Error::from_raw_os_error(r as i32));
+    }
+    Ok(())
+}
+
+unsafe extern "system" fn callback(_: *mut c_void, kind: u32, _: *mut c_void) -> u32 {
+    let dispatch = DISPATCH.get().copied().unwrap_or(drop);
+    match kind {
+        PBT_APMSUSPEND => dispatch(PowerEvent::Suspend(SleepState::Unknown)),
+        PBT_APMRESUMEAUTOMATIC => dispatch(PowerEvent::Resume(SleepState::Unknown)),
+        _ => {}
+    }
+    0
+}
+
+// The execution state belongs to the calling thread. Dropping the guard
+// restores the state it replaced, so nested guards unwind correctly.
+#[derive(Debug)]
+pub(super) struct Inhibitor {
+    previous: u32,
+}
+
+pub(super) fn inhibit(_reason: &str) -> io::Result<Inhibitor> {
+    let previous = unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED) };
+    if previous == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(Inhibitor { previous })
+}
+
+impl Drop for Inhibitor {
+    fn drop(&mut self) {
+        // Flags set without ES_CONTINUOUS were only a one-time idle timer reset
+        let state = if self.previous & ES_CONTINUOUS != 0 {
+            self.previous
+        } else {
+            ES_CONTINUOUS
+        };
+        unsafe { SetThreadExecutionState(state) };
+    }
+}
+
+pub(super) fn standby() -> io::Result<Standby> {
+    match sys::modern_standby() {
+        Some(true) => Ok(Standby::Modern),
+        Some(false) => Ok(Standby::Classic),
+        None => Err(io::Error::other("CallNtPowerInformation failed")),
+    }
+}
+
+pub(super) fn suspend_count() -> io::Result<u64> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the suspend count is not available on this platform",
+    ))
+}
@@ -0,0 +1,321 @@
+//! Running closures at instants of the boot clock
+use core::fmt;
+use core::time::Duration;
+use std::collections::BTreeMap;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::thread::{self, JoinHandle};
+
+use crate::timer_handle::TimerControl;
+use crate::{Deadline, Instant, TimerHandle};
+
+type Job = Box<dyn FnOnce() + Send>;
+// Jobs with equal deadlines run in the order they were scheduled
+type Key = (Instant, u64);
+
+struct State {
+    jobs: BTreeMap<Key, Job>,
+    seq: u64,
+    shutdown: bool,
+}
+
+impl State {
+    fn next_deadline(&self) -> Option<Instant> {
+        self.jobs.keys().next().map(|&(deadline, _)| deadline)
+    }
+}
+
+struct Shared {
+    state: Mutex<State>,
+    waiter: imp::Waiter,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn insert(&self, state: &mut State, deadline: Instant, job: Job) -> Key {
+        let key = (deadline, state.seq);
+        state.seq += 1;
+        let earliest = state.next_deadline().is_none_or(|next| deadline < next);
+        state.jobs.insert(key, job);
+        if earliest {
+            self.waiter.arm(Some(deadline));
+        }
+        key
+    }
+}
+
+/// Runs closures at instants of the boot clock on a worker thread, for
+/// in-process scheduling without an async runtime.
+///
+/// Deadlines are on the clock of [`Instant`], so a job due during a suspend
+/// runs right after resume. On Linux and Android the worker sleeps on a
+/// [`TimerFd`](crate::TimerFd) armed for the earliest job. Elsewhere it
+/// re-checks the clock at least every second while waiting, so a job due
+/// during a suspend may run up to a second after resume.
+///
+/// Jobs run one at a time, in deadline order. A panicking job doesn't stop
+/// the scheduler. Dropping the scheduler drops the pending jobs and waits
+/// for the running one.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{Duration, Scheduler};
+/// use std::sync::mpsc;
+///
+/// let scheduler = Scheduler::new()?;
+/// let (tx, rx) = mpsc::channel();
+/// let cancelled = scheduler.schedule_after(Duration::from_millis(10), || println!("never"));
+/// scheduler.schedule_after(Duration::from_millis(20), move || {
+///     let _ = tx.send("done");
+/// });
+/// assert!(cancelled.cancel());
+/// assert_eq!(rx.recv().unwrap(), "done");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct Scheduler {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Scheduler {
+    /// Creates a scheduler and starts its worker thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the timer or the thread can't be created.
+    pub fn new() -> io::Result<Scheduler> {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                jobs: BTreeMap::new(),
+                seq: 0,
+                shutdown: false,
+            }),
+            waiter: imp::Waiter::new()?,
+        });
+        let thread = thread::Builder::new()
+            .name("boot-time-scheduler".to_owned())
+            .spawn({
+                let shared = Arc::clone(&shared);
+                move || run(&shared)
+            })?;
+        Ok(Scheduler {
+            shared,
+            thread: Some(thread),
+        })
+    }
+
+    /// Schedules `job` to run at `deadline`. A deadline in the past runs the
+    /// job as soon as the worker is free.
+    ///
+    /// The returned handle cancels the job or moves it to another deadline
+    /// until it starts running.
+    pub fn schedule_at<F>(&self, deadline: impl Into<Deadline>, job: F) -> TimerHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let key = self.shared.insert(
+            &mut self.shared.lock(),
+            deadline.into().instant(),
+            Box::new(job),
+        );
+        TimerHandle::new(JobControl {
+            shared: Arc::downgrade(&self.shared),
+            key: Mutex::new(key),
+        })
+    }
+
+    /// Schedules `job` to run `delay` from now.
+    pub fn schedule_after<F>(&self, delay: Duration, job: F) -> TimerHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.schedule_at(Deadline::from_timeout(delay), job)
+    }
+
+    /// Returns the number of jobs waiting to run.
+    #[must_use]
+    pub fn pending(&self) -> usize {
+        self.shared.lock().jobs.len()
+    }
+}
+
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        let jobs = {
+            let mut state = self.shared.lock();
+            state.shutdown = true;
+            self.shared.waiter.arm(Some(Instant::now()));
+            std::mem::take(&mut state.jobs)
+        };
+        drop(jobs);
+        if let Some(thread) = self.thread.take() {
+            // A job may drop the scheduler itself
+            if thread.thread().id() != thread::current().id() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("pending", &self.pending())
+            .finish()
+    }
+}
+
+// Controls a job through its `TimerHandle`. The key changes when the job is
+// moved to another deadline.
+struct JobControl {
+    shared: Weak<Shared>,
+    key: Mutex<Key>,
+}
+
+impl JobControl {
+    fn key(&self) -> MutexGuard<'_, Key> {
+        self.key.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl TimerControl for JobControl {
+    fn cancel(&self) -> bool {
+        let key = self.key();
+        let Some(shared) = self.shared.upgrade() else {
+            return false;
+        };
+        let job = shared.lock().jobs.remove(&key);
+        job.is_some()
+    }
+
+    // Moving a job behind others with the same deadline runs it after them
+    fn reset(&self, deadline: Instant) -> bool {
+        let mut key = self.key();
+        let Some(shared) = self.shared.upgrade() else {
+            return false;
+        };
+        let mut state = shared.lock();
+        let Some(job) = state.jobs.remove(&key) else {
+            return false;
+        };
+        *key = shared.insert(&mut state, deadline, job);
+        true
+    }
+
+    fn is_armed(&self) -> bool {
+        let key = self.key();
+        self.shared
+            .upgrade()
+            .is_some_and(|shared| shared.lock().jobs.contains_key(&key))
+    }
+}
+
+fn run(shared: &Shared) {
+    let mut state = shared.lock();
+    while !state.shutdown {
+        let now = Instant::now();
+        match state.jobs.first_entry() {
+            Some(entry) if entry.key().0 <= now => {
+                let job = entry.remove();
+                drop(state);
+                let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                state = shared.lock();
+            }
+            _ => {
+                // The waiter stays armed for the earliest job, since
+                // scheduling re-arms it under the lock
+                shared.waiter.arm(state.next_deadline());
+                state = shared.waiter.wait(state, &shared.state);
+            }
+        }
+    }
+}
+
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    any(not(feature = "linux-raw"), feature = "linux-libc")
+))]
+mod imp {
+    use std::io;
+    use std::sync::{Mutex, MutexGuard};
+
+    use super::State;
+    use crate::{Instant, TimerClock, TimerFd};
+
+    pub(super) struct Waiter(TimerFd);
+
+    impl Waiter {
+        pub(super) fn new() -> io::Result<Waiter> {
+            TimerFd::new(TimerClock::Boot).map(Waiter)
+        }
+
+        pub(super) fn arm(&self, deadline: Option<Instant>) {
+            let _ = match deadline {
+                Some(deadline) => self.0.set_deadline(deadline),
+                None => self.0.disarm(),
+            };
+        }
+
+        // Expirations while the lock is released aren't lost, since the
+        // timer stays readable until read
+        pub(super) fn wait<'a>(
+            &self,
+            state: MutexGuard<'a, State>,
+            mutex: &'a Mutex<State>,
+        ) -> MutexGuard<'a, State> {
+            drop(state);
+            let _ = self.0.read_expirations();
+            mutex.lock().unwrap_or_else(|e| e.into_inner())
+        }
+    }
+}
+
+#[cfg(not(all(
+    any(target_os = "linux", target_os = "android"),
+    any(not(feature = "linux-raw"), feature = "linux-libc")
+)))]
+mod imp {
+    use std::io;
+    use std::sync::{Condvar, Mutex, MutexGuard};
+
+    use super::State;
+    use crate::sleep::MAX_CHUNK;
+    use crate::Instant;
+
+    pub(super) struct Waiter(Condvar);
+
+    impl Waiter {
+        pub(super) fn new() -> io::Result<Waiter> {
+            Ok(Waiter(Condvar::new()))
+        }
+
+        pub(super) fn arm(&self, _deadline: Option<Instant>) {
+            self.0.notify_all();
+        }
+
+        // Condition variables measure with a clock that may stop during
+        // suspend
+        pub(super) fn wait<'a>(
+            &self,
+            state: MutexGuard<'a, State>,
+            _mutex: &'a Mutex<State>,
+        ) -> MutexGuard<'a, State> {
+            let Some(deadline) = state.next_deadline() else {
+                return self.0.wait(state).unwrap_or_else(|e| e.into_inner());
+            };
+            let mut timeout = deadline.saturating_duration_since(Instant::now());
+            if crate::clock_source().is_suspend_aware() {
+                timeout = timeout.min(MAX_CHUNK);
+            }
+            self.0
+                .wait_timeout(state, timeout)
+                .unwrap_or_else(|e| e.into_inner())
+                .0
+        }
+    }
+}
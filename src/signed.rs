@@ -0,0 +1,149 @@
+//! Signed difference between two instants
+use core::cmp::Ordering;
+use core::ops::Neg;
+use core::time::Duration;
+use std::fmt;
+
+/// A span of time that may be negative.
+///
+/// It is returned by [`Instant::signed_duration_since`] so that out-of-order
+/// instants are representable instead of collapsing to zero. Zero is never
+/// negative.
+///
+/// [`Instant::signed_duration_since`]: crate::Instant::signed_duration_since
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{Duration, Instant, SignedDuration};
+///
+/// let a = Instant::now();
+/// let b = a + Duration::from_secs(1);
+/// assert_eq!(a.signed_duration_since(b), -SignedDuration::from(Duration::from_secs(1)));
+/// assert!(a.signed_duration_since(b).is_negative());
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SignedDuration {
+    negative: bool,
+    abs: Duration,
+}
+
+impl SignedDuration {
+    /// A zero span of time.
+    pub const ZERO: SignedDuration = SignedDuration {
+        negative: false,
+        abs: Duration::ZERO,
+    };
+
+    /// Creates a negative span of time with the given magnitude.
+    #[must_use]
+    pub const fn negative(abs: Duration) -> SignedDuration {
+        SignedDuration {
+            negative: !abs.is_zero(),
+            abs,
+        }
+    }
+
+    /// Returns `true` if the span is less than zero.
+    #[must_use]
+    pub const fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Returns the magnitude of the span.
+    #[must_use]
+    pub const fn abs(&self) -> Duration {
+        self.abs
+    }
+
+    /// Returns the sign of the span relative to zero.
+    #[must_use]
+    pub fn signum(&self) -> Ordering {
+        if self.negative {
+            Ordering::Less
+        } else if self.abs.is_zero() {
+            Ordering::Equal
+        } else {
+            Ordering::Greater
+        }
+    }
+
+    /// Returns the total number of nanoseconds in the span.
+    #[must_use]
+    pub const fn as_nanos(&self) -> i128 {
+        let nanos = self.abs.as_nanos() as i128;
+        if self.negative {
+            -nanos
+        } else {
+            nanos
+        }
+    }
+
+    /// Returns the number of seconds in the span as `f64`.
+    #[must_use]
+    pub fn as_secs_f64(&self) -> f64 {
+        let secs = self.abs.as_secs_f64();
+        if self.negative {
+            -secs
+        } else {
+            secs
+        }
+    }
+
+    /// Returns the span as a [`Duration`] if it is not negative.
+    #[must_use]
+    pub const fn to_duration(self) -> Option<Duration> {
+        if self.negative {
+            None
+        } else {
+            Some(self.abs)
+        }
+    }
+}
+
+impl From<Duration> for SignedDuration {
+    fn from(abs: Duration) -> SignedDuration {
+        SignedDuration {
+            negative: false,
+            abs,
+        }
+    }
+}
+
+impl Neg for SignedDuration {
+    type Output = SignedDuration;
+
+    fn neg(self) -> SignedDuration {
+        if self.negative {
+            SignedDuration::from(self.abs)
+        } else {
+            SignedDuration::negative(self.abs)
+        }
+    }
+}
+
+impl Ord for SignedDuration {
+    fn cmp(&self, other: &SignedDuration) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, false) => self.abs.cmp(&other.abs),
+            (true, true) => other.abs.cmp(&self.abs),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        }
+    }
+}
+
+impl PartialOrd for SignedDuration {
+    fn partial_cmp(&self, other: &SignedDuration) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Debug for SignedDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negative {
+            f.write_str("-")?;
+        }
+        fmt::Debug::fmt(&self.abs, f)
+    }
+}
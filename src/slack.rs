@@ -0,0 +1,101 @@
+//! Timer slack of the current thread
+use core::time::Duration;
+use std::io;
+
+/// Sets how much later than requested the OS may wake the current thread
+/// from timed waits, so it can coalesce wakeups to save power.
+///
+/// On Linux and Android it sets the timer slack of the thread with
+/// `prctl(PR_SET_TIMERSLACK)`. The slack applies to [`sleep`](crate::sleep),
+/// [`sleep_until`](crate::sleep_until), the waits of
+/// [`DelayQueue`](crate::DelayQueue) and other timed waits of the thread, but
+/// not to [`TimerFd`](crate::TimerFd) or [`WakeAlarm`](crate::WakeAlarm)
+/// expirations. The default is 50 µs. A zero slack restores the default, so
+/// latency-sensitive threads should pass a nanosecond to demand tight
+/// delivery. Threads inherit the slack of the thread that spawned them.
+///
+/// For a per-timer slack on Windows see
+/// [`WakeAlarm::set_with_slack`](crate::WakeAlarm::set_with_slack).
+///
+/// # Errors
+///
+/// Returns an error of kind [`io::ErrorKind::Unsupported`] on other
+/// platforms, and with the `linux-raw` feature unless `linux-libc` is
+/// enabled as well.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::Duration;
+///
+/// // a background job that doesn't care to wake up on time
+/// if boot_time::set_thread_timer_slack(Duration::from_millis(100)).is_ok() {
+///     assert_eq!(boot_time::thread_timer_slack()?, Duration::from_millis(100));
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn set_thread_timer_slack(slack: Duration) -> io::Result<()> {
+    imp::set(slack)
+}
+
+/// Returns the timer slack of the current thread, see
+/// [`set_thread_timer_slack`].
+///
+/// # Errors
+///
+/// Returns an error of kind [`io::ErrorKind::Unsupported`] on platforms
+/// other than Linux and Android, and with the `linux-raw` feature unless
+/// `linux-libc` is enabled as well.
+pub fn thread_timer_slack() -> io::Result<Duration> {
+    imp::get()
+}
+
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    any(not(feature = "linux-raw"), feature = "linux-libc")
+))]
+mod imp {
+    use core::time::Duration;
+    use std::io;
+
+    pub(super) fn set(slack: Duration) -> io::Result<()> {
+        let nanos = libc::c_ulong::try_from(slack.as_nanos()).unwrap_or(libc::c_ulong::MAX);
+        if unsafe { libc::prctl(libc::PR_SET_TIMERSLACK, nanos) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    // The slack is returned rather than stored
+    pub(super) fn get() -> io::Result<Duration> {
+        let r = unsafe { libc::prctl(libc::PR_GET_TIMERSLACK) };
+        if r < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Duration::from_nanos(r as u64))
+    }
+}
+
+#[cfg(not(all(
+    any(target_os = "linux", target_os = "android"),
+    any(not(feature = "linux-raw"), feature = "linux-libc")
+)))]
+mod imp {
+    use core::time::Duration;
+    use std::io;
+
+    pub(super) fn set(_slack: Duration) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub(super) fn get() -> io::Result<Duration> {
+        Err(unsupported())
+    }
+
+    fn unsupported() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the timer slack is not available on this platform",
+        )
+    }
+}
@@ -0,0 +1,175 @@
+//! Sleeping that counts the time the system was suspended
+use core::hint;
+use core::time::Duration;
+use std::thread;
+
+use crate::{Deadline, Instant};
+
+// Where the OS can only sleep on a clock that stops while the system is
+// suspended, a sleep overshoots by at most this after resume
+pub(crate) const MAX_CHUNK: Duration = Duration::from_secs(1);
+
+// How late the OS wakes a sleeping thread at most in practice
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const DEFAULT_SPIN_THRESHOLD: Duration = Duration::from_micros(250);
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const DEFAULT_SPIN_THRESHOLD: Duration = Duration::from_millis(1);
+
+/// Puts the current thread to sleep for at least the specified amount of
+/// time, measured like [`Instant`], including the time the system was
+/// suspended.
+///
+/// [`std::thread::sleep`] measures with a clock that stops during suspend on
+/// most platforms, so a 10 minute sleep interrupted by an hour of suspend
+/// returns after 70 minutes. This function returns shortly after resume
+/// instead.
+///
+/// It sleeps until `Instant::now() + dur` with [`sleep_until`].
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{Duration, Instant};
+///
+/// let start = Instant::now();
+/// boot_time::sleep(Duration::from_millis(10));
+/// assert!(start.elapsed() >= Duration::from_millis(10));
+/// ```
+pub fn sleep(dur: Duration) {
+    let now = Instant::now();
+    sleep_until(now.checked_add(dur).unwrap_or_else(Instant::far_future));
+}
+
+/// Puts the current thread to sleep until the deadline has been reached.
+///
+/// The deadline is passed to the OS as an absolute time where possible, so
+/// time passing between computing it and going to sleep, including a
+/// suspend, doesn't delay the wakeup. A deadline in the past returns
+/// immediately.
+///
+/// On Linux and Android the thread sleeps with
+/// `clock_nanosleep(TIMER_ABSTIME)` on the clock of [`Instant`], which is
+/// resumed after signals. Elsewhere it sleeps in chunks of at most one second
+/// when [`Instant`] counts suspended time, re-checking the clock after each,
+/// so it may return up to a second late after resume.
+///
+/// # Examples
+///
+/// A rate limiter sleeping until the next slot:
+///
+/// ```
+/// use boot_time::{Duration, Instant};
+///
+/// let mut next = Instant::now();
+/// for _ in 0..3 {
+///     boot_time::sleep_until(next);
+///     // send a request
+///     next += Duration::from_millis(5);
+/// }
+/// ```
+pub fn sleep_until(deadline: impl Into<Deadline>) {
+    let deadline = deadline.into().instant();
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if crate::sys::sleep_until(&deadline.0) {
+        return;
+    }
+    let chunk = if crate::clock_source().is_suspend_aware() {
+        MAX_CHUNK
+    } else {
+        Duration::MAX
+    };
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            return;
+        }
+        thread::sleep((deadline - now).min(chunk));
+    }
+}
+
+/// Puts the current thread to sleep until the deadline has been reached,
+/// spinning for the last part of the wait to return as close to the
+/// deadline as possible.
+///
+/// It uses a [`SpinSleeper`] with the default spin threshold.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{Duration, Instant};
+///
+/// let deadline = Instant::now() + Duration::from_millis(5);
+/// boot_time::sleep_until_precise(deadline);
+/// assert!(Instant::now() >= deadline);
+/// ```
+pub fn sleep_until_precise(deadline: impl Into<Deadline>) {
+    SpinSleeper::default().sleep_until(deadline);
+}
+
+/// Sleeps with better precision than the OS scheduler, at the cost of
+/// spinning on a CPU.
+///
+/// The thread sleeps with [`sleep_until`] until the spin threshold before
+/// the deadline, then spins on [`Instant::now`] for the rest. The threshold
+/// should cover how late the OS wakes threads: the default is 250 µs on Linux
+/// and Android and 1 ms elsewhere. Like [`sleep_until`], it counts the time
+/// the system was suspended.
+///
+/// # Examples
+///
+/// An audio loop producing a buffer every 2 ms:
+///
+/// ```
+/// use boot_time::{Duration, Instant, SpinSleeper};
+///
+/// let sleeper = SpinSleeper::new(Duration::from_micros(500));
+/// let mut next = Instant::now();
+/// for _ in 0..3 {
+///     // fill the buffer
+///     next += Duration::from_millis(2);
+///     sleeper.sleep_until(next);
+/// }
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct SpinSleeper {
+    threshold: Duration,
+}
+
+impl SpinSleeper {
+    /// Creates a sleeper that spins for the last `threshold` before a
+    /// deadline.
+    #[must_use]
+    pub fn new(threshold: Duration) -> SpinSleeper {
+        SpinSleeper { threshold }
+    }
+
+    /// Returns the spin threshold.
+    #[must_use]
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    /// Puts the current thread to sleep until the deadline has been reached.
+    pub fn sleep_until(&self, deadline: impl Into<Deadline>) {
+        let deadline = deadline.into().instant();
+        if let Some(wake) = deadline.checked_sub(self.threshold) {
+            sleep_until(wake);
+        }
+        while Instant::now() < deadline {
+            hint::spin_loop();
+        }
+    }
+
+    /// Puts the current thread to sleep for at least the specified amount of
+    /// time.
+    pub fn sleep(&self, dur: Duration) {
+        let now = Instant::now();
+        self.sleep_until(now.checked_add(dur).unwrap_or_else(Instant::far_future));
+    }
+}
+
+impl Default for SpinSleeper {
+    fn default() -> SpinSleeper {
+        SpinSleeper::new(DEFAULT_SPIN_THRESHOLD)
+    }
+}
@@ -0,0 +1,93 @@
+//! Introspection of the clock behind `Instant`
+
+/// The clock [`Instant`](crate::Instant) reads on the running system.
+///
+/// Some platforms select the clock at runtime, e.g. Linux falls back to
+/// `CLOCK_MONOTONIC` when `CLOCK_BOOTTIME` is rejected. Logging the source
+/// makes such fallbacks visible.
+///
+/// ```
+/// use boot_time::clock_source;
+///
+/// let source = clock_source();
+/// if !source.is_suspend_aware() {
+///     eprintln!("{source:?} doesn't count suspended time");
+/// }
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[non_exhaustive]
+pub enum ClockSource {
+    /// `clock_gettime(CLOCK_BOOTTIME)`
+    BootTime,
+    /// `clock_gettime(CLOCK_MONOTONIC)`
+    Monotonic,
+    /// `mach_continuous_time` on Apple platforms
+    MachContinuous,
+    /// `mach_absolute_time` on Apple platforms older than macOS 10.12
+    MachAbsolute,
+    /// `QueryInterruptTimePrecise` on Windows 10 and later
+    InterruptTime,
+    /// `QueryPerformanceCounter` corrected by the suspended time on Windows 7
+    /// and 8
+    CorrectedQpc,
+    /// Plain `QueryPerformanceCounter` on older Windows versions
+    Qpc,
+    /// `QueryPerformanceCounter` on Windows 8 with Modern Standby, which
+    /// keeps counting in standby
+    StandbyQpc,
+    /// `gethrtime` on illumos and Solaris
+    Gethrtime,
+    /// `system_time` on Haiku
+    HaikuSystemTime,
+    /// The boot timeline on Fuchsia
+    FuchsiaBoot,
+    /// The monotonic timeline on older Fuchsia versions
+    FuchsiaMonotonic,
+    /// The monotonic clock of WASI preview1 or preview2
+    WasiMonotonic,
+    /// `performance.now()` in browsers
+    PerformanceNow,
+    /// `esp_timer_get_time` on ESP-IDF, behind `CLOCK_MONOTONIC`
+    EspTimer,
+    /// The `insecure_time` usercall in Fortanix SGX enclaves
+    SgxInsecureTime,
+    /// The firmware timer on UEFI
+    UefiTimer,
+    /// The ticktimer service on Xous
+    XousTicktimer,
+    /// The clock registered with `register_custom_clock!`
+    Custom,
+    /// `std::time::Instant` on platforms without a backend
+    StdFallback,
+}
+
+impl ClockSource {
+    /// Returns `true` if the clock counts the time the system was suspended.
+    ///
+    /// Clocks of systems without suspend support, and clocks whose behavior
+    /// across suspend is unknown, are reported as not suspend-aware.
+    pub fn is_suspend_aware(self) -> bool {
+        match self {
+            ClockSource::BootTime
+            | ClockSource::MachContinuous
+            | ClockSource::InterruptTime
+            | ClockSource::CorrectedQpc
+            | ClockSource::StandbyQpc
+            | ClockSource::FuchsiaBoot
+            | ClockSource::EspTimer
+            | ClockSource::SgxInsecureTime
+            | ClockSource::XousTicktimer => true,
+            ClockSource::Monotonic
+            | ClockSource::MachAbsolute
+            | ClockSource::Qpc
+            | ClockSource::Gethrtime
+            | ClockSource::HaikuSystemTime
+            | ClockSource::FuchsiaMonotonic
+            | ClockSource::WasiMonotonic
+            | ClockSource::PerformanceNow
+            | ClockSource::UefiTimer
+            | ClockSource::Custom
+            | ClockSource::StdFallback => false,
+        }
+    }
+}
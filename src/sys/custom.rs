@@ -0,0 +1,53 @@
+pub use crate::sys_common::nanos::Instant;
+use crate::ClockSource;
+use std::io;
+use std::time::Duration;
+
+// Nothing is known about the registered clock
+pub fn epoch_is_boot() -> bool {
+    false
+}
+
+pub const SUSPEND_AWARE: bool = false;
+
+pub fn clock_source() -> ClockSource {
+    ClockSource::Custom
+}
+
+// The registered clock is the only time source
+pub fn monotonic_now() -> Duration {
+    Instant::now().duration_since_epoch()
+}
+
+pub fn raw_monotonic_now() -> Duration {
+    monotonic_now()
+}
+
+pub fn suspended_time() -> Duration {
+    Duration::ZERO
+}
+
+extern "Rust" {
+    // Defined by `register_custom_clock!`
+    fn __boot_time_custom_now() -> u64;
+}
+
+impl Instant {
+    pub fn now() -> Instant {
+        Instant::from_nanos(unsafe { __boot_time_custom_now() })
+    }
+
+    // The registered function cannot report errors
+    pub fn try_now() -> io::Result<Instant> {
+        Ok(Instant::now())
+    }
+
+    pub fn now_coarse() -> Instant {
+        Instant::now()
+    }
+
+    // The precision of the registered clock is unknown
+    pub fn resolution() -> Duration {
+        Duration::from_nanos(1)
+    }
+}
@@ -0,0 +1,87 @@
+pub use crate::sys_common::nanos::Instant;
+#[cfg(feature = "libc")]
+pub use crate::sys_common::read_clock;
+pub use crate::sys_common::{process_cpu_now, thread_cpu_now};
+use crate::ClockSource;
+use std::io;
+use std::mem;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+// Both the boot and the monotonic timelines start at boot
+pub fn epoch_is_boot() -> bool {
+    true
+}
+
+pub const SUSPEND_AWARE: bool = true;
+
+type ClockGetFn = unsafe extern "C" fn() -> i64;
+
+pub fn clock_source() -> ClockSource {
+    clock().1
+}
+
+// The monotonic timeline pauses while the system is suspended and isn't slewed
+pub fn monotonic_now() -> Duration {
+    Duration::from_nanos(unsafe { zx_clock_get_monotonic() } as u64)
+}
+
+pub fn raw_monotonic_now() -> Duration {
+    monotonic_now()
+}
+
+// The boot timeline runs ahead of the monotonic one by the suspended time
+pub fn suspended_time() -> Duration {
+    match clock() {
+        (boot, ClockSource::FuchsiaBoot) => {
+            let boot = unsafe { boot() };
+            let monotonic = unsafe { zx_clock_get_monotonic() };
+            Duration::from_nanos(boot.saturating_sub(monotonic) as u64)
+        }
+        _ => Duration::ZERO,
+    }
+}
+
+#[link(name = "zircon")]
+extern "C" {
+    fn zx_clock_get_monotonic() -> i64;
+}
+
+// Returns `zx_clock_get_boot`, which includes the time the system was
+// suspended, if the vDSO provides it. Older Fuchsia versions only have the
+// monotonic timeline that pauses during suspend.
+fn clock() -> (ClockGetFn, ClockSource) {
+    static CLOCK: OnceLock<(ClockGetFn, ClockSource)> = OnceLock::new();
+    *CLOCK.get_or_init(|| {
+        let f = unsafe { libc::dlsym(libc::RTLD_DEFAULT, c"zx_clock_get_boot".as_ptr()) };
+        if f.is_null() {
+            (zx_clock_get_monotonic, ClockSource::FuchsiaMonotonic)
+        } else {
+            // SAFETY: `zx_clock_get_boot` has the `ClockGetFn` signature
+            let f = unsafe { mem::transmute::<*mut libc::c_void, ClockGetFn>(f) };
+            (f, ClockSource::FuchsiaBoot)
+        }
+    })
+}
+
+impl Instant {
+    pub fn now() -> Instant {
+        // The timelines are never negative
+        Instant::from_nanos(unsafe { (clock().0)() } as u64)
+    }
+
+    // Reading the clock cannot fail
+    pub fn try_now() -> io::Result<Instant> {
+        Ok(Instant::now())
+    }
+
+    // The clock is read in the vDSO and is cheap already
+    pub fn now_coarse() -> Instant {
+        Instant::now()
+    }
+
+    // Timelines are measured in nanoseconds
+    pub fn resolution() -> Duration {
+        Duration::from_nanos(1)
+    }
+}
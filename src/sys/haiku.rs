@@ -0,0 +1,58 @@
+pub use crate::sys_common::nanos::Instant;
+#[cfg(feature = "libc")]
+pub use crate::sys_common::read_clock;
+pub use crate::sys_common::{process_cpu_now, thread_cpu_now};
+use crate::ClockSource;
+use std::io;
+use std::time::Duration;
+
+const NSEC_PER_USEC: u64 = 1_000;
+
+// `system_time` counts from boot. Haiku doesn't account for suspended time.
+pub fn epoch_is_boot() -> bool {
+    true
+}
+
+pub const SUSPEND_AWARE: bool = false;
+
+pub fn clock_source() -> ClockSource {
+    ClockSource::HaikuSystemTime
+}
+
+// `system_time` is the only monotonic clock and it isn't slewed
+pub fn monotonic_now() -> Duration {
+    Instant::now().duration_since_epoch()
+}
+
+pub fn raw_monotonic_now() -> Duration {
+    monotonic_now()
+}
+
+pub fn suspended_time() -> Duration {
+    Duration::ZERO
+}
+
+extern "C" {
+    // Microseconds since boot
+    fn system_time() -> i64;
+}
+
+impl Instant {
+    pub fn now() -> Instant {
+        Instant::from_nanos(unsafe { system_time() } as u64 * NSEC_PER_USEC)
+    }
+
+    // `system_time` cannot fail
+    pub fn try_now() -> io::Result<Instant> {
+        Ok(Instant::now())
+    }
+
+    // `system_time` reads the commpage and is cheap already
+    pub fn now_coarse() -> Instant {
+        Instant::now()
+    }
+
+    pub fn resolution() -> Duration {
+        Duration::from_nanos(NSEC_PER_USEC)
+    }
+}
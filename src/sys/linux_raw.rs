@@ -0,0 +1,409 @@
+//! Linux backend that reads the clocks without going through libc.
+//!
+//! `clock_gettime` is called in the vDSO if the kernel maps one, otherwise the
+//! system call is issued directly. Sleeping issues `clock_nanosleep` directly
+//! as well, so the backend works without the `libc` crate.
+pub use crate::sys_common::nanos::Instant;
+use crate::ClockSource;
+use std::arch::asm;
+use std::ffi::CStr;
+use std::io;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+const NSEC_PER_SEC: u64 = 1_000_000_000;
+
+const CLOCK_MONOTONIC: i32 = 1;
+const CLOCK_PROCESS_CPUTIME_ID: i32 = 2;
+const CLOCK_THREAD_CPUTIME_ID: i32 = 3;
+const CLOCK_MONOTONIC_RAW: i32 = 4;
+const CLOCK_BOOTTIME: i32 = 7;
+
+const TIMER_ABSTIME: usize = 1;
+const EINTR: i32 = 4;
+
+#[cfg(target_arch = "x86_64")]
+mod nr {
+    pub const CLOCK_GETTIME: usize = 228;
+    pub const CLOCK_GETRES: usize = 229;
+    pub const CLOCK_NANOSLEEP: usize = 230;
+}
+#[cfg(any(target_arch = "aarch64", target_arch = "riscv64"))]
+mod nr {
+    pub const CLOCK_GETTIME: usize = 113;
+    pub const CLOCK_GETRES: usize = 114;
+    pub const CLOCK_NANOSLEEP: usize = 115;
+}
+
+#[cfg(target_arch = "x86_64")]
+const VDSO_CLOCK_GETTIME: &CStr = c"__vdso_clock_gettime";
+#[cfg(target_arch = "aarch64")]
+const VDSO_CLOCK_GETTIME: &CStr = c"__kernel_clock_gettime";
+#[cfg(target_arch = "riscv64")]
+const VDSO_CLOCK_GETTIME: &CStr = c"__vdso_clock_gettime";
+
+// Both clocks start at boot, CLOCK_MONOTONIC just doesn't advance while the
+// system is suspended
+pub fn epoch_is_boot() -> bool {
+    true
+}
+
+pub const SUSPEND_AWARE: bool = !cfg!(feature = "force-monotonic");
+
+pub fn clock_source() -> ClockSource {
+    if clock_id() == CLOCK_BOOTTIME {
+        ClockSource::BootTime
+    } else {
+        ClockSource::Monotonic
+    }
+}
+
+// `struct __kernel_timespec`, which is `struct timespec` on 64-bit targets
+#[repr(C)]
+#[derive(Default)]
+struct Timespec {
+    tv_sec: i64,
+    tv_nsec: i64,
+}
+
+// `int clock_gettime(clockid_t, struct timespec *)`. The upper half of the
+// return register is unspecified, so the result must be read as an `int`.
+type ClockGettimeFn = unsafe extern "C" fn(i32, *mut Timespec) -> i32;
+
+unsafe fn syscall2(nr: usize, a0: usize, a1: usize) -> isize {
+    syscall4(nr, a0, a1, 0, 0)
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn syscall4(nr: usize, a0: usize, a1: usize, a2: usize, a3: usize) -> isize {
+    let ret;
+    asm!(
+        "syscall",
+        inlateout("rax") nr as isize => ret,
+        in("rdi") a0,
+        in("rsi") a1,
+        in("rdx") a2,
+        in("r10") a3,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nostack),
+    );
+    ret
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn syscall4(nr: usize, a0: usize, a1: usize, a2: usize, a3: usize) -> isize {
+    let ret;
+    asm!(
+        "svc 0",
+        in("x8") nr,
+        inlateout("x0") a0 as isize => ret,
+        in("x1") a1,
+        in("x2") a2,
+        in("x3") a3,
+        options(nostack),
+    );
+    ret
+}
+
+#[cfg(target_arch = "riscv64")]
+unsafe fn syscall4(nr: usize, a0: usize, a1: usize, a2: usize, a3: usize) -> isize {
+    let ret;
+    asm!(
+        "ecall",
+        in("a7") nr,
+        inlateout("a0") a0 as isize => ret,
+        in("a1") a1,
+        in("a2") a2,
+        in("a3") a3,
+        options(nostack),
+    );
+    ret
+}
+
+// The kernel and the vDSO return `-errno` on failure
+fn cvt(ret: i32) -> io::Result<()> {
+    if ret < 0 {
+        Err(io::Error::from_raw_os_error(-ret))
+    } else {
+        Ok(())
+    }
+}
+
+// The system call returns a `long`, `-errno` always fits in an `int`. The
+// clock id is sign-extended, the kernel only looks at the lower 32 bits.
+unsafe extern "C" fn clock_gettime_syscall(clock: i32, t: *mut Timespec) -> i32 {
+    syscall2(nr::CLOCK_GETTIME, clock as usize, t as usize) as i32
+}
+
+fn clock_gettime(clock: i32) -> io::Result<Timespec> {
+    static CLOCK_GETTIME: OnceLock<ClockGettimeFn> = OnceLock::new();
+    let f = *CLOCK_GETTIME.get_or_init(|| vdso::lookup().unwrap_or(clock_gettime_syscall));
+    let mut t = Timespec::default();
+    cvt(unsafe { f(clock, &mut t) })?;
+    Ok(t)
+}
+
+// Returns the clock used by `Instant::now`, which is probed once.
+// CLOCK_BOOTTIME appeared in Linux 2.6.39 and may be rejected by seccomp
+// filters, CLOCK_MONOTONIC is used then and if suspended time shouldn't
+// count.
+fn clock_id() -> i32 {
+    static CLOCK_ID: OnceLock<i32> = OnceLock::new();
+    *CLOCK_ID.get_or_init(|| {
+        if crate::config::prefer_suspend_aware() && clock_gettime(CLOCK_BOOTTIME).is_ok() {
+            CLOCK_BOOTTIME
+        } else {
+            CLOCK_MONOTONIC
+        }
+    })
+}
+
+pub fn monotonic_now() -> Duration {
+    Duration::from_nanos(timespec_to_nanos(clock_gettime(CLOCK_MONOTONIC).unwrap()))
+}
+
+pub fn raw_monotonic_now() -> Duration {
+    Duration::from_nanos(timespec_to_nanos(
+        clock_gettime(CLOCK_MONOTONIC_RAW).unwrap(),
+    ))
+}
+
+pub fn suspended_time() -> Duration {
+    match (
+        clock_gettime(CLOCK_BOOTTIME),
+        clock_gettime(CLOCK_MONOTONIC),
+    ) {
+        (Ok(boot), Ok(monotonic)) => Duration::from_nanos(
+            timespec_to_nanos(boot).saturating_sub(timespec_to_nanos(monotonic)),
+        ),
+        _ => Duration::ZERO,
+    }
+}
+
+// Returns `false` if the kernel rejects the clock or the deadline
+pub fn sleep_until(deadline: &Instant) -> bool {
+    let Some(nanos) = deadline.as_nanos() else {
+        return false;
+    };
+    let t = Timespec {
+        tv_sec: (nanos / NSEC_PER_SEC) as i64,
+        tv_nsec: (nanos % NSEC_PER_SEC) as i64,
+    };
+    loop {
+        let r = unsafe {
+            syscall4(
+                nr::CLOCK_NANOSLEEP,
+                clock_id() as usize,
+                TIMER_ABSTIME,
+                &t as *const Timespec as usize,
+                0,
+            )
+        } as i32;
+        // The deadline is absolute, so the sleep is simply repeated
+        if r != -EINTR {
+            return r == 0;
+        }
+    }
+}
+
+pub fn process_cpu_now() -> Duration {
+    Duration::from_nanos(timespec_to_nanos(
+        clock_gettime(CLOCK_PROCESS_CPUTIME_ID).unwrap(),
+    ))
+}
+
+pub fn thread_cpu_now() -> Duration {
+    Duration::from_nanos(timespec_to_nanos(
+        clock_gettime(CLOCK_THREAD_CPUTIME_ID).unwrap(),
+    ))
+}
+
+// `clockid_t` is an `int` on Linux
+#[cfg(feature = "libc")]
+pub fn read_clock(clock: i32) -> io::Result<Duration> {
+    let t = clock_gettime(clock)?;
+    crate::sys_common::duration_from_parts(t.tv_sec, t.tv_nsec)
+}
+
+fn timespec_to_nanos(t: Timespec) -> u64 {
+    // The clocks are never negative
+    t.tv_sec as u64 * NSEC_PER_SEC + t.tv_nsec as u64
+}
+
+impl Instant {
+    pub fn now() -> Instant {
+        Instant::try_now().unwrap()
+    }
+
+    pub fn try_now() -> io::Result<Instant> {
+        let clock = clock_id();
+        Ok(Instant::from_clock_nanos(
+            clock.into(),
+            timespec_to_nanos(clock_gettime(clock)?),
+        ))
+    }
+
+    // There is no coarse variant of CLOCK_BOOTTIME. It is served by the vDSO
+    // anyway.
+    pub fn now_coarse() -> Instant {
+        Instant::now()
+    }
+
+    pub fn resolution() -> Duration {
+        let mut t = Timespec::default();
+        cvt(unsafe {
+            syscall2(
+                nr::CLOCK_GETRES,
+                clock_id() as usize,
+                &mut t as *mut Timespec as usize,
+            ) as i32
+        })
+        .unwrap();
+        Duration::from_nanos(timespec_to_nanos(t))
+    }
+}
+
+// Minimal ELF parser that finds `clock_gettime` in the vDSO, along the lines
+// of `tools/testing/selftests/vDSO/parse_vdso.c` in the kernel tree.
+#[allow(dead_code)] // the ELF structures mirror <elf.h>, not all fields are read
+mod vdso {
+    use super::{ClockGettimeFn, VDSO_CLOCK_GETTIME};
+    use std::ffi::{c_ulong, CStr};
+    use std::mem;
+
+    const AT_SYSINFO_EHDR: c_ulong = 33;
+
+    const PT_LOAD: u32 = 1;
+    const PT_DYNAMIC: u32 = 2;
+
+    const DT_NULL: i64 = 0;
+    const DT_HASH: i64 = 4;
+    const DT_STRTAB: i64 = 5;
+    const DT_SYMTAB: i64 = 6;
+
+    const STT_FUNC: u8 = 2;
+    const STB_GLOBAL: u8 = 1;
+    const STB_WEAK: u8 = 2;
+    const SHN_UNDEF: u16 = 0;
+
+    #[repr(C)]
+    struct Elf64Ehdr {
+        e_ident: [u8; 16],
+        e_type: u16,
+        e_machine: u16,
+        e_version: u32,
+        e_entry: u64,
+        e_phoff: u64,
+        e_shoff: u64,
+        e_flags: u32,
+        e_ehsize: u16,
+        e_phentsize: u16,
+        e_phnum: u16,
+        e_shentsize: u16,
+        e_shnum: u16,
+        e_shstrndx: u16,
+    }
+
+    #[repr(C)]
+    struct Elf64Phdr {
+        p_type: u32,
+        p_flags: u32,
+        p_offset: u64,
+        p_vaddr: u64,
+        p_paddr: u64,
+        p_filesz: u64,
+        p_memsz: u64,
+        p_align: u64,
+    }
+
+    #[repr(C)]
+    struct Elf64Dyn {
+        d_tag: i64,
+        d_val: u64,
+    }
+
+    #[repr(C)]
+    struct Elf64Sym {
+        st_name: u32,
+        st_info: u8,
+        st_other: u8,
+        st_shndx: u16,
+        st_value: u64,
+        st_size: u64,
+    }
+
+    // glibc, musl and bionic all export `getauxval`, and std links the C
+    // library anyway. Unlike `/proc/self/auxv` it works without procfs.
+    extern "C" {
+        fn getauxval(kind: c_ulong) -> c_ulong;
+    }
+
+    // Reads the address of the vDSO image from the auxiliary vector the
+    // process was started with
+    fn sysinfo_ehdr() -> Option<usize> {
+        let base = unsafe { getauxval(AT_SYSINFO_EHDR) } as usize;
+        (base != 0).then_some(base)
+    }
+
+    pub(super) fn lookup() -> Option<ClockGettimeFn> {
+        let base = sysinfo_ehdr()?;
+        // SAFETY: the kernel maps a valid ELF image at AT_SYSINFO_EHDR
+        unsafe { find_symbol(base, VDSO_CLOCK_GETTIME) }
+            .map(|f| unsafe { mem::transmute::<usize, ClockGettimeFn>(f) })
+    }
+
+    unsafe fn find_symbol(base: usize, name: &CStr) -> Option<usize> {
+        let ehdr = &*(base as *const Elf64Ehdr);
+        if ehdr.e_ident[..4] != *b"\x7fELF" {
+            return None;
+        }
+        let phdrs = std::slice::from_raw_parts(
+            (base + ehdr.e_phoff as usize) as *const Elf64Phdr,
+            ehdr.e_phnum as usize,
+        );
+
+        let mut load_offset = None;
+        let mut dynamic = None;
+        for phdr in phdrs {
+            match phdr.p_type {
+                PT_LOAD if load_offset.is_none() => {
+                    load_offset =
+                        Some((base + phdr.p_offset as usize).wrapping_sub(phdr.p_vaddr as usize));
+                }
+                PT_DYNAMIC => dynamic = Some((base + phdr.p_offset as usize) as *const Elf64Dyn),
+                _ => {}
+            }
+        }
+        let (load_offset, mut dynamic) = (load_offset?, dynamic?);
+
+        let (mut hash, mut strtab, mut symtab) = (None, None, None);
+        loop {
+            let entry = &*dynamic;
+            let ptr = load_offset.wrapping_add(entry.d_val as usize);
+            match entry.d_tag {
+                DT_NULL => break,
+                DT_HASH => hash = Some(ptr as *const u32),
+                DT_STRTAB => strtab = Some(ptr as *const u8),
+                DT_SYMTAB => symtab = Some(ptr as *const Elf64Sym),
+                _ => {}
+            }
+            dynamic = dynamic.add(1);
+        }
+        let (hash, strtab, symtab) = (hash?, strtab?, symtab?);
+
+        // The second word of the hash table is the number of symbols
+        let nchain = *hash.add(1) as usize;
+        let symbols = std::slice::from_raw_parts(symtab, nchain);
+        symbols
+            .iter()
+            .find(|sym| {
+                let binding = sym.st_info >> 4;
+                sym.st_info & 0xf == STT_FUNC
+                    && (binding == STB_GLOBAL || binding == STB_WEAK)
+                    && sym.st_shndx != SHN_UNDEF
+                    && CStr::from_ptr(strtab.add(sym.st_name as usize).cast()) == name
+            })
+            .map(|sym| load_offset.wrapping_add(sym.st_value as usize))
+    }
+}
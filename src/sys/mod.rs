@@ -1,6 +1,55 @@
 cfg_if::cfg_if! {
-    if #[cfg(unix)] {
+    if #[cfg(any(target_os = "illumos", target_os = "solaris"))] {
+        mod solaris;
+        pub use self::solaris::*;
+    } else if #[cfg(target_os = "haiku")] {
+        mod haiku;
+        pub use self::haiku::*;
+    } else if #[cfg(target_os = "fuchsia")] {
+        mod fuchsia;
+        pub use self::fuchsia::*;
+    } else if #[cfg(all(
+        feature = "linux-raw",
+        any(target_os = "linux", target_os = "android"),
+        any(target_arch = "x86_64", target_arch = "aarch64", target_arch = "riscv64"),
+    ))] {
+        mod linux_raw;
+        pub use self::linux_raw::*;
+    } else if #[cfg(unix)] {
         mod unix;
         pub use self::unix::*;
+    } else if #[cfg(windows)] {
+        mod windows;
+        pub use self::windows::*;
+    } else if #[cfg(all(target_os = "wasi", not(target_env = "p2")))] {
+        mod wasi;
+        pub use self::wasi::*;
+    } else if #[cfg(all(target_os = "wasi", target_env = "p2"))] {
+        mod wasip2;
+        pub use self::wasip2::*;
+    } else if #[cfg(all(target_vendor = "fortanix", target_env = "sgx"))] {
+        mod sgx;
+        pub use self::sgx::*;
+    } else if #[cfg(target_os = "uefi")] {
+        mod uefi;
+        pub use self::uefi::*;
+    } else if #[cfg(target_os = "xous")] {
+        mod xous;
+        pub use self::xous::*;
+    } else if #[cfg(all(target_arch = "wasm32", target_os = "unknown", feature = "web"))] {
+        mod web;
+        pub use self::web::*;
+    } else if #[cfg(feature = "custom")] {
+        mod custom;
+        pub use self::custom::*;
     }
 }
+
+// Looks up `__clock_gettime64`, which older C libraries lack
+#[cfg(all(
+    target_os = "linux",
+    any(target_env = "gnu", target_env = "musl"),
+    target_pointer_width = "32",
+    not(target_arch = "riscv32")
+))]
+mod weak;
@@ -0,0 +1,66 @@
+pub use crate::sys_common::nanos::Instant;
+use crate::ClockSource;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// The enclave has no clock of its own. Time comes from the untrusted host via
+// the `insecure_time` usercall, which reports the host's wall clock. Its epoch
+// is the UNIX epoch and it keeps advancing while the host is suspended.
+pub fn epoch_is_boot() -> bool {
+    false
+}
+
+pub const SUSPEND_AWARE: bool = true;
+
+pub fn clock_source() -> ClockSource {
+    ClockSource::SgxInsecureTime
+}
+
+// The host wall clock is the only time source
+pub fn monotonic_now() -> Duration {
+    Instant::now().duration_since_epoch()
+}
+
+pub fn raw_monotonic_now() -> Duration {
+    monotonic_now()
+}
+
+pub fn suspended_time() -> Duration {
+    Duration::ZERO
+}
+
+// The host controls the returned time and may move it backwards, stop it or
+// jump ahead. Only the monotonicity is enforced here, the elapsed time can't
+// be trusted for security decisions.
+fn insecure_time() -> u64 {
+    static LAST: AtomicU64 = AtomicU64::new(0);
+
+    // `SystemTime::now` is the `insecure_time` usercall on SGX
+    let t = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+    let prev = LAST.fetch_max(t, Ordering::Relaxed);
+    prev.max(t)
+}
+
+impl Instant {
+    pub fn now() -> Instant {
+        Instant::from_nanos(insecure_time())
+    }
+
+    // The usercall cannot fail
+    pub fn try_now() -> io::Result<Instant> {
+        Ok(Instant::now())
+    }
+
+    // Every read is a usercall, there is no cheaper source
+    pub fn now_coarse() -> Instant {
+        Instant::now()
+    }
+
+    // The usercall reports nanoseconds, the actual precision depends on the host
+    pub fn resolution() -> Duration {
+        Duration::from_nanos(1)
+    }
+}
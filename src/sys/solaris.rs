@@ -0,0 +1,65 @@
+pub use crate::sys_common::nanos::Instant;
+#[cfg(feature = "libc")]
+pub use crate::sys_common::read_clock;
+pub use crate::sys_common::{process_cpu_now, thread_cpu_now};
+use crate::ClockSource;
+use std::io;
+use std::mem::MaybeUninit;
+use std::time::Duration;
+
+// illumos and Solaris don't define how the high-resolution time behaves
+// across suspend, which they rarely support. Its epoch is arbitrary.
+pub fn epoch_is_boot() -> bool {
+    false
+}
+
+pub const SUSPEND_AWARE: bool = false;
+
+pub fn clock_source() -> ClockSource {
+    ClockSource::Gethrtime
+}
+
+// `gethrtime` is the only monotonic clock and it isn't slewed
+pub fn monotonic_now() -> Duration {
+    Instant::now().duration_since_epoch()
+}
+
+pub fn raw_monotonic_now() -> Duration {
+    monotonic_now()
+}
+
+pub fn suspended_time() -> Duration {
+    Duration::ZERO
+}
+
+extern "C" {
+    // Nanoseconds since an arbitrary time in the past, never negative
+    fn gethrtime() -> i64;
+}
+
+impl Instant {
+    pub fn now() -> Instant {
+        Instant::from_nanos(unsafe { gethrtime() } as u64)
+    }
+
+    // `gethrtime` cannot fail
+    pub fn try_now() -> io::Result<Instant> {
+        Ok(Instant::now())
+    }
+
+    // `gethrtime` is a fast trap already
+    pub fn now_coarse() -> Instant {
+        Instant::now()
+    }
+
+    pub fn resolution() -> Duration {
+        // CLOCK_HIGHRES is the same clock as `gethrtime` and has the same id
+        // as CLOCK_MONOTONIC
+        let mut t = MaybeUninit::uninit();
+        if unsafe { libc::clock_getres(libc::CLOCK_MONOTONIC, t.as_mut_ptr()) } != 0 {
+            panic!("clock_getres: {}", io::Error::last_os_error());
+        }
+        let t = unsafe { t.assume_init() };
+        Duration::new(t.tv_sec as u64, t.tv_nsec as u32)
+    }
+}
@@ -0,0 +1,61 @@
+pub use crate::sys_common::nanos::Instant;
+use crate::ClockSource;
+use std::io;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+// UEFI firmware runs before any OS suspend support exists, so no suspended
+// time can be missed. The clock counts from the first reading.
+pub fn epoch_is_boot() -> bool {
+    false
+}
+
+pub const SUSPEND_AWARE: bool = false;
+
+pub fn clock_source() -> ClockSource {
+    ClockSource::UefiTimer
+}
+
+// The firmware provides a single time source
+pub fn monotonic_now() -> Duration {
+    Instant::now().duration_since_epoch()
+}
+
+pub fn raw_monotonic_now() -> Duration {
+    monotonic_now()
+}
+
+pub fn suspended_time() -> Duration {
+    Duration::ZERO
+}
+
+// Returns the time elapsed since the first call.
+//
+// `std` reads `EFI_TIMESTAMP_PROTOCOL` and falls back to the TSC calibrated
+// with the boot services timer on x86. Its `Instant` is opaque, so the
+// nanoseconds are measured from an anchor taken on the first call.
+fn elapsed() -> Duration {
+    static ANCHOR: OnceLock<std::time::Instant> = OnceLock::new();
+    ANCHOR.get_or_init(std::time::Instant::now).elapsed()
+}
+
+impl Instant {
+    pub fn now() -> Instant {
+        Instant::from_nanos(elapsed().as_nanos() as u64)
+    }
+
+    // `std` panics if the firmware provides no time source at all
+    pub fn try_now() -> io::Result<Instant> {
+        Ok(Instant::now())
+    }
+
+    pub fn now_coarse() -> Instant {
+        Instant::now()
+    }
+
+    // The timestamp protocol and the TSC run at least at 1 MHz, usually much
+    // faster, and the frequency isn't exposed
+    pub fn resolution() -> Duration {
+        Duration::from_micros(1)
+    }
+}
@@ -1,23 +1,54 @@
-pub use self::inner::Instant;
+#[cfg(feature = "libc")]
+pub use self::inner::read_clock;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use self::inner::sleep_until;
+use self::inner::EPOCH_IS_BOOT;
+pub use self::inner::{clock_source, monotonic_now, raw_monotonic_now, suspended_time, Instant};
+#[cfg(not(any(target_env = "newlib", target_os = "redox", target_os = "nuttx")))]
+pub use crate::sys_common::{process_cpu_now, thread_cpu_now};
+
+// The `force-monotonic` feature selects clocks that stop while the system is
+// suspended
+pub const SUSPEND_AWARE: bool = inner::SUSPEND_AWARE && !cfg!(feature = "force-monotonic");
 
 const NSEC_PER_SEC: u64 = 1_000_000_000;
 
+pub fn epoch_is_boot() -> bool {
+    EPOCH_IS_BOOT
+}
+
 #[cfg(any(
     target_os = "macos",
     target_os = "ios",
     target_os = "watchos",
-    target_os = "tvos"
+    target_os = "tvos",
+    target_os = "visionos"
 ))]
 mod inner {
     use crate::sys_common::mul_div_u64;
+    #[cfg(feature = "libc")]
+    pub use crate::sys_common::read_clock;
+    use crate::sys_common::tag::Tag;
+    use crate::ClockSource;
+    use std::fmt;
+    use std::io;
+    use std::mem;
     use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::OnceLock;
     use std::time::Duration;
 
     use super::NSEC_PER_SEC;
 
-    #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+    // Both mach clocks count from boot. Only `mach_continuous_time` includes
+    // suspended time, it is missing on systems older than macOS 10.12 only.
+    pub const EPOCH_IS_BOOT: bool = true;
+    pub const SUSPEND_AWARE: bool = true;
+
+    #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
     pub struct Instant {
         t: u64,
+        // the clock the instant was read from, in debug builds
+        clock: Tag,
     }
 
     #[repr(C)]
@@ -32,17 +63,226 @@ mod inner {
     #[allow(non_camel_case_types)]
     type kern_return_t = libc::c_int;
 
+    // This is the complete list of the system functions the Darwin backend
+    // uses. All of them are public API declared in <mach/mach_time.h>. No
+    // private API is referenced, which is required by the App Store review.
+    //
+    // `mach_continuous_time` and `mach_continuous_approximate_time` are
+    // looked up at runtime, see `clock()`.
+    extern "C" {
+        fn mach_absolute_time() -> u64;
+        fn mach_approximate_time() -> u64;
+        fn mach_timebase_info(info: mach_timebase_info_t) -> kern_return_t;
+    }
+
+    type MachTimeFn = unsafe extern "C" fn() -> u64;
+
+    /// The pair of functions reading the selected clock
+    #[derive(Copy, Clone)]
+    struct Clock {
+        precise: MachTimeFn,
+        coarse: MachTimeFn,
+        source: ClockSource,
+    }
+
+    impl Clock {
+        // The POSIX clocks reading the mach clocks
+        fn tag(&self) -> Tag {
+            if self.source == ClockSource::MachContinuous {
+                Tag::of(libc::CLOCK_MONOTONIC_RAW as i64)
+            } else {
+                Tag::of(libc::CLOCK_UPTIME_RAW as i64)
+            }
+        }
+    }
+
+    pub fn clock_source() -> ClockSource {
+        clock().source
+    }
+
+    // `mach_absolute_time` stops while the system is asleep and isn't slewed
+    pub fn monotonic_now() -> Duration {
+        let info = info();
+        let nanos = mul_div_u64(
+            unsafe { mach_absolute_time() },
+            info.numer as u64,
+            info.denom as u64,
+        );
+        Duration::from_nanos(nanos)
+    }
+
+    pub fn raw_monotonic_now() -> Duration {
+        monotonic_now()
+    }
+
+    // The continuous clock runs ahead of the absolute one by the time the
+    // system was asleep
+    pub fn suspended_time() -> Duration {
+        match continuous() {
+            Some((continuous, _)) => {
+                let info = info();
+                let ticks = unsafe { continuous() }.saturating_sub(unsafe { mach_absolute_time() });
+                Duration::from_nanos(mul_div_u64(ticks, info.numer as u64, info.denom as u64))
+            }
+            None => Duration::ZERO,
+        }
+    }
+
+    // Looks up `mach_continuous_time` and `mach_continuous_approximate_time`
+    fn continuous() -> Option<(MachTimeFn, MachTimeFn)> {
+        static CONTINUOUS: OnceLock<Option<(MachTimeFn, MachTimeFn)>> = OnceLock::new();
+        *CONTINUOUS.get_or_init(|| {
+            let (precise, coarse) = unsafe {
+                (
+                    libc::dlsym(libc::RTLD_DEFAULT, c"mach_continuous_time".as_ptr()),
+                    libc::dlsym(
+                        libc::RTLD_DEFAULT,
+                        c"mach_continuous_approximate_time".as_ptr(),
+                    ),
+                )
+            };
+            if precise.is_null() || coarse.is_null() {
+                return None;
+            }
+            // SAFETY: both functions have the `MachTimeFn` signature
+            unsafe {
+                Some((
+                    mem::transmute::<*mut libc::c_void, MachTimeFn>(precise),
+                    mem::transmute::<*mut libc::c_void, MachTimeFn>(coarse),
+                ))
+            }
+        })
+    }
+
+    // Returns the continuous clock, which includes the time the system was
+    // asleep, if the system provides it (macOS 10.12, iOS 10, tvOS 10,
+    // watchOS 3 and later). Linking it strongly would prevent the binary from
+    // loading on older systems, so it is looked up at runtime. Older systems
+    // fall back to the absolute clock, which stops while the system is asleep.
+    // The absolute clock is also selected if suspended time shouldn't count.
+    fn clock() -> Clock {
+        static CLOCK: OnceLock<Clock> = OnceLock::new();
+        *CLOCK.get_or_init(|| {
+            let absolute = Clock {
+                precise: mach_absolute_time,
+                coarse: mach_approximate_time,
+                source: ClockSource::MachAbsolute,
+            };
+            if !crate::config::prefer_suspend_aware() {
+                return absolute;
+            }
+            match continuous() {
+                Some((precise, coarse)) => Clock {
+                    precise,
+                    coarse,
+                    source: ClockSource::MachContinuous,
+                },
+                None => absolute,
+            }
+        })
+    }
+
     impl Instant {
+        pub const MIN: Instant = Instant {
+            t: 0,
+            clock: Tag::UNKNOWN,
+        };
+        pub const MAX: Instant = Instant {
+            t: u64::MAX,
+            clock: Tag::UNKNOWN,
+        };
+
         pub fn now() -> Instant {
-            extern "C" {
-                fn mach_continuous_time() -> u64;
+            let clock = clock();
+            Instant {
+                t: unsafe { (clock.precise)() },
+                clock: clock.tag(),
             }
+        }
+
+        // The mach clocks cannot fail
+        pub fn try_now() -> io::Result<Instant> {
+            Ok(Instant::now())
+        }
+
+        pub fn resolution() -> Duration {
+            let info = info();
+            // round up to whole nanoseconds, a tick is 41.67 ns on Apple silicon
+            let nanos = (info.numer as u64).div_ceil(info.denom as u64);
+            Duration::from_nanos(nanos)
+        }
+
+        pub fn now_coarse() -> Instant {
+            let clock = clock();
             Instant {
-                t: unsafe { mach_continuous_time() },
+                t: unsafe { (clock.coarse)() },
+                clock: clock.tag(),
             }
         }
 
+        pub fn as_nanos(&self) -> Option<u64> {
+            let info = info();
+            Some(mul_div_u64(self.t, info.numer as u64, info.denom as u64))
+        }
+
+        pub fn from_nanos(nanos: u64) -> Instant {
+            let info = info();
+            Instant {
+                t: mul_div_u64(nanos, info.denom as u64, info.numer as u64),
+                clock: Tag::UNKNOWN,
+            }
+        }
+
+        #[allow(dead_code)] // not used without 64-bit atomics
+        pub fn into_bits(self) -> Option<u64> {
+            Some(self.t)
+        }
+
+        #[allow(dead_code)] // not used without 64-bit atomics
+        pub fn from_bits(bits: u64) -> Instant {
+            Instant {
+                t: bits,
+                clock: Tag::UNKNOWN,
+            }
+        }
+
+        #[cfg(feature = "libc")]
+        pub fn from_timespec(t: libc::timespec) -> Option<Instant> {
+            if t.tv_nsec < 0 || t.tv_nsec >= NSEC_PER_SEC as libc::c_long {
+                return None;
+            }
+            let nanos = u64::try_from(t.tv_sec)
+                .ok()?
+                .checked_mul(NSEC_PER_SEC)?
+                .checked_add(t.tv_nsec as u64)?;
+            Some(Instant::from_nanos(nanos))
+        }
+
+        #[cfg(feature = "libc")]
+        pub fn as_timespec(&self) -> Option<libc::timespec> {
+            let nanos = self.as_nanos()?;
+            Some(libc::timespec {
+                tv_sec: (nanos / NSEC_PER_SEC).try_into().ok()?,
+                tv_nsec: (nanos % NSEC_PER_SEC) as libc::c_long,
+            })
+        }
+
+        // CLOCK_MONOTONIC is the continuous clock slewed by NTP
+        #[cfg(feature = "libc")]
+        pub fn from_clock(clock: libc::clockid_t, t: Duration) -> Option<Instant> {
+            let clock = if clock == libc::CLOCK_MONOTONIC {
+                libc::CLOCK_MONOTONIC_RAW
+            } else {
+                clock
+            };
+            Some(Instant {
+                clock: Tag::of(clock as i64),
+                ..Instant::from_nanos(u64::try_from(t.as_nanos()).ok()?)
+            })
+        }
+
         pub fn checked_sub_instant(&self, other: &Instant) -> Option<Duration> {
+            self.clock.check(other.clock);
             let diff = self.t.checked_sub(other.t)?;
             let info = info();
             let nanos = mul_div_u64(diff, info.numer as u64, info.denom as u64);
@@ -55,16 +295,24 @@ mod inner {
         pub fn checked_add_duration(&self, other: &Duration) -> Option<Instant> {
             Some(Instant {
                 t: self.t.checked_add(checked_dur2intervals(other)?)?,
+                clock: self.clock,
             })
         }
 
         pub fn checked_sub_duration(&self, other: &Duration) -> Option<Instant> {
             Some(Instant {
                 t: self.t.checked_sub(checked_dur2intervals(other)?)?,
+                clock: self.clock,
             })
         }
     }
 
+    impl fmt::Debug for Instant {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Instant").field("t", &self.t).finish()
+        }
+    }
+
     fn checked_dur2intervals(dur: &Duration) -> Option<u64> {
         let nanos = dur
             .as_secs()
@@ -91,10 +339,6 @@ mod inner {
         }
 
         // ... otherwise learn for ourselves ...
-        extern "C" {
-            fn mach_timebase_info(info: mach_timebase_info_t) -> kern_return_t;
-        }
-
         let mut info = info_from_bits(0);
         unsafe {
             mach_timebase_info(&mut info);
@@ -121,12 +365,17 @@ mod inner {
     target_os = "macos",
     target_os = "ios",
     target_os = "watchos",
-    target_os = "tvos"
+    target_os = "tvos",
+    target_os = "visionos"
 )))]
 mod inner {
     use super::NSEC_PER_SEC;
+    use crate::sys_common::tag::Tag;
+    use crate::ClockSource;
     use std::fmt;
-    use std::mem::MaybeUninit;
+    use std::io;
+    use std::mem::{self, MaybeUninit};
+    use std::sync::OnceLock;
     use std::time::Duration;
 
     #[doc(hidden)]
@@ -144,9 +393,9 @@ mod inner {
 
     impl_is_minus_one! { i8 i16 i32 i64 isize }
 
-    fn cvt<T: IsMinusOne>(t: T) -> std::io::Result<T> {
+    fn cvt<T: IsMinusOne>(t: T) -> io::Result<T> {
         if t.is_minus_one() {
-            Err(std::io::Error::last_os_error())
+            Err(io::Error::last_os_error())
         } else {
             Ok(t)
         }
@@ -208,6 +457,11 @@ mod inner {
             }
         }
 
+        // The clocks read by `now` are never negative
+        fn as_duration(&self) -> Duration {
+            Duration::new(self.tv_sec as u64, self.tv_nsec.0)
+        }
+
         pub fn checked_add_duration(&self, other: &Duration) -> Option<Timespec> {
             let mut secs = self.tv_sec.checked_add_unsigned(other.as_secs())?;
 
@@ -233,12 +487,19 @@ mod inner {
             Some(Timespec::new(secs, nsec.into()))
         }
 
-        #[allow(dead_code)]
+        #[allow(
+            dead_code,
+            clippy::wrong_self_convention,
+            clippy::useless_conversion,
+            clippy::unnecessary_fallible_conversions
+        )]
         pub fn to_timespec(&self) -> Option<libc::timespec> {
-            Some(libc::timespec {
-                tv_sec: self.tv_sec.try_into().ok()?,
-                tv_nsec: self.tv_nsec.0.try_into().ok()?,
-            })
+            // `timespec` has private padding on targets with 64-bit `time_t`
+            // and 32-bit `long`, so it can't be built with a struct literal
+            let mut t: libc::timespec = unsafe { mem::zeroed() };
+            t.tv_sec = self.tv_sec.try_into().ok()?;
+            t.tv_nsec = self.tv_nsec.0.try_into().ok()?;
+            Some(t)
         }
 
         // On QNX Neutrino, the maximum timespec for e.g. pthread_cond_timedwait
@@ -254,19 +515,24 @@ mod inner {
             }
             self.to_timespec()
         }
+    }
 
-        #[cfg(all(
-            target_os = "linux",
-            target_env = "gnu",
-            target_pointer_width = "32",
-            not(target_arch = "riscv32")
-        ))]
-        pub fn to_timespec64(&self) -> __timespec64 {
-            __timespec64::new(self.tv_sec, self.tv_nsec.0 as _)
+    impl Timespec {
+        #[cfg(feature = "libc")]
+        // `time_t` and `c_long` are not 64 bits wide on every target
+        #[allow(clippy::unnecessary_cast)]
+        fn try_from_timespec(t: libc::timespec) -> Option<Timespec> {
+            let tv_nsec = t.tv_nsec as i64;
+            if tv_nsec < 0 || tv_nsec >= NSEC_PER_SEC as i64 {
+                return None;
+            }
+            Some(Timespec::new(t.tv_sec as i64, tv_nsec))
         }
     }
 
     impl From<libc::timespec> for Timespec {
+        // `time_t` and `c_long` are not 64 bits wide on every target
+        #[allow(clippy::unnecessary_cast)]
         fn from(t: libc::timespec) -> Timespec {
             Timespec::new(t.tv_sec as i64, t.tv_nsec as i64)
         }
@@ -274,7 +540,7 @@ mod inner {
 
     #[cfg(all(
         target_os = "linux",
-        target_env = "gnu",
+        any(target_env = "gnu", target_env = "musl"),
         target_pointer_width = "32",
         not(target_arch = "riscv32")
     ))]
@@ -290,71 +556,409 @@ mod inner {
 
     #[cfg(all(
         target_os = "linux",
-        target_env = "gnu",
+        any(target_env = "gnu", target_env = "musl"),
         target_pointer_width = "32",
         not(target_arch = "riscv32")
     ))]
-    impl __timespec64 {
-        pub(in crate::sys::unix) fn new(tv_sec: i64, tv_nsec: i32) -> Self {
-            Self {
-                tv_sec,
-                tv_nsec,
-                _padding: 0,
+    impl From<__timespec64> for Timespec {
+        fn from(t: __timespec64) -> Timespec {
+            Timespec::new(t.tv_sec, t.tv_nsec.into())
+        }
+    }
+
+    cfg_if::cfg_if! {
+        if #[cfg(all(target_os = "linux", target_env = "ohos"))] {
+            // OpenHarmony runs a Linux kernel, but the seccomp policy of app
+            // sandboxes may reject clock ids, so CLOCK_BOOTTIME is probed
+            // like on Linux
+            const CLOCK_CANDIDATES: &[libc::clockid_t] =
+                &[libc::CLOCK_BOOTTIME, libc::CLOCK_MONOTONIC];
+            pub const EPOCH_IS_BOOT: bool = true;
+            pub const SUSPEND_AWARE: bool = true;
+            fn coarse_clock_id() -> libc::clockid_t {
+                clock_id()
+            }
+        } else if #[cfg(any(target_os = "linux", target_os = "android"))] {
+            // CLOCK_BOOTTIME appeared in Linux 2.6.39. Older kernels, some
+            // embedded devices and seccomp-restricted containers reject it
+            // with EINVAL, so CLOCK_MONOTONIC is used there instead of
+            // failing in `Instant::now`.
+            const CLOCK_CANDIDATES: &[libc::clockid_t] =
+                &[libc::CLOCK_BOOTTIME, libc::CLOCK_MONOTONIC];
+            // Both clocks start at boot, CLOCK_MONOTONIC just doesn't advance
+            // while the system is suspended
+            pub const EPOCH_IS_BOOT: bool = true;
+            pub const SUSPEND_AWARE: bool = true;
+            // There is no coarse variant of CLOCK_BOOTTIME. It is served by
+            // vDSO on modern Linux kernels anyway.
+            fn coarse_clock_id() -> libc::clockid_t {
+                clock_id()
+            }
+        } else if #[cfg(target_os = "l4re")] {
+            // The L4Re C library maps the POSIX clocks onto the clock of the
+            // kernel info page, which starts with the microkernel. It doesn't
+            // follow Linux in supporting CLOCK_BOOTTIME, so it is probed.
+            // L4Re has no system suspend, so no suspended time can be missed.
+            const CLOCK_CANDIDATES: &[libc::clockid_t] =
+                &[libc::CLOCK_BOOTTIME, libc::CLOCK_MONOTONIC];
+            pub const EPOCH_IS_BOOT: bool = true;
+            pub const SUSPEND_AWARE: bool = false;
+            fn coarse_clock_id() -> libc::clockid_t {
+                clock_id()
+            }
+        } else if #[cfg(target_os = "openbsd")] {
+            // Native CLOCK_BOOTTIME counts suspended time
+            const CLOCK_CANDIDATES: &[libc::clockid_t] = &[libc::CLOCK_BOOTTIME];
+            pub const EPOCH_IS_BOOT: bool = true;
+            pub const SUSPEND_AWARE: bool = true;
+            fn coarse_clock_id() -> libc::clockid_t {
+                clock_id()
+            }
+        } else if #[cfg(target_os = "freebsd")] {
+            // CLOCK_BOOTTIME is an alias of CLOCK_UPTIME, which reads the same
+            // counter as CLOCK_MONOTONIC and stops while the system is
+            // suspended. Kernels where it counts suspended time are told
+            // apart by `kern.osreldate`, see `counts_suspend`.
+            const CLOCK_CANDIDATES: &[libc::clockid_t] =
+                &[libc::CLOCK_BOOTTIME, libc::CLOCK_MONOTONIC];
+            // POSIX leaves the epoch of CLOCK_MONOTONIC unspecified
+            pub const EPOCH_IS_BOOT: bool = false;
+            pub const SUSPEND_AWARE: bool = false;
+            // Updated once per timer tick (see kern.hz)
+            fn coarse_clock_id() -> libc::clockid_t {
+                if clock_id() == libc::CLOCK_MONOTONIC {
+                    libc::CLOCK_MONOTONIC_FAST
+                } else {
+                    clock_id()
+                }
+            }
+        } else if #[cfg(target_os = "netbsd")] {
+            // NetBSD has no clock that counts suspended time. New candidates
+            // go in front of CLOCK_MONOTONIC once the kernel provides them.
+            const CLOCK_CANDIDATES: &[libc::clockid_t] = &[libc::CLOCK_MONOTONIC];
+            pub const EPOCH_IS_BOOT: bool = false;
+            pub const SUSPEND_AWARE: bool = false;
+            fn coarse_clock_id() -> libc::clockid_t {
+                clock_id()
+            }
+        } else if #[cfg(target_os = "dragonfly")] {
+            const CLOCK_CANDIDATES: &[libc::clockid_t] = &[libc::CLOCK_MONOTONIC];
+            pub const EPOCH_IS_BOOT: bool = false;
+            pub const SUSPEND_AWARE: bool = false;
+            // Updated once per timer tick (see kern.hz)
+            fn coarse_clock_id() -> libc::clockid_t {
+                libc::CLOCK_MONOTONIC_FAST
+            }
+        } else if #[cfg(target_os = "redox")] {
+            // Redox implements CLOCK_MONOTONIC in the kernel. It has no
+            // suspend support, so no suspended time can be missed.
+            const CLOCK_CANDIDATES: &[libc::clockid_t] = &[libc::CLOCK_MONOTONIC];
+            pub const EPOCH_IS_BOOT: bool = false;
+            pub const SUSPEND_AWARE: bool = false;
+            fn coarse_clock_id() -> libc::clockid_t {
+                clock_id()
+            }
+        } else if #[cfg(target_os = "emscripten")] {
+            // Emscripten doesn't implement CLOCK_BOOTTIME. CLOCK_MONOTONIC is
+            // backed by `emscripten_get_now()`, i.e. `performance.now()` in
+            // browsers and `process.hrtime()` in Node.js.
+            const CLOCK_CANDIDATES: &[libc::clockid_t] = &[libc::CLOCK_MONOTONIC];
+            pub const EPOCH_IS_BOOT: bool = false;
+            pub const SUSPEND_AWARE: bool = false;
+            fn coarse_clock_id() -> libc::clockid_t {
+                clock_id()
+            }
+        } else if #[cfg(target_os = "espidf")] {
+            // ESP-IDF implements CLOCK_MONOTONIC with `esp_timer_get_time()`,
+            // which starts at boot and is compensated for light sleep. Waking
+            // up from deep sleep resets the chip, so the clock starts over.
+            const CLOCK_CANDIDATES: &[libc::clockid_t] = &[libc::CLOCK_MONOTONIC];
+            pub const EPOCH_IS_BOOT: bool = true;
+            pub const SUSPEND_AWARE: bool = true;
+            fn coarse_clock_id() -> libc::clockid_t {
+                clock_id()
+            }
+        } else if #[cfg(target_os = "horizon")] {
+            // libctru implements CLOCK_MONOTONIC with `svcGetSystemTick`, the
+            // 268 MHz tick counter that starts when the console boots
+            const CLOCK_CANDIDATES: &[libc::clockid_t] = &[libc::CLOCK_MONOTONIC];
+            pub const EPOCH_IS_BOOT: bool = true;
+            pub const SUSPEND_AWARE: bool = false;
+            fn coarse_clock_id() -> libc::clockid_t {
+                clock_id()
+            }
+        } else if #[cfg(target_os = "cygwin")] {
+            // Cygwin serves CLOCK_BOOTTIME from the Windows interrupt time,
+            // which includes suspended time. CLOCK_MONOTONIC is backed by
+            // QueryPerformanceCounter and is kept for older Cygwin releases.
+            const CLOCK_CANDIDATES: &[libc::clockid_t] =
+                &[libc::CLOCK_BOOTTIME, libc::CLOCK_MONOTONIC];
+            // The epoch of the fallback clock is unspecified
+            pub const EPOCH_IS_BOOT: bool = false;
+            pub const SUSPEND_AWARE: bool = true;
+            fn coarse_clock_id() -> libc::clockid_t {
+                clock_id()
+            }
+        } else if #[cfg(target_os = "aix")] {
+            // AIX has no clock that counts suspended time
+            const CLOCK_CANDIDATES: &[libc::clockid_t] = &[libc::CLOCK_MONOTONIC];
+            pub const EPOCH_IS_BOOT: bool = false;
+            pub const SUSPEND_AWARE: bool = false;
+            fn coarse_clock_id() -> libc::clockid_t {
+                clock_id()
+            }
+        } else {
+            const CLOCK_CANDIDATES: &[libc::clockid_t] = &[libc::CLOCK_MONOTONIC];
+            pub const EPOCH_IS_BOOT: bool = false;
+            pub const SUSPEND_AWARE: bool = false;
+            fn coarse_clock_id() -> libc::clockid_t {
+                clock_id()
             }
         }
     }
 
-    #[cfg(all(
-        target_os = "linux",
-        target_env = "gnu",
-        target_pointer_width = "32",
-        not(target_arch = "riscv32")
-    ))]
-    impl From<__timespec64> for Timespec {
-        fn from(t: __timespec64) -> Timespec {
-            Timespec::new(t.tv_sec, t.tv_nsec.into())
+    // Returns the clock used by `Instant::now`, which is probed once
+    fn clock_id() -> libc::clockid_t {
+        static CLOCK_ID: OnceLock<libc::clockid_t> = OnceLock::new();
+        *CLOCK_ID.get_or_init(|| {
+            if !crate::config::prefer_suspend_aware() {
+                libc::CLOCK_MONOTONIC
+            } else {
+                probe_clock(CLOCK_CANDIDATES)
+            }
+        })
+    }
+
+    // Every candidate list ends with CLOCK_MONOTONIC, or only has clocks
+    // counting suspended time
+    pub fn clock_source() -> ClockSource {
+        if cfg!(target_os = "espidf") {
+            ClockSource::EspTimer
+        } else if clock_id() == libc::CLOCK_MONOTONIC {
+            ClockSource::Monotonic
+        } else {
+            ClockSource::BootTime
+        }
+    }
+
+    pub fn monotonic_now() -> Duration {
+        Timespec::now(libc::CLOCK_MONOTONIC).unwrap().as_duration()
+    }
+
+    #[cfg(feature = "libc")]
+    pub fn read_clock(clock: libc::clockid_t) -> io::Result<Duration> {
+        let t = Timespec::now(clock)?;
+        crate::sys_common::duration_from_parts(t.tv_sec, t.tv_nsec.0.into())
+    }
+
+    // Only Linux has a monotonic clock that isn't slewed by NTP
+    pub fn raw_monotonic_now() -> Duration {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let clock = libc::CLOCK_MONOTONIC_RAW;
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        let clock = libc::CLOCK_MONOTONIC;
+        Timespec::now(clock).unwrap().as_duration()
+    }
+
+    // CLOCK_BOOTTIME runs ahead of CLOCK_MONOTONIC by the suspended time.
+    // Elsewhere either no clock counts suspended time, or none stops.
+    pub fn suspended_time() -> Duration {
+        #[cfg(target_os = "freebsd")]
+        if clock_id() == libc::CLOCK_MONOTONIC {
+            return Duration::ZERO;
+        }
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "cygwin",
+            target_os = "freebsd"
+        ))]
+        if let (Ok(boot), Ok(monotonic)) = (
+            Timespec::now(libc::CLOCK_BOOTTIME),
+            Timespec::now(libc::CLOCK_MONOTONIC),
+        ) {
+            return boot.as_duration().saturating_sub(monotonic.as_duration());
         }
+        Duration::ZERO
+    }
+
+    // Sleeps on the clock of `Instant` until the absolute deadline, so the
+    // time spent suspended counts. Returns `false` if the kernel rejects the
+    // clock or the deadline.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn sleep_until(deadline: &Instant) -> bool {
+        let Some(t) = deadline.t.to_timespec() else {
+            return false;
+        };
+        loop {
+            let r = unsafe {
+                libc::clock_nanosleep(clock_id(), libc::TIMER_ABSTIME, &t, std::ptr::null_mut())
+            };
+            // The deadline is absolute, so the sleep is simply repeated
+            if r != libc::EINTR {
+                return r == 0;
+            }
+        }
+    }
+
+    // Returns the first candidate the kernel supports. The last candidate is
+    // returned if none is supported, so the error surfaces on use.
+    fn probe_clock(candidates: &[libc::clockid_t]) -> libc::clockid_t {
+        match candidates {
+            [clock] => *clock,
+            _ => candidates
+                .iter()
+                .copied()
+                .find(|&clock| Timespec::now(clock).is_ok() && counts_suspend(clock))
+                .unwrap_or(candidates[candidates.len() - 1]),
+        }
+    }
+
+    // The first `kern.osreldate` whose CLOCK_BOOTTIME counts suspended time.
+    // No release does so far.
+    #[cfg(target_os = "freebsd")]
+    const BOOTTIME_COUNTS_SUSPEND_SINCE: Option<libc::c_int> = None;
+
+    // The kernel version decides rather than a comparison of the clocks, so
+    // every process on a boot picks the same clock
+    #[cfg(target_os = "freebsd")]
+    fn counts_suspend(clock: libc::clockid_t) -> bool {
+        if clock != libc::CLOCK_BOOTTIME {
+            return true;
+        }
+        match (BOOTTIME_COUNTS_SUSPEND_SINCE, osreldate()) {
+            (Some(since), Some(release)) => release >= since,
+            _ => false,
+        }
+    }
+
+    #[cfg(target_os = "freebsd")]
+    fn osreldate() -> Option<libc::c_int> {
+        let mut value: libc::c_int = 0;
+        let mut len = std::mem::size_of::<libc::c_int>();
+        let r = unsafe {
+            libc::sysctlbyname(
+                b"kern.osreldate\0".as_ptr().cast(),
+                (&mut value as *mut libc::c_int).cast(),
+                &mut len,
+                std::ptr::null(),
+                0,
+            )
+        };
+        (r == 0).then_some(value)
+    }
+
+    // The candidates elsewhere are distinct clocks
+    #[cfg(not(target_os = "freebsd"))]
+    fn counts_suspend(_clock: libc::clockid_t) -> bool {
+        true
     }
 
     #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
     pub struct Instant {
         t: Timespec,
+        // the clock the instant was read from, in debug builds
+        clock: Tag,
     }
 
     impl Instant {
+        pub const MIN: Instant = Instant {
+            t: Timespec::new(i64::MIN, 0),
+            clock: Tag::UNKNOWN,
+        };
+        pub const MAX: Instant = Instant {
+            t: Timespec::new(i64::MAX, NSEC_PER_SEC as i64 - 1),
+            clock: Tag::UNKNOWN,
+        };
+
         pub fn now() -> Instant {
-            cfg_if::cfg_if! {
-                if #[cfg(any(
-                    target_os = "linux",
-                    target_os = "l4re",
-                    target_os = "android",
-                    target_os = "openbsd",
-                ))] {
-                    #[allow(non_upper_case_globals)]
-                    const clock_id: libc::clockid_t = libc::CLOCK_BOOTTIME;
-                } else {
-                    #[allow(non_upper_case_globals)]
-                    const clock_id: libc::clockid_t = libc::CLOCK_MONOTONIC;
-                }
+            Instant::try_now().unwrap()
+        }
+
+        pub fn try_now() -> io::Result<Instant> {
+            let clock = clock_id();
+            Ok(Instant {
+                t: Timespec::now(clock)?,
+                clock: Tag::of(clock as i64),
+            })
+        }
+
+        pub fn resolution() -> Duration {
+            let mut t = MaybeUninit::uninit();
+            cvt(unsafe { libc::clock_getres(clock_id(), t.as_mut_ptr()) }).unwrap();
+            let t = Timespec::from(unsafe { t.assume_init() });
+            Duration::new(t.tv_sec as u64, t.tv_nsec.0)
+        }
+
+        pub fn now_coarse() -> Instant {
+            Instant {
+                t: Timespec::now(coarse_clock_id()).unwrap(),
+                // The coarse clock follows the timeline of the precise one
+                clock: Tag::of(clock_id() as i64),
             }
+        }
+
+        pub fn as_nanos(&self) -> Option<u64> {
+            u64::try_from(self.t.tv_sec)
+                .ok()?
+                .checked_mul(NSEC_PER_SEC)?
+                .checked_add(self.t.tv_nsec.0 as u64)
+        }
+
+        pub fn from_nanos(nanos: u64) -> Instant {
             Instant {
-                t: Timespec::now(clock_id),
+                t: Timespec::new((nanos / NSEC_PER_SEC) as i64, (nanos % NSEC_PER_SEC) as i64),
+                clock: Tag::UNKNOWN,
             }
         }
 
+        #[allow(dead_code)] // not used without 64-bit atomics
+        pub fn into_bits(self) -> Option<u64> {
+            self.as_nanos()
+        }
+
+        #[allow(dead_code)] // not used without 64-bit atomics
+        pub fn from_bits(bits: u64) -> Instant {
+            Instant::from_nanos(bits)
+        }
+
+        #[cfg(feature = "libc")]
+        pub fn from_timespec(t: libc::timespec) -> Option<Instant> {
+            Some(Instant {
+                t: Timespec::try_from_timespec(t)?,
+                clock: Tag::UNKNOWN,
+            })
+        }
+
+        #[cfg(feature = "libc")]
+        pub fn as_timespec(&self) -> Option<libc::timespec> {
+            self.t.to_timespec()
+        }
+
+        #[cfg(feature = "libc")]
+        pub fn from_clock(clock: libc::clockid_t, t: Duration) -> Option<Instant> {
+            Some(Instant {
+                t: Timespec::new(i64::try_from(t.as_secs()).ok()?, t.subsec_nanos().into()),
+                clock: Tag::of(clock as i64),
+            })
+        }
+
         pub fn checked_sub_instant(&self, other: &Instant) -> Option<Duration> {
+            self.clock.check(other.clock);
             self.t.sub_timespec(&other.t).ok()
         }
 
         pub fn checked_add_duration(&self, other: &Duration) -> Option<Instant> {
             Some(Instant {
                 t: self.t.checked_add_duration(other)?,
+                clock: self.clock,
             })
         }
 
         pub fn checked_sub_duration(&self, other: &Duration) -> Option<Instant> {
             Some(Instant {
                 t: self.t.checked_sub_duration(other)?,
+                clock: self.clock,
             })
         }
     }
@@ -369,31 +973,33 @@ mod inner {
     }
 
     impl Timespec {
-        pub fn now(clock: libc::clockid_t) -> Timespec {
+        pub fn now(clock: libc::clockid_t) -> io::Result<Timespec> {
             // Try to use 64-bit time in preparation for Y2038.
             #[cfg(all(
                 target_os = "linux",
-                target_env = "gnu",
+                any(target_env = "gnu", target_env = "musl"),
                 target_pointer_width = "32",
                 not(target_arch = "riscv32")
             ))]
             {
                 use crate::sys::weak::weak;
 
-                // __clock_gettime64 was added to 32-bit arches in glibc 2.34,
-                // and it handles both vDSO calls and ENOSYS fallbacks itself.
-                weak!(fn __clock_gettime64(libc::clockid_t, *mut super::__timespec64) -> libc::c_int);
+                // __clock_gettime64 was added to 32-bit arches in glibc 2.34
+                // and musl 1.2, and it handles both vDSO calls and ENOSYS
+                // fallbacks itself. The legacy call truncates the seconds to
+                // 32 bits.
+                weak!(fn __clock_gettime64(libc::clockid_t, *mut __timespec64) -> libc::c_int);
 
                 if let Some(clock_gettime64) = __clock_gettime64.get() {
                     let mut t = MaybeUninit::uninit();
-                    cvt(unsafe { clock_gettime64(clock, t.as_mut_ptr()) }).unwrap();
-                    return Timespec::from(unsafe { t.assume_init() });
+                    cvt(unsafe { clock_gettime64(clock, t.as_mut_ptr()) })?;
+                    return Ok(Timespec::from(unsafe { t.assume_init() }));
                 }
             }
 
             let mut t = MaybeUninit::uninit();
-            cvt(unsafe { libc::clock_gettime(clock, t.as_mut_ptr()) }).unwrap();
-            Timespec::from(unsafe { t.assume_init() })
+            cvt(unsafe { libc::clock_gettime(clock, t.as_mut_ptr()) })?;
+            Ok(Timespec::from(unsafe { t.assume_init() }))
         }
     }
 }
@@ -0,0 +1,69 @@
+pub use crate::sys_common::nanos::Instant;
+use crate::ClockSource;
+use std::io;
+use std::time::Duration;
+
+// The monotonic clock of WASI has an arbitrary epoch and the host decides
+// whether it counts suspended time.
+pub fn epoch_is_boot() -> bool {
+    false
+}
+
+pub const SUSPEND_AWARE: bool = false;
+
+pub fn clock_source() -> ClockSource {
+    ClockSource::WasiMonotonic
+}
+
+// WASI has a single monotonic clock
+pub fn monotonic_now() -> Duration {
+    Instant::now().duration_since_epoch()
+}
+
+pub fn raw_monotonic_now() -> Duration {
+    monotonic_now()
+}
+
+pub fn suspended_time() -> Duration {
+    Duration::ZERO
+}
+
+const CLOCKID_MONOTONIC: u32 = 1;
+
+#[link(wasm_import_module = "wasi_snapshot_preview1")]
+extern "C" {
+    fn clock_time_get(id: u32, precision: u64, time: *mut u64) -> u16;
+    fn clock_res_get(id: u32, resolution: *mut u64) -> u16;
+}
+
+fn cvt(errno: u16) -> io::Result<()> {
+    match errno {
+        0 => Ok(()),
+        errno => Err(io::Error::from_raw_os_error(errno.into())),
+    }
+}
+
+impl Instant {
+    pub fn now() -> Instant {
+        Instant::try_now().unwrap()
+    }
+
+    pub fn try_now() -> io::Result<Instant> {
+        let mut t = 0;
+        cvt(unsafe { clock_time_get(CLOCKID_MONOTONIC, 1, &mut t) })?;
+        Ok(Instant::from_nanos(t))
+    }
+
+    pub fn now_coarse() -> Instant {
+        let mut t = 0;
+        // Let the host return a cached value within a millisecond
+        cvt(unsafe { clock_time_get(CLOCKID_MONOTONIC, 1_000_000, &mut t) }).unwrap();
+        Instant::from_nanos(t)
+    }
+
+    pub fn resolution() -> Duration {
+        let mut res = 0;
+        cvt(unsafe { clock_res_get(CLOCKID_MONOTONIC, &mut res) }).unwrap();
+        Duration::from_nanos(res)
+    }
+}
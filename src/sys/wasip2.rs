@@ -0,0 +1,57 @@
+pub use crate::sys_common::nanos::Instant;
+use crate::ClockSource;
+use std::io;
+use std::time::Duration;
+
+// The monotonic clock of WASI has an arbitrary epoch and the host decides
+// whether it counts suspended time.
+pub fn epoch_is_boot() -> bool {
+    false
+}
+
+pub const SUSPEND_AWARE: bool = false;
+
+pub fn clock_source() -> ClockSource {
+    ClockSource::WasiMonotonic
+}
+
+// WASI has a single monotonic clock
+pub fn monotonic_now() -> Duration {
+    Instant::now().duration_since_epoch()
+}
+
+pub fn raw_monotonic_now() -> Duration {
+    monotonic_now()
+}
+
+pub fn suspended_time() -> Duration {
+    Duration::ZERO
+}
+
+// `instant` and `duration` are nanosecond counts in the canonical ABI
+#[link(wasm_import_module = "wasi:clocks/monotonic-clock@0.2.0")]
+extern "C" {
+    #[link_name = "now"]
+    fn monotonic_clock_now() -> u64;
+    #[link_name = "resolution"]
+    fn monotonic_clock_resolution() -> u64;
+}
+
+impl Instant {
+    pub fn now() -> Instant {
+        Instant::from_nanos(unsafe { monotonic_clock_now() })
+    }
+
+    // `wasi:clocks/monotonic-clock.now` cannot fail
+    pub fn try_now() -> io::Result<Instant> {
+        Ok(Instant::now())
+    }
+
+    pub fn now_coarse() -> Instant {
+        Instant::now()
+    }
+
+    pub fn resolution() -> Duration {
+        Duration::from_nanos(unsafe { monotonic_clock_resolution() })
+    }
+}
@@ -0,0 +1,56 @@
+//! Runtime lookup of C functions that are missing from older system libraries.
+//!
+//! Linking such a function directly would prevent the binary from loading on
+//! systems that lack it. `weak!` declares a static that resolves the symbol
+//! with `dlsym` on first use instead.
+use std::ffi::CStr;
+use std::marker::PhantomData;
+use std::mem;
+use std::sync::OnceLock;
+
+macro_rules! weak {
+    (fn $name:ident($($t:ty),*) -> $ret:ty) => {
+        #[allow(non_upper_case_globals)]
+        static $name: $crate::sys::weak::Weak<unsafe extern "C" fn($($t),*) -> $ret> =
+            // SAFETY: the signature matches the C declaration of the function
+            unsafe { $crate::sys::weak::Weak::new(concat!(stringify!($name), '\0')) };
+    };
+}
+pub(crate) use weak;
+
+pub(crate) struct Weak<F> {
+    name: &'static CStr,
+    addr: OnceLock<usize>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Copy> Weak<F> {
+    /// # Safety
+    ///
+    /// `F` must be a function pointer type matching the C declaration of
+    /// `name`, which must be nul-terminated.
+    pub(crate) const unsafe fn new(name: &'static str) -> Self {
+        let name = match CStr::from_bytes_with_nul(name.as_bytes()) {
+            Ok(name) => name,
+            Err(_) => panic!("symbol name must be nul-terminated"),
+        };
+        Weak {
+            name,
+            addr: OnceLock::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the function if the system provides it.
+    pub(crate) fn get(&self) -> Option<F> {
+        assert_eq!(mem::size_of::<F>(), mem::size_of::<usize>());
+        let lookup = || unsafe { libc::dlsym(libc::RTLD_DEFAULT, self.name.as_ptr()) } as usize;
+        let addr = *self.addr.get_or_init(lookup);
+        if addr == 0 {
+            None
+        } else {
+            // SAFETY: `F` is a function pointer type matching the symbol
+            Some(unsafe { mem::transmute_copy::<usize, F>(&addr) })
+        }
+    }
+}
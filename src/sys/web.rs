@@ -0,0 +1,67 @@
+pub use crate::sys_common::nanos::Instant;
+use crate::ClockSource;
+use std::io;
+use std::time::Duration;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+// `performance.now()` counts from the time origin of the page or worker.
+// Browsers differ on whether it advances while the device is suspended.
+pub fn epoch_is_boot() -> bool {
+    false
+}
+
+pub const SUSPEND_AWARE: bool = false;
+
+pub fn clock_source() -> ClockSource {
+    ClockSource::PerformanceNow
+}
+
+// Browsers have a single monotonic clock
+pub fn monotonic_now() -> Duration {
+    Instant::now().duration_since_epoch()
+}
+
+pub fn raw_monotonic_now() -> Duration {
+    monotonic_now()
+}
+
+pub fn suspended_time() -> Duration {
+    Duration::ZERO
+}
+
+const NSEC_PER_MSEC: f64 = 1_000_000.0;
+
+#[wasm_bindgen]
+extern "C" {
+    type Performance;
+
+    // Available both in windows and workers
+    #[wasm_bindgen(thread_local_v2, js_namespace = globalThis, js_name = performance)]
+    static PERFORMANCE: Performance;
+
+    #[wasm_bindgen(method)]
+    fn now(this: &Performance) -> f64;
+}
+
+impl Instant {
+    pub fn now() -> Instant {
+        let millis = PERFORMANCE.with(Performance::now);
+        Instant::from_nanos((millis * NSEC_PER_MSEC) as u64)
+    }
+
+    // `performance.now()` cannot fail
+    pub fn try_now() -> io::Result<Instant> {
+        Ok(Instant::now())
+    }
+
+    pub fn now_coarse() -> Instant {
+        Instant::now()
+    }
+
+    // Browsers clamp the precision of `performance.now()` to mitigate timing
+    // attacks: to 100 µs, 5 µs with cross-origin isolation, or even 1 ms. The
+    // actual value can't be queried, so the finest one is reported.
+    pub fn resolution() -> Duration {
+        Duration::from_micros(5)
+    }
+}
@@ -0,0 +1,361 @@
+use crate::sys_common::mul_div_u64;
+pub use crate::sys_common::nanos::Instant;
+use crate::ClockSource;
+use std::ffi::{c_char, c_void, CStr};
+use std::io;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+const NSEC_PER_SEC: u64 = 1_000_000_000;
+const NSEC_PER_MSEC: u64 = 1_000_000;
+// Interrupt time is measured in 100 ns units
+const NSEC_PER_INTERRUPT_TICK: u64 = 100;
+
+#[allow(clippy::upper_case_acronyms)]
+type BOOL = i32;
+#[allow(clippy::upper_case_acronyms)]
+type HMODULE = *mut c_void;
+#[allow(clippy::upper_case_acronyms)]
+type HANDLE = *mut c_void;
+#[allow(clippy::upper_case_acronyms)]
+type FARPROC = Option<RawProc>;
+type RawProc = unsafe extern "system" fn() -> isize;
+type QueryInterruptTimeFn = unsafe extern "system" fn(*mut u64);
+type QueryUnbiasedInterruptTimeFn = unsafe extern "system" fn(*mut u64) -> BOOL;
+type GetTickCount64Fn = unsafe extern "system" fn() -> u64;
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn QueryPerformanceCounter(lpPerformanceCount: *mut i64) -> BOOL;
+    fn QueryPerformanceFrequency(lpFrequency: *mut i64) -> BOOL;
+    fn GetModuleHandleA(lpModuleName: *const c_char) -> HMODULE;
+    fn GetProcAddress(hModule: HMODULE, lpProcName: *const c_char) -> FARPROC;
+    fn GetCurrentProcess() -> HANDLE;
+    fn GetCurrentThread() -> HANDLE;
+    fn GetProcessTimes(
+        hProcess: HANDLE,
+        lpCreationTime: *mut FILETIME,
+        lpExitTime: *mut FILETIME,
+        lpKernelTime: *mut FILETIME,
+        lpUserTime: *mut FILETIME,
+    ) -> BOOL;
+    fn GetThreadTimes(
+        hThread: HANDLE,
+        lpCreationTime: *mut FILETIME,
+        lpExitTime: *mut FILETIME,
+        lpKernelTime: *mut FILETIME,
+        lpUserTime: *mut FILETIME,
+    ) -> BOOL;
+}
+
+#[link(name = "powrprof")]
+extern "system" {
+    fn CallNtPowerInformation(
+        InformationLevel: i32,
+        InputBuffer: *const c_void,
+        InputBufferLength: u32,
+        OutputBuffer: *mut c_void,
+        OutputBufferLength: u32,
+    ) -> i32;
+}
+
+// POWER_INFORMATION_LEVEL
+const SYSTEM_POWER_CAPABILITIES: i32 = 4;
+
+// SYSTEM_POWER_CAPABILITIES, of which only `AoAc` is read. It is preceded by
+// 20 one-byte fields.
+#[repr(C)]
+struct SystemPowerCapabilities {
+    before_ao_ac: [u8; 20],
+    ao_ac: u8,
+    after_ao_ac: [u8; 55],
+}
+
+#[allow(clippy::upper_case_acronyms, non_snake_case)]
+#[repr(C)]
+#[derive(Copy, Clone, Default)]
+struct FILETIME {
+    dwLowDateTime: u32,
+    dwHighDateTime: u32,
+}
+
+impl FILETIME {
+    // FILETIME durations are measured in 100 ns units
+    fn as_nanos(self) -> u64 {
+        (u64::from(self.dwHighDateTime) << 32 | u64::from(self.dwLowDateTime)) * 100
+    }
+}
+
+/// The time source selected at runtime
+#[derive(Copy, Clone)]
+enum Source {
+    /// `QueryInterruptTimePrecise`, counts suspended time (Windows 10+)
+    InterruptTime {
+        precise: QueryInterruptTimeFn,
+        coarse: QueryInterruptTimeFn,
+    },
+    /// `QueryPerformanceCounter` corrected by the suspended time, which is the
+    /// difference between `GetTickCount64` and `QueryUnbiasedInterruptTime`
+    /// (Windows 7 and 8)
+    CorrectedQpc {
+        frequency: u64,
+        unbiased_interrupt_time: QueryUnbiasedInterruptTimeFn,
+        tick_count: GetTickCount64Fn,
+    },
+    /// `QueryPerformanceCounter` with the counter frequency
+    Qpc { frequency: u64 },
+    /// `QueryPerformanceCounter` on Windows 8 with Modern Standby, where it
+    /// keeps counting in standby
+    StandbyQpc { frequency: u64 },
+}
+
+fn source() -> Source {
+    static SOURCE: OnceLock<Source> = OnceLock::new();
+    *SOURCE.get_or_init(|| {
+        // SAFETY: the looked up functions have the declared signatures
+        unsafe {
+            if let (Some(precise), Some(coarse)) = (
+                lookup(c"kernelbase.dll", c"QueryInterruptTimePrecise"),
+                lookup(c"kernelbase.dll", c"QueryInterruptTime"),
+            ) {
+                return Source::InterruptTime {
+                    precise: mem::transmute::<RawProc, QueryInterruptTimeFn>(precise),
+                    coarse: mem::transmute::<RawProc, QueryInterruptTimeFn>(coarse),
+                };
+            }
+            let frequency = qpc_frequency();
+            // Modern Standby is a low-power idle state, not a sleep state.
+            // QPC keeps counting, so correcting it would count standby twice.
+            if modern_standby() == Some(true) {
+                return Source::StandbyQpc { frequency };
+            }
+            if let (Some(unbiased_interrupt_time), Some(tick_count)) = (
+                lookup(c"kernel32.dll", c"QueryUnbiasedInterruptTime"),
+                lookup(c"kernel32.dll", c"GetTickCount64"),
+            ) {
+                return Source::CorrectedQpc {
+                    frequency,
+                    unbiased_interrupt_time: mem::transmute::<
+                        RawProc,
+                        QueryUnbiasedInterruptTimeFn,
+                    >(unbiased_interrupt_time),
+                    tick_count: mem::transmute::<RawProc, GetTickCount64Fn>(tick_count),
+                };
+            }
+            Source::Qpc { frequency }
+        }
+    })
+}
+
+// Returns whether the system uses Modern Standby (S0 low-power idle, also
+// known as Connected Standby) rather than classic S3 sleep
+pub fn modern_standby() -> Option<bool> {
+    static MODERN: OnceLock<Option<bool>> = OnceLock::new();
+    *MODERN.get_or_init(|| {
+        let mut caps = SystemPowerCapabilities {
+            before_ao_ac: [0; 20],
+            ao_ac: 0,
+            after_ao_ac: [0; 55],
+        };
+        let status = unsafe {
+            CallNtPowerInformation(
+                SYSTEM_POWER_CAPABILITIES,
+                ptr::null(),
+                0,
+                (&mut caps as *mut SystemPowerCapabilities).cast(),
+                mem::size_of::<SystemPowerCapabilities>() as u32,
+            )
+        };
+        // STATUS_SUCCESS
+        (status == 0).then_some(caps.ao_ac != 0)
+    })
+}
+
+fn lookup(module: &CStr, name: &CStr) -> FARPROC {
+    // kernel32.dll and kernelbase.dll (Windows 7+) are loaded into every process
+    let module = unsafe { GetModuleHandleA(module.as_ptr()) };
+    if module.is_null() {
+        return None;
+    }
+    unsafe { GetProcAddress(module, name.as_ptr()) }
+}
+
+fn qpc_frequency() -> u64 {
+    let mut frequency = 0;
+    // Can't fail on Windows XP and later
+    unsafe { QueryPerformanceFrequency(&mut frequency) };
+    frequency as u64
+}
+
+fn qpc_nanos() -> u64 {
+    static FREQUENCY: OnceLock<u64> = OnceLock::new();
+    mul_div_u64(qpc(), NSEC_PER_SEC, *FREQUENCY.get_or_init(qpc_frequency))
+}
+
+fn qpc() -> u64 {
+    let mut counter = 0;
+    // Can't fail on Windows XP and later
+    unsafe { QueryPerformanceCounter(&mut counter) };
+    counter as u64
+}
+
+// Returns the total time the system was suspended since boot.
+//
+// `GetTickCount64` counts suspended time while the unbiased interrupt time
+// doesn't. Both are updated once per timer interrupt but not atomically, so
+// the largest difference seen so far is used to keep the clock monotonic.
+fn suspend_bias(
+    unbiased_interrupt_time: QueryUnbiasedInterruptTimeFn,
+    tick_count: GetTickCount64Fn,
+) -> u64 {
+    static SUSPEND_BIAS: AtomicU64 = AtomicU64::new(0);
+
+    let mut unbiased = 0;
+    unsafe { unbiased_interrupt_time(&mut unbiased) };
+    let ticks = unsafe { tick_count() };
+    let bias = (ticks * NSEC_PER_MSEC).saturating_sub(unbiased * NSEC_PER_INTERRUPT_TICK);
+    let prev = SUSPEND_BIAS.fetch_max(bias, Ordering::Relaxed);
+    prev.max(bias)
+}
+
+fn interrupt_time(f: QueryInterruptTimeFn) -> u64 {
+    let mut t = 0;
+    unsafe { f(&mut t) };
+    t * NSEC_PER_INTERRUPT_TICK
+}
+
+// Only older Windows versions without `QueryUnbiasedInterruptTime` lack
+// suspended time
+pub const SUSPEND_AWARE: bool = true;
+
+pub fn clock_source() -> ClockSource {
+    match source() {
+        Source::InterruptTime { .. } => ClockSource::InterruptTime,
+        Source::CorrectedQpc { .. } => ClockSource::CorrectedQpc,
+        Source::Qpc { .. } => ClockSource::Qpc,
+        Source::StandbyQpc { .. } => ClockSource::StandbyQpc,
+    }
+}
+
+// `QueryPerformanceCounter` isn't slewed. Whether it advances while the
+// system is suspended depends on the Windows version and hardware.
+pub fn monotonic_now() -> Duration {
+    Duration::from_nanos(qpc_nanos())
+}
+
+pub fn raw_monotonic_now() -> Duration {
+    monotonic_now()
+}
+
+// Returns the time the system was suspended since boot. The unbiased
+// interrupt time is updated on the same timer interrupt as the interrupt
+// time, but stops while the system is suspended.
+pub fn suspended_time() -> Duration {
+    let nanos = match source() {
+        Source::InterruptTime { coarse, .. } => {
+            static UNBIASED: OnceLock<FARPROC> = OnceLock::new();
+            let Some(f) =
+                *UNBIASED.get_or_init(|| lookup(c"kernel32.dll", c"QueryUnbiasedInterruptTime"))
+            else {
+                return Duration::ZERO;
+            };
+            // SAFETY: `QueryUnbiasedInterruptTime` has the declared signature
+            let unbiased_interrupt_time =
+                unsafe { mem::transmute::<RawProc, QueryUnbiasedInterruptTimeFn>(f) };
+            let mut unbiased = 0;
+            unsafe { unbiased_interrupt_time(&mut unbiased) };
+            interrupt_time(coarse).saturating_sub(unbiased * NSEC_PER_INTERRUPT_TICK)
+        }
+        Source::CorrectedQpc {
+            unbiased_interrupt_time,
+            tick_count,
+            ..
+        } => suspend_bias(unbiased_interrupt_time, tick_count),
+        Source::Qpc { .. } | Source::StandbyQpc { .. } => 0,
+    };
+    Duration::from_nanos(nanos)
+}
+
+// The CPU time is the sum of the time spent in kernel and user mode
+pub fn process_cpu_now() -> Duration {
+    let [mut creation, mut exit, mut kernel, mut user] = [FILETIME::default(); 4];
+    // Can't fail for the pseudo handle of the current process
+    unsafe {
+        GetProcessTimes(
+            GetCurrentProcess(),
+            &mut creation,
+            &mut exit,
+            &mut kernel,
+            &mut user,
+        )
+    };
+    Duration::from_nanos(kernel.as_nanos() + user.as_nanos())
+}
+
+pub fn thread_cpu_now() -> Duration {
+    let [mut creation, mut exit, mut kernel, mut user] = [FILETIME::default(); 4];
+    // Can't fail for the pseudo handle of the current thread
+    unsafe {
+        GetThreadTimes(
+            GetCurrentThread(),
+            &mut creation,
+            &mut exit,
+            &mut kernel,
+            &mut user,
+        )
+    };
+    Duration::from_nanos(kernel.as_nanos() + user.as_nanos())
+}
+
+pub fn epoch_is_boot() -> bool {
+    // Interrupt time starts at zero on boot
+    matches!(source(), Source::InterruptTime { .. })
+}
+
+impl Instant {
+    pub fn now() -> Instant {
+        let t = match source() {
+            Source::InterruptTime { precise, .. } => interrupt_time(precise),
+            Source::CorrectedQpc {
+                frequency,
+                unbiased_interrupt_time,
+                tick_count,
+            } => {
+                mul_div_u64(qpc(), NSEC_PER_SEC, frequency)
+                    + suspend_bias(unbiased_interrupt_time, tick_count)
+            }
+            Source::Qpc { frequency } | Source::StandbyQpc { frequency } => {
+                mul_div_u64(qpc(), NSEC_PER_SEC, frequency)
+            }
+        };
+        Instant::from_nanos(t)
+    }
+
+    // The time sources cannot fail
+    pub fn try_now() -> io::Result<Instant> {
+        Ok(Instant::now())
+    }
+
+    pub fn now_coarse() -> Instant {
+        match source() {
+            Source::InterruptTime { coarse, .. } => Instant::from_nanos(interrupt_time(coarse)),
+            Source::CorrectedQpc { .. } | Source::Qpc { .. } | Source::StandbyQpc { .. } => {
+                Instant::now()
+            }
+        }
+    }
+
+    pub fn resolution() -> Duration {
+        match source() {
+            Source::InterruptTime { .. } => Duration::from_nanos(NSEC_PER_INTERRUPT_TICK),
+            Source::CorrectedQpc { frequency, .. }
+            | Source::Qpc { frequency }
+            | Source::StandbyQpc { frequency } => {
+                Duration::from_nanos(NSEC_PER_SEC.div_ceil(frequency))
+            }
+        }
+    }
+}
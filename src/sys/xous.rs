@@ -0,0 +1,61 @@
+pub use crate::sys_common::nanos::Instant;
+use crate::ClockSource;
+use std::io;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+// The ticktimer service counts milliseconds since boot and keeps counting
+// while the device sleeps. The clock counts from the first reading.
+pub fn epoch_is_boot() -> bool {
+    false
+}
+
+pub const SUSPEND_AWARE: bool = true;
+
+pub fn clock_source() -> ClockSource {
+    ClockSource::XousTicktimer
+}
+
+// The ticktimer service is the only time source
+pub fn monotonic_now() -> Duration {
+    Instant::now().duration_since_epoch()
+}
+
+pub fn raw_monotonic_now() -> Duration {
+    monotonic_now()
+}
+
+pub fn suspended_time() -> Duration {
+    Duration::ZERO
+}
+
+// Returns the time elapsed since the first call.
+//
+// `std` asks the ticktimer service for the elapsed milliseconds. Its
+// `Instant` is opaque, so the nanoseconds are measured from an anchor taken
+// on the first call.
+fn elapsed() -> Duration {
+    static ANCHOR: OnceLock<std::time::Instant> = OnceLock::new();
+    ANCHOR.get_or_init(std::time::Instant::now).elapsed()
+}
+
+impl Instant {
+    pub fn now() -> Instant {
+        Instant::from_nanos(elapsed().as_nanos() as u64)
+    }
+
+    // Messages to the ticktimer service cannot fail
+    pub fn try_now() -> io::Result<Instant> {
+        Ok(Instant::now())
+    }
+
+    // Every reading is a message to the ticktimer service already
+    pub fn now_coarse() -> Instant {
+        Instant::now()
+    }
+
+    // The ticktimer service reports milliseconds
+    pub fn resolution() -> Duration {
+        Duration::from_millis(1)
+    }
+}
@@ -0,0 +1,92 @@
+// Computes (value*numer)/denom without overflow, as long as both
+// (numer*denom) and the overall result fit into i64 (which is the case
+// for our time conversions).
+#[allow(dead_code)] // not used on all platforms
+pub(crate) fn mul_div_u64(value: u64, numer: u64, denom: u64) -> u64 {
+    let q = value / denom;
+    let r = value % denom;
+    // Decompose value as (value/denom*denom + value%denom),
+    // substitute into (value*numer)/denom and simplify.
+    // r < denom, so (denom*numer) is the upper bound of (r*numer)
+    q * numer + r * numer / denom
+}
+
+pub(crate) mod nanos;
+pub(crate) mod tag;
+
+// Reads an arbitrary POSIX clock, see `ClockHandle`
+#[cfg(all(unix, feature = "libc"))]
+#[allow(dead_code)] // backends with their own `clock_gettime` don't use it
+#[allow(clippy::useless_conversion)] // `time_t` and `c_long` are `i32` on some targets
+pub fn read_clock(clock: libc::clockid_t) -> std::io::Result<std::time::Duration> {
+    let mut t = std::mem::MaybeUninit::uninit();
+    if unsafe { libc::clock_gettime(clock, t.as_mut_ptr()) } == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let t = unsafe { t.assume_init() };
+    duration_from_parts(t.tv_sec.into(), t.tv_nsec.into())
+}
+
+// Converts a clock reading to the time since the clock epoch. Clocks with
+// dynamic ids may be set to any time, including one before their epoch.
+#[cfg(all(unix, feature = "libc"))]
+pub(crate) fn duration_from_parts(secs: i64, nanos: i64) -> std::io::Result<std::time::Duration> {
+    match (u64::try_from(secs), u32::try_from(nanos)) {
+        (Ok(secs), Ok(nanos)) if nanos < 1_000_000_000 => Ok(std::time::Duration::new(secs, nanos)),
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "clock reading precedes the clock epoch",
+        )),
+    }
+}
+
+// CPU time consumed by the process and by the calling thread. The C libraries
+// of newlib targets, Redox and NuttX lack the CPU clocks.
+#[cfg(all(
+    unix,
+    not(any(target_env = "newlib", target_os = "redox", target_os = "nuttx")),
+    any(
+        not(any(target_os = "linux", target_os = "android")),
+        any(not(feature = "linux-raw"), feature = "linux-libc")
+    ),
+))]
+#[allow(dead_code)] // the `linux-raw` backend issues its own system calls
+pub fn process_cpu_now() -> std::time::Duration {
+    cpu_time(libc::CLOCK_PROCESS_CPUTIME_ID)
+}
+
+#[cfg(all(
+    unix,
+    not(any(target_env = "newlib", target_os = "redox", target_os = "nuttx")),
+    any(
+        not(any(target_os = "linux", target_os = "android")),
+        any(not(feature = "linux-raw"), feature = "linux-libc")
+    ),
+))]
+#[allow(dead_code)] // the `linux-raw` backend issues its own system calls
+pub fn thread_cpu_now() -> std::time::Duration {
+    cpu_time(libc::CLOCK_THREAD_CPUTIME_ID)
+}
+
+#[cfg(all(
+    unix,
+    not(any(target_env = "newlib", target_os = "redox", target_os = "nuttx")),
+    any(
+        not(any(target_os = "linux", target_os = "android")),
+        any(not(feature = "linux-raw"), feature = "linux-libc")
+    ),
+))]
+#[allow(dead_code)]
+fn cpu_time(clock: libc::clockid_t) -> std::time::Duration {
+    let mut t = std::mem::MaybeUninit::uninit();
+    // The CPU clocks of the calling process and thread are always readable
+    let r = unsafe { libc::clock_gettime(clock, t.as_mut_ptr()) };
+    assert_eq!(
+        r,
+        0,
+        "clock_gettime failed: {}",
+        std::io::Error::last_os_error()
+    );
+    let t = unsafe { t.assume_init() };
+    std::time::Duration::new(t.tv_sec as u64, t.tv_nsec as u32)
+}
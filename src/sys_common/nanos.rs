@@ -0,0 +1,123 @@
+//! Instant arithmetic shared by backends whose clock is a nanosecond counter.
+//!
+//! A backend reexports [`Instant`] and adds the clock reading functions
+//! (`now`, `try_now`, `now_coarse`, `resolution`) in its own `impl` block.
+use std::fmt;
+use std::time::Duration;
+
+use super::tag::Tag;
+
+const NSEC_PER_SEC: u64 = 1_000_000_000;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant {
+    // nanoseconds since the epoch of the platform clock
+    t: u64,
+    // the clock the instant was read from, in debug builds
+    clock: Tag,
+}
+
+#[allow(dead_code)] // not all functions are used on all platforms
+impl Instant {
+    pub const MIN: Instant = Instant {
+        t: 0,
+        clock: Tag::UNKNOWN,
+    };
+    pub const MAX: Instant = Instant {
+        t: u64::MAX,
+        clock: Tag::UNKNOWN,
+    };
+
+    pub fn as_nanos(&self) -> Option<u64> {
+        Some(self.t)
+    }
+
+    pub const fn from_nanos(nanos: u64) -> Instant {
+        Instant {
+            t: nanos,
+            clock: Tag::UNKNOWN,
+        }
+    }
+
+    // For backends that read one of several clocks, identified by their
+    // `clockid_t`
+    pub fn from_clock_nanos(clock: i64, nanos: u64) -> Instant {
+        Instant {
+            t: nanos,
+            clock: Tag::of(clock),
+        }
+    }
+
+    pub fn duration_since_epoch(&self) -> Duration {
+        Duration::from_nanos(self.t)
+    }
+
+    pub fn into_bits(self) -> Option<u64> {
+        Some(self.t)
+    }
+
+    pub fn from_bits(bits: u64) -> Instant {
+        Instant::from_nanos(bits)
+    }
+
+    #[cfg(all(unix, feature = "libc"))]
+    pub fn from_timespec(t: libc::timespec) -> Option<Instant> {
+        if t.tv_nsec < 0 || t.tv_nsec >= NSEC_PER_SEC as libc::c_long {
+            return None;
+        }
+        let nanos = u64::try_from(t.tv_sec)
+            .ok()?
+            .checked_mul(NSEC_PER_SEC)?
+            .checked_add(t.tv_nsec as u64)?;
+        Some(Instant::from_nanos(nanos))
+    }
+
+    #[cfg(all(unix, feature = "libc"))]
+    pub fn as_timespec(&self) -> Option<libc::timespec> {
+        // `timespec` has private padding on targets with 64-bit `time_t`
+        // and 32-bit `long`, so it can't be built with a struct literal
+        let mut t: libc::timespec = unsafe { std::mem::zeroed() };
+        t.tv_sec = (self.t / NSEC_PER_SEC).try_into().ok()?;
+        t.tv_nsec = (self.t % NSEC_PER_SEC) as libc::c_long;
+        Some(t)
+    }
+
+    #[cfg(all(unix, feature = "libc"))]
+    pub fn from_clock(clock: libc::clockid_t, t: Duration) -> Option<Instant> {
+        Some(Instant::from_clock_nanos(
+            clock as i64,
+            checked_dur2nanos(&t)?,
+        ))
+    }
+
+    pub fn checked_sub_instant(&self, other: &Instant) -> Option<Duration> {
+        self.clock.check(other.clock);
+        Some(Duration::from_nanos(self.t.checked_sub(other.t)?))
+    }
+
+    pub fn checked_add_duration(&self, other: &Duration) -> Option<Instant> {
+        Some(Instant {
+            t: self.t.checked_add(checked_dur2nanos(other)?)?,
+            clock: self.clock,
+        })
+    }
+
+    pub fn checked_sub_duration(&self, other: &Duration) -> Option<Instant> {
+        Some(Instant {
+            t: self.t.checked_sub(checked_dur2nanos(other)?)?,
+            clock: self.clock,
+        })
+    }
+}
+
+impl fmt::Debug for Instant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Instant").field("t", &self.t).finish()
+    }
+}
+
+fn checked_dur2nanos(dur: &Duration) -> Option<u64> {
+    dur.as_secs()
+        .checked_mul(NSEC_PER_SEC)?
+        .checked_add(dur.subsec_nanos() as u64)
+}
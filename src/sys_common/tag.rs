@@ -0,0 +1,63 @@
+//! The clock an instant was read from.
+//!
+//! It is tracked in debug builds to catch arithmetic between instants of
+//! different clocks, e.g. an instant converted from a `CLOCK_MONOTONIC`
+//! [`ClockInstant`](crate::ClockInstant) and one of `CLOCK_BOOTTIME`, and is
+//! zero-sized otherwise. Backends with a single clock and instants built from
+//! raw values are untagged and match any clock. Tags don't take part in
+//! comparisons.
+
+#[derive(Copy, Clone)]
+pub struct Tag {
+    // The `clockid_t` of the clock
+    #[cfg(debug_assertions)]
+    clock: Option<i64>,
+}
+
+#[allow(dead_code)] // not all backends tag their instants
+impl Tag {
+    pub const UNKNOWN: Tag = Tag {
+        #[cfg(debug_assertions)]
+        clock: None,
+    };
+
+    #[cfg_attr(not(debug_assertions), allow(unused_variables))]
+    pub fn of(clock: i64) -> Tag {
+        Tag {
+            #[cfg(debug_assertions)]
+            clock: Some(clock),
+        }
+    }
+
+    #[cfg_attr(not(debug_assertions), allow(unused_variables))]
+    pub fn check(self, other: Tag) {
+        #[cfg(debug_assertions)]
+        if let (Some(a), Some(b)) = (self.clock, other.clock) {
+            assert_eq!(a, b, "instants of different clocks are not comparable");
+        }
+    }
+}
+
+impl PartialEq for Tag {
+    fn eq(&self, _: &Tag) -> bool {
+        true
+    }
+}
+
+impl Eq for Tag {}
+
+impl PartialOrd for Tag {
+    fn partial_cmp(&self, other: &Tag) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Tag {
+    fn cmp(&self, _: &Tag) -> core::cmp::Ordering {
+        core::cmp::Ordering::Equal
+    }
+}
+
+impl core::hash::Hash for Tag {
+    fn hash<H: core::hash::Hasher>(&self, _: &mut H) {}
+}
@@ -0,0 +1,166 @@
+//! Leap-second-free wall-clock time
+use core::ops::{Add, Sub};
+use core::time::Duration;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{Instant, SignedDuration};
+
+// TAI - UTC since 2017-01-01, the latest leap second
+const TAI_UTC_OFFSET: Duration = Duration::from_secs(37);
+
+/// A point in International Atomic Time (TAI).
+///
+/// TAI doesn't have leap seconds, so the time between two `TaiTime`s is the
+/// real time that passed, unless the system clock was stepped in between.
+///
+/// On Linux and Android it is read from `CLOCK_TAI`. The kernel only knows
+/// the TAI offset if an NTP daemon set it; until then, and on other
+/// platforms, the UTC system time plus the TAI-UTC offset of 37 seconds in
+/// effect since 2017 is used. [`TaiTime::is_exact`] tells which.
+///
+/// # Examples
+///
+/// Logging absolute timestamps next to the instants used for timeouts:
+///
+/// ```
+/// use boot_time::TaiTime;
+///
+/// let (tai, instant) = TaiTime::now_with_instant();
+/// println!("{:?} at {:?}", tai.duration_since_epoch(), instant);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct TaiTime {
+    // time since 1970-01-01 00:00:00 TAI
+    t: Duration,
+}
+
+impl TaiTime {
+    /// 1970-01-01 00:00:00 TAI, the epoch of `CLOCK_TAI`.
+    pub const EPOCH: TaiTime = TaiTime { t: Duration::ZERO };
+
+    /// Returns the current TAI time.
+    #[must_use]
+    pub fn now() -> TaiTime {
+        TaiTime { t: now().0 }
+    }
+
+    /// Returns the current TAI time and [`Instant`], sampled back-to-back.
+    #[must_use]
+    pub fn now_with_instant() -> (TaiTime, Instant) {
+        let tai = TaiTime::now();
+        (tai, Instant::now())
+    }
+
+    /// Returns `true` if the kernel provides the TAI time, or `false` if it is
+    /// derived from the UTC system time with a fixed offset.
+    pub fn is_exact() -> bool {
+        now().1
+    }
+
+    /// Creates a time the given duration after [`TaiTime::EPOCH`].
+    pub const fn from_duration_since_epoch(t: Duration) -> TaiTime {
+        TaiTime { t }
+    }
+
+    /// Returns the time elapsed from [`TaiTime::EPOCH`] to this time.
+    #[must_use]
+    pub const fn duration_since_epoch(&self) -> Duration {
+        self.t
+    }
+
+    /// Returns the signed amount of time from another time to this one.
+    #[must_use]
+    pub fn signed_duration_since(&self, other: TaiTime) -> SignedDuration {
+        match self.t.checked_sub(other.t) {
+            Some(d) => SignedDuration::from(d),
+            None => SignedDuration::negative(other.t - self.t),
+        }
+    }
+
+    /// Returns the amount of time elapsed from another time to this one, or
+    /// `None` if that time is later than this one.
+    #[must_use]
+    pub fn checked_duration_since(&self, earlier: TaiTime) -> Option<Duration> {
+        self.t.checked_sub(earlier.t)
+    }
+
+    /// Returns `Some(t)` where `t` is the time `self + duration` if `t` can be
+    /// represented, `None` otherwise.
+    pub fn checked_add(&self, duration: Duration) -> Option<TaiTime> {
+        self.t
+            .checked_add(duration)
+            .map(TaiTime::from_duration_since_epoch)
+    }
+
+    /// Returns `Some(t)` where `t` is the time `self - duration` if `t` can be
+    /// represented, `None` otherwise.
+    pub fn checked_sub(&self, duration: Duration) -> Option<TaiTime> {
+        self.t
+            .checked_sub(duration)
+            .map(TaiTime::from_duration_since_epoch)
+    }
+}
+
+impl Add<Duration> for TaiTime {
+    type Output = TaiTime;
+
+    fn add(self, other: Duration) -> TaiTime {
+        self.checked_add(other)
+            .expect("overflow when adding duration to TAI time")
+    }
+}
+
+impl Sub<Duration> for TaiTime {
+    type Output = TaiTime;
+
+    fn sub(self, other: Duration) -> TaiTime {
+        self.checked_sub(other)
+            .expect("overflow when subtracting duration from TAI time")
+    }
+}
+
+// Returns the TAI time and whether it came from the kernel
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    any(not(feature = "linux-raw"), feature = "linux-libc")
+))]
+fn now() -> (Duration, bool) {
+    let tai = read(libc::CLOCK_TAI);
+    let utc = read(libc::CLOCK_REALTIME);
+    // `CLOCK_TAI` equals `CLOCK_REALTIME` until the TAI offset is set
+    if tai.saturating_sub(utc) >= Duration::from_secs(1) {
+        (tai, true)
+    } else {
+        (utc + TAI_UTC_OFFSET, false)
+    }
+}
+
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    any(not(feature = "linux-raw"), feature = "linux-libc")
+))]
+fn read(clock: libc::clockid_t) -> Duration {
+    let mut t = core::mem::MaybeUninit::uninit();
+    // Both clocks exist since Linux 3.10 and the system time is never before
+    // the epoch in practice
+    let r = unsafe { libc::clock_gettime(clock, t.as_mut_ptr()) };
+    if r != 0 {
+        return utc_now();
+    }
+    let t = unsafe { t.assume_init() };
+    Duration::new(t.tv_sec as u64, t.tv_nsec as u32)
+}
+
+#[cfg(not(all(
+    any(target_os = "linux", target_os = "android"),
+    any(not(feature = "linux-raw"), feature = "linux-libc")
+)))]
+fn now() -> (Duration, bool) {
+    (utc_now() + TAI_UTC_OFFSET, false)
+}
+
+fn utc_now() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+}
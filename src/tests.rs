@@ -52,12 +52,983 @@ fn instant_monotonic_concurrent() -> std::thread::Result<()> {
     Ok(())
 }
 
+#[test]
+#[cfg(any(unix, windows))]
+fn instant_try_now() {
+    let a = Instant::now();
+    let b = Instant::try_now().unwrap();
+    assert!(b >= a);
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn instant_coarse_monotonic() {
+    let a = Instant::now_coarse();
+    let b = Instant::now_coarse();
+    assert!(b >= a);
+    assert!(Instant::now() + SECOND > b);
+}
+
+#[test]
+#[cfg(all(any(unix, windows), feature = "raw"))]
+fn instant_raw_roundtrip() {
+    let a = Instant::now();
+    let b = Instant::from_raw(a.into_raw());
+    assert_almost_eq!(a, b);
+    assert_eq!(Instant::from_raw(0).into_raw(), 0);
+    assert_eq!(
+        Instant::from_raw(1_500_000_000) + SECOND,
+        Instant::from_raw(2_500_000_000)
+    );
+}
+
+#[test]
+#[cfg(all(unix, feature = "libc"))]
+fn instant_timespec_roundtrip() {
+    let a = Instant::now();
+    let ts = a.as_timespec().unwrap();
+    assert_almost_eq!(Instant::from_timespec(ts).unwrap(), a);
+    let mut invalid = ts;
+    invalid.tv_nsec = 1_000_000_000;
+    assert!(Instant::from_timespec(invalid).is_none());
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn instant_resolution() {
+    let res = Instant::resolution();
+    assert!(res > Duration::ZERO);
+    assert!(res <= SECOND);
+}
+
+#[test]
+#[cfg(all(any(unix, windows), target_has_atomic = "64"))]
+fn atomic_instant() {
+    use super::AtomicInstant;
+    use std::sync::atomic::Ordering;
+
+    let a = Instant::now();
+    let atomic = AtomicInstant::new(a);
+    assert_eq!(atomic.load(Ordering::Relaxed), a);
+
+    let later = a + SECOND;
+    assert_eq!(atomic.fetch_max(later, Ordering::Relaxed), a);
+    assert_eq!(atomic.fetch_max(a, Ordering::Relaxed), later);
+    assert_eq!(atomic.load(Ordering::Relaxed), later);
+
+    atomic.store(a, Ordering::Relaxed);
+    assert_eq!(atomic.swap(later, Ordering::Relaxed), a);
+    assert_eq!(atomic.into_inner(), later);
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn instant_sentinels() {
+    let now = Instant::now();
+    let never = Instant::far_future();
+    let long_ago = Instant::distant_past();
+    assert!(never > now);
+    assert!(long_ago <= now);
+    assert!(never
+        .checked_add(Duration::from_secs(60 * 60 * 24 * 365))
+        .is_some());
+    assert_eq!(long_ago.duration_since(now), Duration::ZERO);
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+fn instant_duration_since_boot() {
+    let a = Instant::now();
+    let uptime = a.duration_since_boot().unwrap();
+    assert!(uptime > Duration::ZERO);
+    assert_eq!((a + SECOND).duration_since_boot(), Some(uptime + SECOND));
+    assert_almost_eq!(Instant::boot_epoch().unwrap() + uptime, a);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn instant_display() {
+    let t = Instant::boot_epoch().unwrap() + Duration::new(1234, 567_890_123);
+    assert_eq!(t.to_string(), "1234.567890s since boot");
+    assert_eq!(format!("{t:.3}"), "1234.567s since boot");
+    assert_eq!(format!("{t:.0}"), "1234s since boot");
+    assert_eq!(format!("{t:.9}"), "1234.567890123s since boot");
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn instant_program_start() {
+    let start = Instant::program_start();
+    assert!(start <= Instant::now());
+    assert_eq!(Instant::program_start(), start);
+}
+
+#[test]
+#[cfg(all(
+    any(target_os = "linux", target_os = "android", target_os = "openbsd"),
+    not(feature = "force-monotonic")
+))]
+fn instant_matches_boottime_clock() {
+    fn boottime() -> Duration {
+        let mut t: libc::timespec = unsafe { std::mem::zeroed() };
+        assert_eq!(
+            unsafe { libc::clock_gettime(libc::CLOCK_BOOTTIME, &mut t) },
+            0
+        );
+        Duration::new(t.tv_sec as u64, t.tv_nsec as u32)
+    }
+
+    let before = boottime();
+    let uptime = Instant::now().duration_since_boot().unwrap();
+    let after = boottime();
+    assert!(before <= uptime && uptime <= after);
+}
+
+#[test]
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "watchos",
+    target_os = "tvos",
+    target_os = "visionos"
+))]
+fn instant_mach_timebase_conversion() {
+    // Durations survive the conversion to mach ticks and back within a tick
+    let now = Instant::now();
+    let resolution = Instant::resolution();
+    for d in [
+        Duration::from_nanos(1),
+        Duration::from_micros(1),
+        Duration::from_millis(1),
+        SECOND,
+        Duration::from_secs(60 * 60 * 24),
+    ] {
+        let measured = (now + d) - now;
+        assert!(measured <= d && d - measured < resolution, "{d:?}");
+    }
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn clock_source() {
+    let source = crate::clock_source();
+    assert_ne!(source, crate::ClockSource::StdFallback);
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android", target_os = "macos"),
+        not(feature = "force-monotonic")
+    ))]
+    assert!(source.is_suspend_aware(), "{source:?}");
+    #[cfg(all(unix, feature = "force-monotonic"))]
+    assert!(!source.is_suspend_aware(), "{source:?}");
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple"))]
+fn config_init_after_clock_selection() {
+    // Selecting the clock installs the default configuration
+    let _ = Instant::now();
+    assert!(crate::Config::new().suspend_aware(false).init().is_err());
+}
+
+#[test]
+fn config_fallback_policy() {
+    use crate::{ClockSource, Config, FallbackPolicy};
+
+    let strict = Config::new().fallback_policy(FallbackPolicy::Strict);
+    assert!(strict.check_fallback(ClockSource::BootTime).is_ok());
+    assert!(strict
+        .clone()
+        .suspend_aware(false)
+        .check_fallback(ClockSource::Monotonic)
+        .is_ok());
+    let err = strict.check_fallback(ClockSource::Monotonic);
+    if cfg!(feature = "force-monotonic") {
+        assert!(err.is_ok());
+    } else {
+        assert_eq!(
+            err.unwrap_err().fallback_source(),
+            Some(ClockSource::Monotonic)
+        );
+    }
+    assert!(Config::new().check_fallback(ClockSource::Monotonic).is_ok());
+}
+
+#[test]
+#[cfg(all(unix, feature = "libc"))]
+fn clock_handle() {
+    use crate::ClockHandle;
+
+    let monotonic = ClockHandle::from_raw(libc::CLOCK_MONOTONIC);
+    let realtime = ClockHandle::from_raw(libc::CLOCK_REALTIME);
+    let a = monotonic.now().unwrap();
+    let b = monotonic.now().unwrap();
+    assert!(b >= a);
+    assert_eq!((a + SECOND) - a, SECOND);
+
+    let other = realtime.now().unwrap();
+    assert_ne!(a, other);
+    assert_eq!(a.partial_cmp(&other), None);
+    assert_eq!(a.checked_duration_since(other), None);
+
+    assert!(ClockHandle::from_raw(libc::clockid_t::MAX).now().is_err());
+}
+
+#[test]
+#[cfg(all(
+    any(target_os = "linux", target_os = "android", target_vendor = "apple"),
+    feature = "libc",
+    debug_assertions
+))]
+#[should_panic(expected = "instants of different clocks are not comparable")]
+fn instant_of_other_clock() {
+    use crate::ClockHandle;
+
+    // Instant::now never reads the real-time clock
+    let realtime = ClockHandle::from_raw(libc::CLOCK_REALTIME).now().unwrap();
+    let realtime = Instant::from_clock_instant(realtime).unwrap();
+    let _ = Instant::now().checked_duration_since(realtime);
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn typed_instant() {
+    use crate::{BootClock, Clock, MonotonicClock, RawMonotonicClock, TypedInstant};
+
+    fn check<C: Clock>() {
+        let a = TypedInstant::<C>::now();
+        let b = TypedInstant::<C>::now();
+        assert!(b >= a);
+        assert_eq!((a + SECOND) - a, SECOND);
+        assert_eq!(a - (a + SECOND), Duration::ZERO);
+        assert_eq!(a.checked_duration_since(a + SECOND), None);
+    }
+
+    check::<BootClock>();
+    check::<MonotonicClock>();
+    check::<RawMonotonicClock>();
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn raw_instant() {
+    use crate::{RawInstant, RawMonotonicClock, TypedInstant};
+
+    let a: TypedInstant<RawMonotonicClock> = RawInstant::now();
+    let b = RawInstant::now();
+    assert!(b >= a);
+    assert_eq!(b.checked_duration_since(a), Some(b - a));
+}
+
+#[test]
+#[cfg(any(
+    windows,
+    all(
+        unix,
+        not(any(target_env = "newlib", target_os = "redox", target_os = "nuttx"))
+    ),
+))]
+fn cpu_instants() {
+    use crate::{ProcessCpuInstant, ThreadCpuInstant};
+
+    let process = ProcessCpuInstant::now();
+    let thread = ThreadCpuInstant::now();
+    // Burn some CPU time
+    let mut x = 0u64;
+    while process.elapsed() < Duration::from_millis(10) {
+        x = std::hint::black_box(x.wrapping_add(1));
+    }
+    assert!(thread.elapsed() > Duration::ZERO);
+    assert_eq!(process.checked_duration_since(process + SECOND), None);
+    assert!(format!("{process:?}").contains("ProcessCpuClock"));
+}
+
+#[test]
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn tai_time() {
+    use crate::TaiTime;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let utc = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let (tai, _) = TaiTime::now_with_instant();
+    // TAI is ahead of UTC by the leap seconds since 1972
+    let offset = tai.signed_duration_since(TaiTime::from_duration_since_epoch(utc));
+    assert!(!offset.is_negative());
+    assert!(offset.abs() >= Duration::from_secs(37));
+    assert_eq!((tai + SECOND).checked_duration_since(tai), Some(SECOND));
+    assert_eq!(tai.checked_duration_since(tai + SECOND), None);
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn dual_instant() {
+    use crate::DualInstant;
+
+    let a = DualInstant::now();
+    let b = DualInstant::now();
+    assert!(b.boot() >= a.boot());
+    assert!(b.monotonic() >= a.monotonic());
+    assert!(b.suspended_duration_since(a) <= b.duration_since(a));
+    assert_eq!(a.suspended_duration_since(b), Duration::ZERO);
+    let split = b.split_duration_since(a);
+    assert_eq!(split.total(), b.duration_since(a));
+    assert_eq!(a.split_duration_since(b).total(), Duration::ZERO);
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn boot_id() {
+    use crate::{boot_id, BootId};
+
+    match (boot_id(), boot_id()) {
+        (Ok(a), Ok(b)) => {
+            assert_eq!(a, b);
+            assert_eq!(BootId::from_bytes(a.to_bytes()), a);
+            assert_eq!(a.to_string().len(), 36);
+        }
+        (Err(_), Err(_)) => {}
+        _ => panic!("boot_id changed between calls"),
+    }
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn sleep() {
+    let start = Instant::now();
+    crate::sleep(Duration::from_millis(20));
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    crate::sleep(Duration::ZERO);
+
+    let deadline = Instant::now() + Duration::from_millis(20);
+    crate::sleep_until(deadline);
+    assert!(Instant::now() >= deadline);
+    crate::sleep_until(start);
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn deadline() {
+    use crate::Deadline;
+
+    let deadline = Deadline::from_timeout(Duration::from_millis(20));
+    assert!(!deadline.has_elapsed());
+    assert!(deadline.checked_remaining().unwrap() <= Duration::from_millis(20));
+    crate::sleep_until(deadline);
+    assert!(deadline.has_elapsed());
+    assert_eq!(deadline.remaining(), Duration::ZERO);
+    assert_eq!(deadline.checked_remaining(), None);
+    assert_eq!(Deadline::from(deadline.instant()), deadline);
+    assert!(Deadline::from_timeout(Duration::MAX) >= Deadline::never());
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn countdown() {
+    use crate::Countdown;
+
+    let mut countdown = Countdown::paused(Duration::from_millis(20));
+    assert!(countdown.is_paused());
+    assert_eq!(countdown.deadline(), None);
+    std::thread::sleep(Duration::from_millis(10));
+    assert_eq!(countdown.remaining(), Duration::from_millis(20));
+    countdown.resume();
+    std::thread::sleep(Duration::from_millis(10));
+    countdown.pause();
+    let elapsed = countdown.elapsed();
+    assert!(elapsed >= Duration::from_millis(10));
+    std::thread::sleep(Duration::from_millis(10));
+    assert_eq!(countdown.elapsed(), elapsed);
+    countdown.resume();
+    crate::sleep_until(countdown.deadline().unwrap());
+    assert!(countdown.has_elapsed());
+    assert_eq!(countdown.remaining(), Duration::ZERO);
+    countdown.reset();
+    assert!(!countdown.has_elapsed());
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn interval_missed_ticks() {
+    use crate::MissedTickBehavior;
+
+    let period = Duration::from_millis(50);
+    for behavior in [
+        MissedTickBehavior::Burst,
+        MissedTickBehavior::Delay,
+        MissedTickBehavior::Skip,
+    ] {
+        let start = Instant::now();
+        let mut interval = crate::interval_at(start, period);
+        interval.set_missed_tick_behavior(behavior);
+        assert_eq!(interval.tick(), start);
+        std::thread::sleep(period * 3 + period / 2);
+        assert_eq!(interval.tick(), start + period);
+        assert_eq!(interval.missed_ticks(), 2);
+        let next = interval.next_tick();
+        match behavior {
+            MissedTickBehavior::Burst => assert_eq!(next, start + period * 2),
+            MissedTickBehavior::Delay => assert!(next >= start + period * 4 + period / 2),
+            MissedTickBehavior::Skip => assert_eq!(next, start + period * 4),
+        }
+    }
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn periodic_timer() {
+    use crate::PeriodicTimer;
+    use std::sync::mpsc;
+
+    let (tx, rx) = mpsc::channel();
+    let start = Instant::now();
+    let interval = crate::interval_at(start, Duration::from_millis(5));
+    let timer = PeriodicTimer::spawn(interval, move |tick| {
+        // A slow callback doesn't shift the schedule
+        std::thread::sleep(Duration::from_millis(2));
+        let _ = tx.send(tick);
+    })
+    .unwrap();
+    for n in 0..3 {
+        assert_eq!(rx.recv().unwrap(), start + Duration::from_millis(5) * n);
+    }
+    timer.stop();
+    while rx.recv().is_ok() {}
+
+    // Each missed tick counts once, however many burst ticks follow it
+    let (tx, rx) = mpsc::channel();
+    let start = Instant::now() - Duration::from_millis(3500);
+    let mut interval = crate::interval_at(start, SECOND);
+    interval.set_missed_tick_behavior(crate::MissedTickBehavior::Burst);
+    let timer = PeriodicTimer::spawn(interval, move |tick| {
+        let _ = tx.send(tick);
+    })
+    .unwrap();
+    for n in 0..4 {
+        assert_eq!(rx.recv().unwrap(), start + SECOND * n);
+    }
+    assert_eq!(timer.missed_ticks(), 3);
+    timer.stop();
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn scheduler() {
+    use crate::Scheduler;
+    use std::sync::mpsc;
+
+    let scheduler = Scheduler::new().unwrap();
+    let (tx, rx) = mpsc::channel();
+    let start = Instant::now();
+    for (n, delay) in [(2, 20), (1, 10), (3, 30)] {
+        let tx = tx.clone();
+        scheduler.schedule_after(Duration::from_millis(delay), move || {
+            let _ = tx.send((n, Instant::now()));
+        });
+    }
+    let cancelled = scheduler.schedule_after(SECOND, || panic!("cancelled job ran"));
+    assert!(cancelled.cancel());
+    scheduler.schedule_at(start, || panic!("a panicking job"));
+    for expected in 1..=3 {
+        let (n, at) = rx.recv().unwrap();
+        assert_eq!(n, expected);
+        assert!(at >= start + Duration::from_millis(10 * n));
+    }
+    assert!(!cancelled.is_armed());
+    let never = scheduler.schedule_after(Duration::from_secs(60), || {});
+    assert_eq!(scheduler.pending(), 1);
+    drop(scheduler);
+    assert!(!never.cancel());
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn delay() {
+    use crate::Delay;
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::{Context, Poll, Waker};
+    use std::sync::Arc;
+    use std::task::Wake;
+
+    struct Unpark(std::thread::Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let start = Instant::now();
+    let mut delay = Delay::new(SECOND);
+    assert!(Pin::new(&mut delay).poll(&mut cx).is_pending());
+    delay.reset(start + Duration::from_millis(10));
+    while Pin::new(&mut delay).poll(&mut cx).is_pending() {
+        std::thread::park();
+    }
+    assert!(Instant::now() >= start + Duration::from_millis(10));
+    assert_eq!(
+        Pin::new(&mut Delay::until(start)).poll(&mut cx),
+        Poll::Ready(())
+    );
+}
+
+#[test]
+#[cfg(all(feature = "embassy-time-driver", any(unix, windows)))]
+fn embassy_time_driver() {
+    use core::task::Waker;
+    use std::sync::Arc;
+    use std::task::Wake;
+
+    struct Unpark(std::thread::Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let ticks_per_ms = embassy_time_driver::TICK_HZ / 1000;
+    let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+    let start = embassy_time_driver::now();
+    let at = start + 10 * ticks_per_ms;
+    embassy_time_driver::schedule_wake(start + 1000 * ticks_per_ms, &waker);
+    embassy_time_driver::schedule_wake(at, &waker);
+    while embassy_time_driver::now() < at {
+        std::thread::park();
+    }
+    assert!(embassy_time_driver::now() >= at);
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn delay_queue() {
+    use crate::DelayQueue;
+
+    let queue = DelayQueue::new();
+    let now = Instant::now();
+    queue.insert(3, now + Duration::from_millis(20));
+    queue.insert(1, now);
+    queue.insert(2, now);
+    assert_eq!(queue.len(), 3);
+    assert_eq!(queue.pop_expired(), Some(1));
+    assert_eq!(queue.pop_expired(), Some(2));
+    assert_eq!(queue.pop_expired(), None);
+    assert_eq!(queue.next_deadline(), Some(now + Duration::from_millis(20)));
+    assert_eq!(queue.wait_next(), 3);
+    assert!(Instant::now() >= now + Duration::from_millis(20));
+    assert!(queue.is_empty());
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn timer_handle() {
+    use crate::{DelayQueue, Scheduler};
+    use std::sync::mpsc;
+
+    let queue = DelayQueue::new();
+    let now = Instant::now();
+    let first = queue.insert(1, now + SECOND);
+    let second = queue.insert(2, now);
+    assert!(first.is_armed());
+    assert!(first.reset(now));
+    assert!(second.cancel());
+    assert!(!second.cancel());
+    assert!(!second.reset(now));
+    assert_eq!(queue.pop_expired(), Some(1));
+    assert!(!first.is_armed());
+
+    let scheduler = Scheduler::new().unwrap();
+    let (tx, rx) = mpsc::channel();
+    let job = scheduler.schedule_after(Duration::from_secs(60), move || {
+        let _ = tx.send(Instant::now());
+    });
+    let start = Instant::now();
+    assert!(job.reset(start + Duration::from_millis(10)));
+    assert!(rx.recv().unwrap() >= start + Duration::from_millis(10));
+    assert!(!job.is_armed());
+    assert!(!job.reset(start));
+}
+
+#[test]
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    any(not(feature = "linux-raw"), feature = "linux-libc")
+))]
+fn thread_timer_slack() {
+    std::thread::spawn(|| {
+        crate::set_thread_timer_slack(Duration::from_millis(1)).unwrap();
+        assert_eq!(
+            crate::thread_timer_slack().unwrap(),
+            Duration::from_millis(1)
+        );
+        crate::set_thread_timer_slack(Duration::from_nanos(1)).unwrap();
+        assert_eq!(
+            crate::thread_timer_slack().unwrap(),
+            Duration::from_nanos(1)
+        );
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn sleep_until_precise() {
+    use crate::SpinSleeper;
+
+    let deadline = Instant::now() + Duration::from_millis(5);
+    crate::sleep_until_precise(deadline);
+    assert!(Instant::now() >= deadline);
+    let sleeper = SpinSleeper::new(Duration::from_millis(1));
+    let deadline = Instant::now() + Duration::from_millis(2);
+    sleeper.sleep_until(deadline);
+    assert!(Instant::now() >= deadline);
+    SpinSleeper::new(Duration::MAX).sleep(Duration::from_millis(1));
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn wake_alarm() {
+    use crate::WakeAlarm;
+
+    assert_eq!(WakeAlarm::is_supported(), WakeAlarm::new().is_ok());
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        any(not(feature = "linux-raw"), feature = "linux-libc")
+    ))]
+    {
+        let alarm = WakeAlarm::new_or_fallback().unwrap();
+        assert_eq!(alarm.warning().is_none(), WakeAlarm::is_supported());
+        alarm.set(Instant::now()).unwrap();
+        alarm.wait().unwrap();
+    }
+    // Needs privileges and a real-time clock or power plan that can wake the
+    // system
+    let Ok(alarm) = WakeAlarm::new() else {
+        return;
+    };
+    let deadline = Instant::now() + Duration::from_millis(20);
+    if alarm.set(deadline).is_err() {
+        return;
+    }
+    alarm.wait().unwrap();
+    assert!(Instant::now() >= deadline);
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn sleep_until_wall() {
+    use std::time::SystemTime;
+
+    let time = SystemTime::now() + Duration::from_millis(20);
+    // Needs privileges to wake the system
+    if crate::sleep_until_wall(time).is_ok() {
+        assert!(SystemTime::now() >= time);
+    }
+}
+
+#[test]
+#[cfg(all(feature = "tokio", any(target_os = "linux", target_os = "android")))]
+fn tokio_sleep() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let start = Instant::now();
+        let mut sleep = crate::tokio::sleep(Duration::from_secs(60));
+        sleep.reset(start + Duration::from_millis(10));
+        (&mut sleep).await;
+        assert!(Instant::now() >= start + Duration::from_millis(10));
+        assert!(sleep.is_elapsed());
+        sleep.reset(Instant::now() + Duration::from_millis(10));
+        assert!(!sleep.is_elapsed());
+        sleep.await;
+        crate::tokio::sleep_until(start).await;
+    });
+}
+
+#[test]
+#[cfg(all(feature = "tokio", any(target_os = "linux", target_os = "android")))]
+fn tokio_timeout() {
+    use crate::tokio::{sleep, timeout, timeout_at};
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let start = Instant::now();
+        let elapsed = timeout(Duration::from_millis(10), sleep(SECOND)).await;
+        assert!(elapsed.is_err());
+        assert!(Instant::now() >= start + Duration::from_millis(10));
+        let done = timeout(SECOND, sleep(Duration::from_millis(10))).await;
+        assert_eq!(done, Ok(()));
+        assert_eq!(timeout_at(start, async { 1 }).await, Ok(1));
+        let e = std::io::Error::from(elapsed.unwrap_err());
+        assert_eq!(e.kind(), std::io::ErrorKind::TimedOut);
+    });
+}
+
+#[test]
+#[cfg(all(feature = "tokio", any(target_os = "linux", target_os = "android")))]
+fn tokio_interval() {
+    use crate::MissedTickBehavior;
+    use core::future::poll_fn;
+    use core::pin::Pin;
+    use futures_core::Stream;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let period = Duration::from_millis(20);
+        let start = Instant::now();
+        let mut interval = crate::tokio::interval_at(start, period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        assert_eq!(interval.tick().await, start);
+        crate::sleep(period * 2 + period / 2);
+        assert_eq!(interval.tick().await, start + period);
+        assert_eq!(interval.missed_ticks(), 1);
+        let next = poll_fn(|cx| Pin::new(&mut interval).poll_next(cx)).await;
+        assert_eq!(next, Some(start + period * 3));
+        assert!(Instant::now() >= start + period * 3);
+    });
+}
+
+#[test]
+#[cfg(all(feature = "async-io", any(target_os = "linux", target_os = "android")))]
+fn async_io_timer() {
+    use crate::async_io::Timer;
+    use core::future::poll_fn;
+    use core::pin::Pin;
+    use futures_core::Stream;
+
+    async_io::block_on(async {
+        let start = Instant::now();
+        let mut timer = Timer::after(SECOND);
+        timer.set_after(Duration::from_millis(10));
+        let fired = (&mut timer).await;
+        assert!(Instant::now() >= fired && fired >= start + Duration::from_millis(10));
+        assert!(!timer.will_fire());
+        let period = Duration::from_millis(10);
+        let mut interval = Timer::interval_at(start, period);
+        for n in 0..3 {
+            let tick = poll_fn(|cx| Pin::new(&mut interval).poll_next(cx)).await;
+            assert_eq!(tick, Some(start + period * n));
+        }
+        assert!(!Timer::never().will_fire());
+    });
+}
+
+#[test]
+#[cfg(all(feature = "async-std", any(target_os = "linux", target_os = "android")))]
+fn async_std_timers() {
+    use crate::async_std::{interval_at, sleep, timeout};
+
+    async_io::block_on(async {
+        let start = Instant::now();
+        assert!(timeout(Duration::from_millis(10), sleep(SECOND))
+            .await
+            .is_err());
+        assert!(Instant::now() >= start + Duration::from_millis(10));
+        assert_eq!(timeout(SECOND, async { 1 }).await, Ok(1));
+        let period = Duration::from_millis(10);
+        let mut interval = interval_at(start, period);
+        assert_eq!(interval.tick().await, start);
+        assert_eq!(interval.tick().await, start + period);
+    });
+}
+
+#[test]
+#[cfg(all(
+    feature = "io-uring",
+    target_os = "linux",
+    any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "riscv64",
+        target_arch = "loongarch64",
+        target_arch = "powerpc64",
+    ),
+))]
+fn io_uring_timeout() {
+    use crate::io_uring::UringTimeout;
+
+    // Needs Linux 5.15 and io_uring allowed by seccomp
+    let Ok(mut ring) = io_uring::IoUring::new(4) else {
+        return;
+    };
+    let timeout = UringTimeout::after(Duration::from_millis(10));
+    let entry = timeout.timeout().user_data(7);
+    unsafe { ring.submission().push(entry.entry()).unwrap() };
+    ring.submit_and_wait(1).unwrap();
+    let cqe = ring.completion().next().unwrap();
+    assert_eq!(cqe.user_data(), 7);
+    if cqe.result() == -libc::EINVAL {
+        return;
+    }
+    assert_eq!(cqe.result(), -libc::ETIME);
+    assert!(Instant::now() >= timeout.deadline());
+}
+
+#[test]
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    any(not(feature = "linux-raw"), feature = "linux-libc")
+))]
+fn posix_timer() {
+    use crate::PosixTimer;
+    use std::sync::mpsc;
+
+    let (tx, rx) = mpsc::channel();
+    let timer = PosixTimer::with_callback(move || {
+        let _ = tx.send(Instant::now());
+    })
+    .unwrap();
+    timer.set_missed_tick_behavior(crate::MissedTickBehavior::Burst);
+    let start = Instant::now();
+    timer
+        .set_periodic(start + Duration::from_millis(5), Duration::from_millis(5))
+        .unwrap();
+    let first = rx.recv().unwrap();
+    let second = rx.recv().unwrap();
+    assert!(first >= start + Duration::from_millis(5));
+    assert!(second >= first);
+    timer.cancel().unwrap();
+    assert!(timer.overrun_count().is_ok());
+
+    // Callbacks still running when the timer is dropped must not touch it
+    let (tx, rx) = mpsc::channel();
+    let timer = PosixTimer::with_callback(move || {
+        let _ = tx.send(());
+        std::thread::sleep(Duration::from_millis(5));
+    })
+    .unwrap();
+    timer.set_missed_tick_behavior(crate::MissedTickBehavior::Delay);
+    timer
+        .set_periodic(Instant::now(), Duration::from_millis(1))
+        .unwrap();
+    rx.recv().unwrap();
+    drop(timer);
+    std::thread::sleep(Duration::from_millis(10));
+}
+
+#[test]
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    any(not(feature = "linux-raw"), feature = "linux-libc")
+))]
+fn timer_fd() {
+    use crate::{TimerClock, TimerFd};
+
+    for clock in [TimerClock::Boot, TimerClock::Monotonic] {
+        let timer = TimerFd::new(clock).unwrap();
+        let start = Instant::now();
+        timer
+            .set_deadline(start + Duration::from_millis(5))
+            .unwrap();
+        timer.set_interval(Duration::from_millis(5)).unwrap();
+        assert!(timer.read_expirations().unwrap() >= 1);
+        assert!(Instant::now() >= start + Duration::from_millis(5));
+        assert!(timer.read_expirations().unwrap() >= 1);
+        timer.disarm().unwrap();
+    }
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn clock_generation() {
+    let generation = crate::clock_generation();
+    let now = Instant::now();
+    assert!(now.is_valid());
+    // Deadlines of the current generation are valid
+    assert!((now + Duration::from_secs(60)).is_valid());
+    assert_eq!(crate::clock_generation(), generation);
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn power_subscribe() {
+    use crate::power;
+
+    match power::subscribe() {
+        Ok(mut events) => assert_eq!(events.try_next(), None),
+        Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::Unsupported),
+    }
+    if let Err(e) = power::on_resume(|| {}) {
+        assert_eq!(e.kind(), std::io::ErrorKind::Unsupported);
+    }
+    // Fails without systemd-logind, e.g. in containers
+    drop(power::SuspendInhibitor::new("running tests"));
+    // Fails if the kernel lacks `/sys/power/mem_sleep`
+    let _ = power::standby();
+    // The counter may be missing or unreadable, but it never is garbage
+    if let Err(e) = power::suspend_count() {
+        assert_ne!(e.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
+
+#[test]
+fn total_suspended_time() {
+    let a = crate::total_suspended_time();
+    // Less than the time since boot
+    #[cfg(any(unix, windows))]
+    if let Some(boot) = Instant::boot_epoch() {
+        assert!(a <= boot.elapsed());
+    }
+    let _ = a;
+}
+
+#[test]
+#[cfg(all(target_os = "android", feature = "android-wakelock"))]
+fn android_wake_lock() {
+    use crate::android::WakeLock;
+
+    // Tests run outside an app, without a `PowerManager`
+    let e = WakeLock::acquire("boot_time:test").unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::Unsupported);
+    let e = WakeLock::sleep_until("boot_time:test", Instant::now()).unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::Unsupported);
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn power_was_suspended_since() {
+    use crate::power;
+
+    let now = Instant::now();
+    let _ = power::was_suspended_since(now);
+    assert!(!power::was_suspended_since(now + Duration::from_secs(1)));
+    assert!(!power::was_suspended_since_dual(crate::DualInstant::now()));
+}
+
 #[test]
 fn instant_elapsed() {
     let a = Instant::now();
     let _ = a.elapsed();
 }
 
+#[test]
+#[cfg(any(unix, windows))]
+fn instant_elapsed_and_reset() {
+    let start = Instant::now();
+    let mut last = start;
+    let elapsed = last.elapsed_and_reset();
+    assert!(last >= start);
+    assert_eq!(last - start, elapsed);
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn instant_checked_elapsed() {
+    let a = Instant::now();
+    assert!(a.checked_elapsed().is_some());
+    assert_eq!((a + SECOND * 60).checked_elapsed(), None);
+}
+
 #[test]
 fn instant_math() {
     let a = Instant::now();
@@ -90,6 +1061,42 @@ fn instant_math() {
     assert_eq!(a + year, a.checked_add(year).unwrap());
 }
 
+#[test]
+#[cfg(any(unix, windows))]
+fn instant_saturating_math() {
+    let now = Instant::now();
+    let max = now.saturating_add(Duration::MAX);
+    assert!(max > now);
+    assert_eq!(max.checked_add(SECOND), None);
+    assert_eq!(max.saturating_add(SECOND), max);
+    assert_eq!(now.saturating_add(SECOND), now + SECOND);
+
+    let min = now.saturating_sub(Duration::MAX);
+    assert!(min < now);
+    assert_eq!(min.checked_sub(SECOND), None);
+    assert_eq!(now.saturating_sub(SECOND), now - SECOND);
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn instant_floor_round() {
+    let tick = Duration::from_millis(10);
+    let epoch = Instant::distant_past();
+    let t = epoch + Duration::new(5, 14_000_000);
+    assert_eq!(t.floor(tick), Some(epoch + Duration::new(5, 10_000_000)));
+    assert_eq!(t.round(tick), Some(epoch + Duration::new(5, 10_000_000)));
+    let t = epoch + Duration::new(5, 15_000_000);
+    assert_eq!(t.round(tick), Some(epoch + Duration::new(5, 20_000_000)));
+    assert_eq!(t.floor(Duration::ZERO), None);
+
+    let now = Instant::now();
+    let floor = now.floor(tick).unwrap();
+    assert!(floor <= now && now - floor < tick);
+    assert_eq!(floor.floor(tick), Some(floor));
+    let max = now.saturating_add(Duration::MAX);
+    assert_eq!(max.round(SECOND * 2), None);
+}
+
 #[test]
 fn instant_math_is_associative() {
     let now = Instant::now();
@@ -123,6 +1130,30 @@ fn instant_checked_duration_since_nopanic() {
     assert_eq!(now.checked_duration_since(now), Some(Duration::ZERO));
 }
 
+#[test]
+#[cfg(any(unix, windows))]
+fn instant_signed_duration_since() {
+    use super::SignedDuration;
+    use core::cmp::Ordering;
+
+    let now = Instant::now();
+    let later = now + SECOND;
+    assert_eq!(
+        later.signed_duration_since(now),
+        SignedDuration::from(SECOND)
+    );
+    assert_eq!(
+        now.signed_duration_since(later),
+        -SignedDuration::from(SECOND)
+    );
+    assert_eq!(now.signed_duration_since(now), SignedDuration::ZERO);
+    assert_eq!(now.signed_duration_since(later).as_nanos(), -1_000_000_000);
+    assert_eq!(now.signed_duration_since(later).signum(), Ordering::Less);
+    assert!(now.signed_duration_since(later) < SignedDuration::ZERO);
+    assert_eq!(-SignedDuration::ZERO, SignedDuration::ZERO);
+    assert_eq!(format!("{:?}", -SignedDuration::from(SECOND)), "-1s");
+}
+
 #[test]
 fn instant_saturating_duration_since_nopanic() {
     let a = Instant::now();
@@ -131,6 +1162,23 @@ fn instant_saturating_duration_since_nopanic() {
     assert_eq!(ret, Duration::ZERO);
 }
 
+#[test]
+fn instant_large_offsets() {
+    // Multi-year uptimes don't fit into the 32-bit `time_t` of legacy targets
+    let now = Instant::now();
+    for secs in [
+        i32::MAX as u64 + 1,
+        u32::MAX as u64 + 1,
+        100 * 365 * 24 * 60 * 60,
+    ] {
+        let offset = Duration::from_secs(secs);
+        let later = now + offset;
+        assert!(later > now);
+        assert_almost_eq!(later - now, offset);
+        assert_almost_eq!(later - offset, now);
+    }
+}
+
 #[test]
 fn big_math() {
     // Check that the same result occurs when adding/subtracting each duration one at a time as when
@@ -1,9 +1,17 @@
 //! Reimplementation of `std::time::Instant` for supported platforms
 use core::time::Duration;
 use std::fmt;
+use std::io;
 use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::sync::OnceLock;
 
 use crate::sys;
+use crate::SignedDuration;
+
+// Never reached by a real clock while leaving room for arithmetic.
+const FAR_FUTURE_NANOS: u64 = u64::MAX / 2;
+
+const NSEC_PER_SEC: u128 = 1_000_000_000;
 
 /// A measurement of a suspend-aware monotonically nondecreasing clock.
 /// Opaque and useful only with [`Duration`].
@@ -66,13 +74,36 @@ use crate::sys;
 ///
 /// |  Platform                         |               System call                                            |
 /// |-----------------------------------|----------------------------------------------------------------------|
-/// | UNIX (Linux/L4Re/Android/openBSD) | [clock_gettime (Monotonic Clock with CLOCK_BOOTTIME source)]         |
+/// | UNIX (Linux/Android/openBSD)      | [clock_gettime (Monotonic Clock with CLOCK_BOOTTIME source)]         |
+/// | OpenHarmony                       | [clock_gettime (Monotonic Clock with CLOCK_BOOTTIME source)]         |
+/// | L4Re                              | [clock_gettime (Monotonic Clock)], CLOCK_BOOTTIME if supported       |
 /// | UNIX (other)                      | [clock_gettime (Monotonic Clock with CLOCK_MONOTONIC source)]        |
-/// | Darwin                            | [mach_continuous_time]                                               |
-/// | Windows                           | [QueryPerformanceCounter]                                                                    |
+/// | Cygwin                            | [clock_gettime (Monotonic Clock with CLOCK_BOOTTIME source)]         |
+/// | ESP-IDF                           | [clock_gettime (Monotonic Clock with CLOCK_MONOTONIC source)]        |
+/// | Horizon (Nintendo 3DS)            | [clock_gettime (Monotonic Clock with CLOCK_MONOTONIC source)]        |
+/// | Darwin (all Apple platforms)      | [mach_continuous_time]                                               |
+/// | illumos/Solaris                   | [gethrtime]                                                          |
+/// | Haiku                             | [system_time]                                                        |
+/// | Fuchsia                           | [zx_clock_get_boot] (`zx_clock_get_monotonic` on older versions)     |
+/// | Windows 10+                       | [QueryInterruptTimePrecise]                                          |
+/// | WASI preview1                     | [clock_time_get] (monotonic clock)                                   |
+/// | WASI preview2                     | [wasi:clocks/monotonic-clock]                                        |
+/// | Browser (`web` feature)           | [performance.now()]                                                  |
+/// | Fortanix SGX                      | [insecure_time] usercall (untrusted host wall clock)                 |
+/// | UEFI                              | `EFI_TIMESTAMP_PROTOCOL` or TSC (via `std::time::Instant`)           |
+/// | Xous                              | ticktimer service (via `std::time::Instant`)                         |
+/// | Windows 7/8                       | [QueryPerformanceCounter] + suspend bias                             |
 ///
 /// [currently]: crate::io#platform-specific-behavior
 /// [QueryPerformanceCounter]: https://docs.microsoft.com/en-us/windows/win32/api/profileapi/nf-profileapi-queryperformancecounter
+/// [gethrtime]: https://illumos.org/man/3C/gethrtime
+/// [system_time]: https://www.haiku-os.org/docs/api/group__support__globals.html
+/// [zx_clock_get_boot]: https://fuchsia.dev/reference/syscalls/clock_get_boot
+/// [clock_time_get]: https://github.com/WebAssembly/WASI/blob/main/legacy/preview1/docs.md#clock_time_get
+/// [wasi:clocks/monotonic-clock]: https://github.com/WebAssembly/wasi-clocks
+/// [performance.now()]: https://developer.mozilla.org/en-US/docs/Web/API/Performance/now
+/// [insecure_time]: https://edp.fortanix.com/docs/api/fortanix_sgx_abi/struct.Usercalls.html#method.insecure_time
+/// [QueryInterruptTimePrecise]: https://learn.microsoft.com/en-us/windows/win32/api/realtimeapiset/nf-realtimeapiset-queryinterrupttimeprecise
 /// [clock_gettime (Monotonic Clock)]: https://linux.die.net/man/3/clock_gettime
 /// FreeBSD doesn't take into account suspended time in CLOCK_BOOTIME. So CLOCK_MONOTONIC is used.
 /// [mach_absolute_time]: https://developer.apple.com/documentation/kernel/1646199-mach_continuous_time
@@ -104,7 +135,7 @@ use crate::sys;
 /// [`checked_duration_since`]: Instant::checked_duration_since
 ///
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Instant(sys::Instant);
+pub struct Instant(pub(crate) sys::Instant);
 
 impl Instant {
     /// Returns an instant corresponding to "now".
@@ -121,6 +152,137 @@ impl Instant {
         Instant(sys::Instant::now())
     }
 
+    /// Returns an instant corresponding to "now", or the OS error if the
+    /// underlying clock could not be read.
+    ///
+    /// [`Instant::now`] panics in that case, which may happen in sandboxes that
+    /// deny the clock system call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::Instant;
+    ///
+    /// let now = Instant::try_now().expect("clock is unavailable");
+    /// ```
+    pub fn try_now() -> io::Result<Instant> {
+        sys::Instant::try_now().map(Instant)
+    }
+
+    /// Returns the instant the program started.
+    ///
+    /// The instant is captured the first time this function is called and is
+    /// the same for the rest of the process lifetime. Call it early in `main`
+    /// to anchor it to the program start.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::Instant;
+    ///
+    /// fn main() {
+    ///     let _ = Instant::program_start();
+    ///     // ...
+    ///     println!("uptime including suspend: {:?}", Instant::program_start().elapsed());
+    /// }
+    /// ```
+    #[must_use]
+    pub fn program_start() -> Instant {
+        static PROGRAM_START: OnceLock<Instant> = OnceLock::new();
+        *PROGRAM_START.get_or_init(Instant::now)
+    }
+
+    /// Returns an instant corresponding to "now" read from the cheapest
+    /// available clock source.
+    ///
+    /// The result is comparable with instants returned by [`Instant::now`] but
+    /// may lag behind the precise clock by up to one timer tick of the kernel.
+    /// So a coarse instant taken right after a precise one may compare as
+    /// earlier. Coarse instants are monotonic among themselves.
+    ///
+    /// | Platform          | Clock source                        | Worst-case error        |
+    /// |-------------------|-------------------------------------|-------------------------|
+    /// | FreeBSD/DragonFly | `CLOCK_MONOTONIC_FAST`              | `1/kern.hz` (1-10 ms)   |
+    /// | Darwin            | `mach_continuous_approximate_time`  | one scheduler tick      |
+    /// | Windows 10+       | `QueryInterruptTime`                | one timer interrupt     |
+    /// | Other             | same clock as [`Instant::now`]      | none                    |
+    ///
+    /// Linux has no coarse variant of `CLOCK_BOOTTIME`. Its precise reading is
+    /// served by vDSO and is cheap already.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::Instant;
+    ///
+    /// let now = Instant::now_coarse();
+    /// ```
+    #[must_use]
+    pub fn now_coarse() -> Instant {
+        Instant(sys::Instant::now_coarse())
+    }
+
+    /// Returns an instant that is later than any instant read from the clock.
+    ///
+    /// It is about 292 years after the clock epoch, which leaves headroom for
+    /// further arithmetic, and is representable by [`AtomicInstant`]. Use it as
+    /// an "effectively never" deadline.
+    ///
+    /// [`AtomicInstant`]: crate::AtomicInstant
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::Instant;
+    ///
+    /// let never = Instant::far_future();
+    /// assert!(never > Instant::now());
+    /// ```
+    #[must_use]
+    pub fn far_future() -> Instant {
+        Instant(sys::Instant::from_nanos(FAR_FUTURE_NANOS))
+    }
+
+    /// Returns an instant that is not later than any instant read from the
+    /// clock.
+    ///
+    /// This is the clock epoch itself. Use it as an "effectively forever ago"
+    /// timestamp.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::Instant;
+    ///
+    /// let long_ago = Instant::distant_past();
+    /// assert!(long_ago <= Instant::now());
+    /// ```
+    #[must_use]
+    pub fn distant_past() -> Instant {
+        Instant(sys::Instant::from_nanos(0))
+    }
+
+    /// Returns the resolution of the clock used by [`Instant::now`].
+    ///
+    /// This is `clock_getres` of the clock on UNIX, the duration of one
+    /// `mach_continuous_time` tick on Darwin and one interrupt time or QPC
+    /// tick on Windows, rounded up to whole nanoseconds.
+    /// Instants can't be distinguished if they are closer than this.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::{Duration, Instant};
+    ///
+    /// if Instant::resolution() < Duration::from_millis(1) {
+    ///     println!("sub-millisecond scheduling is possible");
+    /// }
+    /// ```
+    #[must_use]
+    pub fn resolution() -> Duration {
+        sys::Instant::resolution()
+    }
+
     /// Returns the amount of time elapsed from another instant to this one,
     /// or zero duration if that instant is later than this one.
     ///
@@ -174,6 +336,32 @@ impl Instant {
         self.0.checked_sub_instant(&earlier.0)
     }
 
+    /// Returns the signed amount of time elapsed from another instant to this
+    /// one. The result is negative if that instant is later than this one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::{Duration, Instant};
+    ///
+    /// let a = Instant::now();
+    /// let b = a + Duration::from_millis(5);
+    /// assert!(a.signed_duration_since(b).is_negative());
+    /// assert_eq!(a.signed_duration_since(b).abs(), Duration::from_millis(5));
+    /// ```
+    #[must_use]
+    pub fn signed_duration_since(&self, other: Instant) -> SignedDuration {
+        match self.0.checked_sub_instant(&other.0) {
+            Some(d) => SignedDuration::from(d),
+            None => SignedDuration::negative(
+                other
+                    .0
+                    .checked_sub_instant(&self.0)
+                    .expect("instants are ordered"),
+            ),
+        }
+    }
+
     /// Returns the amount of time elapsed from another instant to this one,
     /// or zero duration if that instant is later than this one.
     ///
@@ -220,6 +408,107 @@ impl Instant {
         Instant::now() - *self
     }
 
+    /// Returns the amount of time elapsed from system boot to this instant.
+    ///
+    /// It includes the time the system was suspended only if
+    /// [`clock_source()`](crate::clock_source)`.`[`is_suspend_aware()`](crate::ClockSource::is_suspend_aware)
+    /// returns `true`. Otherwise, e.g. when Linux falls back to
+    /// `CLOCK_MONOTONIC` or with the `force-monotonic` feature, it is the time
+    /// the system was running.
+    ///
+    /// The clock epoch is boot on Linux, Android, OpenBSD, Darwin, Horizon,
+    /// Haiku, Fuchsia and Windows 10+, the start of the microkernel on L4Re
+    /// and the last boot or deep sleep wakeup on ESP-IDF.
+    /// Returns `None` on other platforms or if this instant precedes boot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::Instant;
+    ///
+    /// if let Some(uptime) = Instant::now().duration_since_boot() {
+    ///     println!("[{:12.6}] event", uptime.as_secs_f64());
+    /// }
+    /// ```
+    #[must_use]
+    pub fn duration_since_boot(&self) -> Option<Duration> {
+        self.checked_duration_since(Instant::boot_epoch()?)
+    }
+
+    /// Returns the instant the system booted.
+    ///
+    /// Returns `None` on platforms whose clock epoch isn't boot, see
+    /// [`Instant::duration_since_boot`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::{Duration, Instant};
+    ///
+    /// // a kernel event timestamp expressed as time since boot
+    /// let since_boot = Duration::from_millis(1500);
+    /// if let Some(boot) = Instant::boot_epoch() {
+    ///     let event = boot + since_boot;
+    ///     println!("event happened {:?} ago", event.elapsed());
+    /// }
+    /// ```
+    #[must_use]
+    pub fn boot_epoch() -> Option<Instant> {
+        if sys::epoch_is_boot() {
+            Some(Instant::distant_past())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the amount of time elapsed since this instant and replaces it
+    /// with the current time.
+    ///
+    /// The clock is read once, so no time is lost between measuring and
+    /// resetting.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use boot_time::Instant;
+    ///
+    /// let mut last_tick = Instant::now();
+    /// loop {
+    ///     // do some work
+    ///     let since_last_tick = last_tick.elapsed_and_reset();
+    ///     println!("tick took {since_last_tick:?}");
+    /// }
+    /// ```
+    pub fn elapsed_and_reset(&mut self) -> Duration {
+        let now = Instant::now();
+        let elapsed = now - *self;
+        *self = now;
+        elapsed
+    }
+
+    /// Returns the amount of time elapsed since this instant, or `None` if the
+    /// current time is earlier than this instant.
+    ///
+    /// Unlike [`Instant::elapsed`] this doesn't mask [monotonicity bugs] of
+    /// the platform.
+    ///
+    /// [monotonicity bugs]: Instant#monotonicity
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::Instant;
+    ///
+    /// let instant = Instant::now();
+    /// if instant.checked_elapsed().is_none() {
+    ///     eprintln!("clock went backwards");
+    /// }
+    /// ```
+    #[must_use]
+    pub fn checked_elapsed(&self) -> Option<Duration> {
+        Instant::now().checked_duration_since(*self)
+    }
+
     /// Returns `Some(t)` where `t` is the time `self + duration` if `t` can be represented as
     /// `Instant` (which means it's inside the bounds of the underlying data structure), `None`
     /// otherwise.
@@ -233,6 +522,212 @@ impl Instant {
     pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
         self.0.checked_sub_duration(&duration).map(Instant)
     }
+
+    /// Returns `self + duration`, or the latest instant representable by the
+    /// underlying data structure on overflow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::{Duration, Instant};
+    ///
+    /// let now = Instant::now();
+    /// let deadline = now.saturating_add(Duration::MAX);
+    /// assert!(deadline > now);
+    /// ```
+    #[must_use]
+    pub fn saturating_add(&self, duration: Duration) -> Instant {
+        self.checked_add(duration)
+            .unwrap_or(Instant(sys::Instant::MAX))
+    }
+
+    /// Returns `self - duration`, or the earliest instant representable by the
+    /// underlying data structure on overflow.
+    #[must_use]
+    pub fn saturating_sub(&self, duration: Duration) -> Instant {
+        self.checked_sub(duration)
+            .unwrap_or(Instant(sys::Instant::MIN))
+    }
+
+    /// Truncates the instant down to a multiple of `granularity` since the
+    /// clock epoch.
+    ///
+    /// Returns `None` if `granularity` is zero or the instant precedes the
+    /// clock epoch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::{Duration, Instant};
+    ///
+    /// let tick = Duration::from_millis(10);
+    /// let now = Instant::now();
+    /// let bucket = now.floor(tick).unwrap();
+    /// assert!(bucket <= now && now - bucket < tick);
+    /// ```
+    #[must_use]
+    pub fn floor(&self, granularity: Duration) -> Option<Instant> {
+        self.snap(granularity).map(|(floor, _)| floor)
+    }
+
+    /// Rounds the instant to the nearest multiple of `granularity` since the
+    /// clock epoch. Halfway cases are rounded up.
+    ///
+    /// Returns `None` if `granularity` is zero, the instant precedes the clock
+    /// epoch or the rounded instant cannot be represented.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::{Duration, Instant};
+    ///
+    /// let tick = Duration::from_millis(10);
+    /// let now = Instant::now();
+    /// let nearest = now.round(tick).unwrap();
+    /// assert!(nearest.duration_since(now).max(now.duration_since(nearest)) <= tick / 2);
+    /// ```
+    #[must_use]
+    pub fn round(&self, granularity: Duration) -> Option<Instant> {
+        let (floor, rem) = self.snap(granularity)?;
+        if rem * 2 >= granularity.as_nanos() {
+            floor.checked_add(granularity)
+        } else {
+            Some(floor)
+        }
+    }
+
+    // Returns the instant truncated to `granularity` and the remainder in nanoseconds
+    fn snap(&self, granularity: Duration) -> Option<(Instant, u128)> {
+        let granularity = granularity.as_nanos();
+        if granularity == 0 {
+            return None;
+        }
+        let epoch = Instant::distant_past();
+        let since_epoch = self.checked_duration_since(epoch)?.as_nanos();
+        let rem = since_epoch % granularity;
+        let floor = since_epoch - rem;
+        let floor = Duration::new(
+            (floor / NSEC_PER_SEC).try_into().ok()?,
+            (floor % NSEC_PER_SEC) as u32,
+        );
+        Some((epoch.checked_add(floor)?, rem))
+    }
+}
+
+#[cfg(feature = "raw")]
+impl Instant {
+    /// Returns the number of nanoseconds between the clock epoch and this instant.
+    ///
+    /// The epoch is the zero point of the underlying clock:
+    ///
+    /// | Platform                          | Epoch                                            |
+    /// |-----------------------------------|--------------------------------------------------|
+    /// | UNIX (Linux/Android/openBSD)      | system boot (`CLOCK_BOOTTIME`)                   |
+    /// | L4Re                              | microkernel start (kernel info page clock)       |
+    /// | Darwin                            | system boot (`mach_continuous_time`)             |
+    /// | ESP-IDF                           | system boot or deep sleep wakeup (`esp_timer`)   |
+    /// | Horizon (Nintendo 3DS)            | system boot (`svcGetSystemTick`)                 |
+    /// | Haiku                             | system boot (`system_time`)                      |
+    /// | Fuchsia                           | system boot (boot timeline)                      |
+    /// | Windows 10+                       | system boot (interrupt time)                     |
+    /// | Windows (older)                   | unspecified, usually boot (QPC)                  |
+    /// | UNIX (other)                      | unspecified, usually boot (`CLOCK_MONOTONIC`)    |
+    ///
+    /// The value is only meaningful within the same boot of the same machine.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the instant is before the epoch or more than `u64::MAX`
+    /// nanoseconds after it. Instants returned by [`Instant::now`] are always
+    /// representable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::Instant;
+    ///
+    /// let now = Instant::now();
+    /// let raw = now.into_raw();
+    /// assert!(Instant::from_raw(raw) <= now);
+    /// ```
+    #[must_use]
+    pub fn into_raw(self) -> u64 {
+        self.0
+            .as_nanos()
+            .expect("instant is not representable as nanoseconds since clock epoch")
+    }
+
+    /// Creates an instant from the number of nanoseconds since the clock epoch.
+    ///
+    /// This is the inverse of [`Instant::into_raw`]. On Darwin the value is
+    /// rounded down to the resolution of the mach timebase.
+    #[must_use]
+    pub fn from_raw(nanos: u64) -> Instant {
+        Instant(sys::Instant::from_nanos(nanos))
+    }
+}
+
+#[cfg(all(unix, feature = "libc"))]
+impl Instant {
+    /// Creates an instant from a `timespec` read from the same clock that
+    /// [`Instant::now`] uses, e.g. a `CLOCK_BOOTTIME` timestamp on Linux.
+    ///
+    /// On Darwin the `timespec` is interpreted as a `CLOCK_MONOTONIC` reading,
+    /// which is derived from `mach_continuous_time`.
+    ///
+    /// Returns `None` if `tv_nsec` is out of the `[0, 999_999_999]` range or
+    /// the value cannot be represented by the underlying data structure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::Instant;
+    ///
+    /// let ts = libc::timespec { tv_sec: 10, tv_nsec: 0 };
+    /// let instant = Instant::from_timespec(ts).unwrap();
+    /// assert_eq!(instant.as_timespec().unwrap().tv_sec, 10);
+    /// ```
+    #[must_use]
+    pub fn from_timespec(ts: libc::timespec) -> Option<Instant> {
+        sys::Instant::from_timespec(ts).map(Instant)
+    }
+
+    /// Returns the instant as a `timespec` of the underlying clock, or `None`
+    /// if it does not fit into `timespec` on this target.
+    #[must_use]
+    pub fn as_timespec(&self) -> Option<libc::timespec> {
+        self.0.as_timespec()
+    }
+
+    /// Creates an instant from a reading of a [`ClockHandle`] for the clock
+    /// that [`Instant::now`] uses, e.g. `CLOCK_BOOTTIME` on Linux.
+    ///
+    /// Unlike [`Instant::from_timespec`], the instant remembers its clock in
+    /// debug builds: measuring the time between it and an instant of another
+    /// clock panics there, since the result would be meaningless.
+    ///
+    /// Returns `None` if the value cannot be represented by the underlying
+    /// data structure.
+    ///
+    /// [`ClockHandle`]: crate::ClockHandle
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::{ClockHandle, Instant};
+    ///
+    /// # #[cfg(any(target_os = "linux", target_os = "android"))]
+    /// # {
+    /// let reading = ClockHandle::from_raw(libc::CLOCK_BOOTTIME).now()?;
+    /// let instant = Instant::from_clock_instant(reading).unwrap();
+    /// # let _ = instant;
+    /// # }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[must_use]
+    pub fn from_clock_instant(t: crate::ClockInstant) -> Option<Instant> {
+        sys::Instant::from_clock(t.clock().as_raw(), t.duration_since_epoch()).map(Instant)
+    }
 }
 
 impl Add<Duration> for Instant {
@@ -292,3 +787,40 @@ impl fmt::Debug for Instant {
         self.0.fmt(f)
     }
 }
+
+/// Renders the instant as seconds since boot, e.g. `1234.567890s since boot`.
+///
+/// The precision of the fractional part defaults to microseconds and can be
+/// set with the precision format flag, up to nanoseconds. On platforms where
+/// the clock epoch is not boot (see [`Instant::duration_since_boot`]) the raw
+/// number of nanoseconds since the clock epoch is rendered instead.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::Instant;
+///
+/// let now = Instant::now();
+/// println!("{now}"); // 1234.567890s since boot
+/// println!("{now:.3}"); // 1234.567s since boot
+/// ```
+impl fmt::Display for Instant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.duration_since_boot() {
+            Some(d) => {
+                let precision = f.precision().unwrap_or(6).min(9);
+                let secs = d.as_secs();
+                if precision == 0 {
+                    write!(f, "{secs}s since boot")
+                } else {
+                    let frac = d.subsec_nanos() / 10u32.pow(9 - precision as u32);
+                    write!(f, "{secs}.{frac:0precision$}s since boot")
+                }
+            }
+            None => match self.0.as_nanos() {
+                Some(nanos) => write!(f, "{nanos}ns since clock epoch"),
+                None => fmt::Debug::fmt(self, f),
+            },
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Handles to cancel and re-arm timers
+use core::fmt;
+use std::sync::Arc;
+
+use crate::{Deadline, Instant};
+
+// Implemented by the timers of this crate for their handles
+pub(crate) trait TimerControl: Send + Sync {
+    fn cancel(&self) -> bool;
+    fn reset(&self, deadline: Instant) -> bool;
+    fn is_armed(&self) -> bool;
+}
+
+/// A handle to a timer, e.g. a [`Scheduler`](crate::Scheduler) job, an item of
+/// a [`DelayQueue`](crate::DelayQueue) or a [`WakeAlarm`](crate::WakeAlarm).
+///
+/// Handles are cheap to clone and can be used from any thread. They don't
+/// keep the timer's owner alive: once it is dropped, the handle does nothing.
+///
+/// # Examples
+///
+/// A connection timeout that is usually cancelled before it fires:
+///
+/// ```
+/// use boot_time::{Duration, Scheduler};
+///
+/// let scheduler = Scheduler::new()?;
+/// let timeout = scheduler.schedule_after(Duration::from_secs(30), || {
+///     // close the connection
+/// });
+/// // data arrived, extend the timeout
+/// assert!(timeout.reset(boot_time::Deadline::from_timeout(Duration::from_secs(30))));
+/// // the connection was closed by the peer
+/// assert!(timeout.cancel());
+/// assert!(!timeout.is_armed());
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct TimerHandle(Arc<dyn TimerControl>);
+
+impl TimerHandle {
+    pub(crate) fn new(control: impl TimerControl + 'static) -> TimerHandle {
+        TimerHandle(Arc::new(control))
+    }
+
+    /// Disarms the timer. Returns `false` if it already fired, was cancelled
+    /// or its owner was dropped.
+    pub fn cancel(&self) -> bool {
+        self.0.cancel()
+    }
+
+    /// Re-arms the timer to fire at `deadline` instead. Returns `false` if
+    /// the timer can't fire anymore, because it was consumed when it fired
+    /// or was cancelled, or because its owner was dropped.
+    pub fn reset(&self, deadline: impl Into<Deadline>) -> bool {
+        self.0.reset(deadline.into().instant())
+    }
+
+    /// Returns `true` if the timer is set and hasn't fired or been cancelled.
+    #[must_use]
+    pub fn is_armed(&self) -> bool {
+        self.0.is_armed()
+    }
+}
+
+impl fmt::Debug for TimerHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimerHandle")
+            .field("armed", &self.is_armed())
+            .finish()
+    }
+}
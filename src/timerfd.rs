@@ -0,0 +1,241 @@
+//! Timer file descriptors on Linux and Android
+use core::time::Duration;
+use std::io;
+use std::mem;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd};
+use std::ptr;
+
+use crate::{ClockSource, Deadline, Instant};
+
+const NSEC_PER_SEC: u64 = 1_000_000_000;
+
+/// The clock a [`TimerFd`] measures with.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum TimerClock {
+    /// The clock of [`Instant`], `CLOCK_BOOTTIME` unless it fell back to
+    /// `CLOCK_MONOTONIC`.
+    Boot,
+    /// `CLOCK_MONOTONIC`, which stops while the system is suspended.
+    Monotonic,
+    /// `CLOCK_BOOTTIME_ALARM`, which wakes the system from suspend. It
+    /// requires `CAP_WAKE_ALARM`.
+    BootAlarm,
+}
+
+impl TimerClock {
+    fn id(self) -> libc::clockid_t {
+        match self {
+            TimerClock::Boot => instant_clock_id(),
+            TimerClock::Monotonic => libc::CLOCK_MONOTONIC,
+            TimerClock::BootAlarm => libc::CLOCK_BOOTTIME_ALARM,
+        }
+    }
+
+    // Whether the clock shares the timeline of `Instant`, so deadlines can be
+    // passed as absolute times
+    fn follows_instant(self) -> bool {
+        match self {
+            TimerClock::Boot => true,
+            TimerClock::Monotonic => instant_clock_id() == libc::CLOCK_MONOTONIC,
+            TimerClock::BootAlarm => instant_clock_id() == libc::CLOCK_BOOTTIME,
+        }
+    }
+}
+
+/// A timer file descriptor (`timerfd`), for event loops.
+///
+/// The descriptor becomes readable when the timer expires, so it can be
+/// registered with epoll, mio or io_uring. Reading it returns the number of
+/// expirations, see [`TimerFd::read_expirations`].
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{Duration, Instant, TimerClock, TimerFd};
+///
+/// let timer = TimerFd::new(TimerClock::Boot)?;
+/// timer.set_deadline(Instant::now() + Duration::from_millis(10))?;
+/// assert_eq!(timer.read_expirations()?, 1);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct TimerFd {
+    fd: OwnedFd,
+    clock: TimerClock,
+}
+
+impl TimerFd {
+    /// Creates a disarmed timer on `clock`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the kernel doesn't support the clock, or if the
+    /// process lacks `CAP_WAKE_ALARM` for [`TimerClock::BootAlarm`].
+    pub fn new(clock: TimerClock) -> io::Result<TimerFd> {
+        let fd = unsafe { libc::timerfd_create(clock.id(), libc::TFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(TimerFd {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+            clock,
+        })
+    }
+
+    /// Returns the clock of the timer.
+    pub fn clock(&self) -> TimerClock {
+        self.clock
+    }
+
+    /// Arms the timer to expire at `deadline`, keeping the interval. A
+    /// deadline in the past expires immediately.
+    ///
+    /// On a clock that doesn't follow [`Instant`] the deadline is converted
+    /// to a relative timeout.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the kernel rejects the deadline.
+    pub fn set_deadline(&self, deadline: impl Into<Deadline>) -> io::Result<()> {
+        let deadline = deadline.into().instant();
+        let mut spec = self.get()?;
+        let flags = if self.clock.follows_instant() {
+            spec.it_value = deadline_timespec(deadline);
+            libc::TFD_TIMER_ABSTIME
+        } else {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            spec.it_value = duration_timespec(timeout.max(Duration::from_nanos(1)));
+            0
+        };
+        self.set(flags, &spec)
+    }
+
+    /// Makes the timer expire every `interval` after the next expiration. A
+    /// disarmed timer is armed to expire one interval from now. A zero
+    /// interval makes the timer one-shot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the kernel rejects the interval.
+    pub fn set_interval(&self, interval: Duration) -> io::Result<()> {
+        // The current value is relative to now
+        let mut spec = self.get()?;
+        if spec.it_value.tv_sec == 0 && spec.it_value.tv_nsec == 0 {
+            if interval.is_zero() {
+                return Ok(());
+            }
+            spec.it_value = duration_timespec(interval);
+        }
+        spec.it_interval = duration_timespec(interval);
+        self.set(0, &spec)
+    }
+
+    /// Disarms the timer.
+    ///
+    /// # Errors
+    ///
+    /// Doesn't fail for a valid timer.
+    pub fn disarm(&self) -> io::Result<()> {
+        let spec: libc::itimerspec = unsafe { mem::zeroed() };
+        self.set(0, &spec)
+    }
+
+    /// Returns `true` if the timer has a pending expiration. A timer with an
+    /// interval stays armed after it expires.
+    #[must_use]
+    pub fn is_armed(&self) -> bool {
+        self.get()
+            .is_ok_and(|spec| spec.it_value.tv_sec != 0 || spec.it_value.tv_nsec != 0)
+    }
+
+    /// Blocks until the timer expires and returns the number of expirations
+    /// since the last read, which is more than one if reads fell behind or
+    /// the system was suspended for several intervals. The expirations
+    /// beyond the first are the missed ticks.
+    ///
+    /// A disarmed timer blocks forever.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails.
+    pub fn read_expirations(&self) -> io::Result<u64> {
+        let mut expirations = 0u64;
+        loop {
+            let r = unsafe {
+                libc::read(
+                    self.fd.as_raw_fd(),
+                    (&mut expirations as *mut u64).cast(),
+                    mem::size_of::<u64>(),
+                )
+            };
+            if r >= 0 {
+                return Ok(expirations);
+            }
+            let e = io::Error::last_os_error();
+            if e.kind() != io::ErrorKind::Interrupted {
+                return Err(e);
+            }
+        }
+    }
+
+    fn get(&self) -> io::Result<libc::itimerspec> {
+        let mut spec: libc::itimerspec = unsafe { mem::zeroed() };
+        if unsafe { libc::timerfd_gettime(self.fd.as_raw_fd(), &mut spec) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(spec)
+    }
+
+    fn set(&self, flags: libc::c_int, spec: &libc::itimerspec) -> io::Result<()> {
+        let r = unsafe { libc::timerfd_settime(self.fd.as_raw_fd(), flags, spec, ptr::null_mut()) };
+        if r != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl AsFd for TimerFd {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+impl AsRawFd for TimerFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+// The clock `Instant` reads
+pub(crate) fn instant_clock_id() -> libc::clockid_t {
+    if crate::clock_source() == ClockSource::BootTime {
+        libc::CLOCK_BOOTTIME
+    } else {
+        libc::CLOCK_MONOTONIC
+    }
+}
+
+// A zero value disarms timers, so a due deadline is rounded up to a
+// nanosecond, and one past the representable range is capped
+pub(crate) fn deadline_timespec(deadline: Instant) -> libc::timespec {
+    match deadline.0.as_nanos() {
+        Some(nanos) => timespec(nanos.max(1)),
+        None if deadline < Instant::now() => timespec(1),
+        None => timespec(u64::MAX),
+    }
+}
+
+pub(crate) fn duration_timespec(d: Duration) -> libc::timespec {
+    timespec(u64::try_from(d.as_nanos()).unwrap_or(u64::MAX))
+}
+
+fn timespec(nanos: u64) -> libc::timespec {
+    let mut t: libc::timespec = unsafe { mem::zeroed() };
+    t.tv_sec = (nanos / NSEC_PER_SEC)
+        .try_into()
+        .unwrap_or(libc::time_t::MAX);
+    t.tv_nsec = (nanos % NSEC_PER_SEC) as libc::c_long;
+    t
+}
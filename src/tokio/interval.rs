@@ -0,0 +1,140 @@
+use core::future::{poll_fn, Future};
+use core::pin::Pin;
+use core::task::{ready, Context, Poll};
+use core::time::Duration;
+
+use futures_core::Stream;
+
+use super::{sleep_until, Sleep};
+use crate::{Deadline, Instant, MissedTickBehavior};
+
+/// Returns an [`Interval`] whose first tick completes immediately and the
+/// following ones every `period`.
+///
+/// # Panics
+///
+/// Panics if `period` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::Duration;
+///
+/// # tokio::runtime::Builder::new_current_thread().enable_io().build().unwrap().block_on(async {
+/// let mut interval = boot_time::tokio::interval(Duration::from_millis(10));
+/// let first = interval.tick().await;
+/// let second = interval.tick().await;
+/// assert_eq!(second - first, Duration::from_millis(10));
+/// # });
+/// ```
+#[must_use]
+pub fn interval(period: Duration) -> Interval {
+    Interval::from(crate::interval(period))
+}
+
+/// Returns an [`Interval`] whose first tick completes at `start` and the
+/// following ones every `period`, e.g. aligned to the start of a minute.
+///
+/// # Panics
+///
+/// Panics if `period` is zero.
+#[must_use]
+pub fn interval_at(start: impl Into<Deadline>, period: Duration) -> Interval {
+    Interval::from(crate::interval_at(start.into().instant(), period))
+}
+
+/// Asynchronous ticks at `start + n * period` on the clock of [`Instant`].
+///
+/// It schedules ticks like the blocking [`crate::Interval`], which it can be
+/// created from, and waits for them with a [`Sleep`]. Ticks missed while the
+/// system was suspended are handled by its [`MissedTickBehavior`], so a job
+/// runs once rather than not at all or many times after resume.
+///
+/// It is an endless [`Stream`] of the scheduled tick instants.
+///
+/// # Panics
+///
+/// Polling panics outside a Tokio runtime with the I/O driver enabled, like
+/// [`Sleep`].
+#[derive(Debug)]
+pub struct Interval {
+    inner: crate::Interval,
+    sleep: Sleep,
+}
+
+impl Interval {
+    /// Completes at the next tick and returns its scheduled instant.
+    ///
+    /// The future is cancel safe: dropping it before it completes doesn't
+    /// consume a tick.
+    pub async fn tick(&mut self) -> Instant {
+        poll_fn(|cx| self.poll_tick(cx)).await
+    }
+
+    /// Polls for the next tick, returning its scheduled instant once it is
+    /// due.
+    pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<Instant> {
+        let next = self.inner.next_tick();
+        if self.sleep.deadline() != next {
+            self.sleep.reset(next);
+        }
+        ready!(Pin::new(&mut self.sleep).poll(cx));
+        Poll::Ready(self.inner.advance(Instant::now()))
+    }
+
+    /// Reschedules the next tick a period from now.
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    /// Returns how many further ticks were already due when the last tick
+    /// was returned, see [`crate::Interval::missed_ticks`].
+    #[must_use]
+    pub fn missed_ticks(&self) -> u64 {
+        self.inner.missed_ticks()
+    }
+
+    /// Returns the instant of the next tick.
+    #[must_use]
+    pub fn next_tick(&self) -> Instant {
+        self.inner.next_tick()
+    }
+
+    /// Returns the period of the interval.
+    #[must_use]
+    pub fn period(&self) -> Duration {
+        self.inner.period()
+    }
+
+    /// Returns how missed ticks are caught up with.
+    #[must_use]
+    pub fn missed_tick_behavior(&self) -> MissedTickBehavior {
+        self.inner.missed_tick_behavior()
+    }
+
+    /// Sets how missed ticks are caught up with.
+    pub fn set_missed_tick_behavior(&mut self, behavior: MissedTickBehavior) {
+        self.inner.set_missed_tick_behavior(behavior);
+    }
+}
+
+impl From<crate::Interval> for Interval {
+    fn from(inner: crate::Interval) -> Interval {
+        Interval {
+            sleep: sleep_until(inner.next_tick()),
+            inner,
+        }
+    }
+}
+
+impl Stream for Interval {
+    type Item = Instant;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Instant>> {
+        self.get_mut().poll_tick(cx).map(Some)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
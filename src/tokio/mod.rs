@@ -0,0 +1,15 @@
+//! Suspend-aware timers for the Tokio runtime
+//!
+//! Tokio's timers measure with `CLOCK_MONOTONIC`, which stops while the
+//! system is suspended, so `tokio::time::sleep(Duration::from_secs(60))`
+//! stretches by the length of a suspend. The timers of this module measure
+//! with the clock of [`Instant`](crate::Instant) instead. They are driven by
+//! a [`TimerFd`](crate::TimerFd) registered with the runtime's I/O driver,
+//! which has to be enabled, e.g. with `Builder::enable_io`.
+mod interval;
+mod sleep;
+mod timeout;
+
+pub use self::interval::{interval, interval_at, Interval};
+pub use self::sleep::{sleep, sleep_until, Sleep};
+pub use self::timeout::{timeout, timeout_at, Elapsed, Timeout};
@@ -0,0 +1,135 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{ready, Context, Poll};
+use core::time::Duration;
+use std::io;
+
+use ::tokio::io::unix::AsyncFd;
+
+use crate::{Deadline, Instant, TimerClock, TimerFd};
+
+/// Waits until `duration` has elapsed on the boot clock.
+///
+/// # Panics
+///
+/// Polling the future panics outside a Tokio runtime with the I/O driver
+/// enabled, or if the timer can't be created, e.g. when the process ran out
+/// of file descriptors.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::Duration;
+///
+/// # tokio::runtime::Builder::new_current_thread().enable_io().build().unwrap().block_on(async {
+/// boot_time::tokio::sleep(Duration::from_millis(10)).await;
+/// # });
+/// ```
+pub fn sleep(duration: Duration) -> Sleep {
+    sleep_until(Deadline::from_timeout(duration))
+}
+
+/// Waits until `deadline` on the boot clock.
+///
+/// # Panics
+///
+/// Polling the future panics outside a Tokio runtime with the I/O driver
+/// enabled, or if the timer can't be created.
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{Duration, Instant};
+///
+/// # tokio::runtime::Builder::new_current_thread().enable_io().build().unwrap().block_on(async {
+/// let deadline = Instant::now() + Duration::from_millis(10);
+/// boot_time::tokio::sleep_until(deadline).await;
+/// assert!(Instant::now() >= deadline);
+/// # });
+/// ```
+pub fn sleep_until(deadline: impl Into<Deadline>) -> Sleep {
+    Sleep {
+        deadline: deadline.into().instant(),
+        timer: None,
+    }
+}
+
+/// A future returned by [`sleep`] and [`sleep_until`].
+///
+/// The timer is created and registered with the runtime on the first poll,
+/// so a sleep whose deadline has already passed completes without one.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Sleep {
+    deadline: Instant,
+    timer: Option<AsyncFd<TimerFd>>,
+}
+
+impl Sleep {
+    /// Returns the instant the sleep completes at.
+    #[must_use]
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Returns `true` if the deadline has passed.
+    #[must_use]
+    pub fn is_elapsed(&self) -> bool {
+        Instant::now() >= self.deadline
+    }
+
+    /// Moves the deadline to `deadline`, which may be earlier or later, even
+    /// if the sleep has completed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the kernel rejects the deadline.
+    pub fn reset(&mut self, deadline: impl Into<Deadline>) {
+        self.deadline = deadline.into().instant();
+        if let Some(timer) = &self.timer {
+            if let Err(e) = timer.get_ref().set_deadline(self.deadline) {
+                panic!("failed to arm the boot-time timer: {e}");
+            }
+        }
+    }
+
+    fn register(&mut self) -> io::Result<&AsyncFd<TimerFd>> {
+        let timer = match self.timer.take() {
+            Some(timer) => timer,
+            None => {
+                let timer = TimerFd::new(TimerClock::Boot)?;
+                timer.set_deadline(self.deadline)?;
+                AsyncFd::new(timer)?
+            }
+        };
+        Ok(self.timer.insert(timer))
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    // The expirations aren't read: re-arming the timer resets them, and the
+    // deadline is checked against the clock after each wakeup
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        let deadline = this.deadline;
+        loop {
+            if Instant::now() >= deadline {
+                return Poll::Ready(());
+            }
+            let timer = match this.register() {
+                Ok(timer) => timer,
+                Err(e) => panic!("failed to create the boot-time timer: {e}"),
+            };
+            match ready!(timer.poll_read_ready(cx)) {
+                Ok(mut guard) => {
+                    if Instant::now() < deadline {
+                        guard.clear_ready();
+                    }
+                }
+                Err(e) => panic!("failed to wait for the boot-time timer: {e}"),
+            }
+        }
+    }
+}
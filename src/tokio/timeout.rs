@@ -0,0 +1,124 @@
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use core::time::Duration;
+use std::error::Error;
+use std::io;
+
+use super::{sleep_until, Sleep};
+use crate::{Deadline, Instant};
+
+/// Requires `future` to complete within `duration` of the boot clock.
+///
+/// It's [`timeout_at`] with a deadline `duration` from now.
+///
+/// # Panics
+///
+/// Polling the future panics outside a Tokio runtime with the I/O driver
+/// enabled, like [`sleep`](super::sleep).
+pub fn timeout<F: Future>(duration: Duration, future: F) -> Timeout<F> {
+    timeout_at(Deadline::from_timeout(duration), future)
+}
+
+/// Requires `future` to complete by `deadline` on the boot clock.
+///
+/// The future yields `Ok` with the output of `future` if it completes
+/// first, and [`Elapsed`] if the deadline passes first, in which case
+/// `future` is dropped with the `Timeout`. A future that is ready on the
+/// first poll completes even if the deadline has passed.
+///
+/// Unlike `tokio::time::timeout_at`, the deadline counts the time the system
+/// is suspended, so a request started before a suspend times out right after
+/// resume.
+///
+/// # Panics
+///
+/// Polling the future panics outside a Tokio runtime with the I/O driver
+/// enabled, like [`sleep`](super::sleep).
+///
+/// # Examples
+///
+/// ```
+/// use boot_time::{Duration, Instant};
+/// use boot_time::tokio::{sleep, timeout_at};
+///
+/// # tokio::runtime::Builder::new_current_thread().enable_io().build().unwrap().block_on(async {
+/// let deadline = Instant::now() + Duration::from_millis(10);
+/// assert_eq!(timeout_at(deadline, async { 1 }).await, Ok(1));
+/// assert!(timeout_at(deadline, sleep(Duration::from_secs(60))).await.is_err());
+/// # });
+/// ```
+pub fn timeout_at<F: Future>(deadline: impl Into<Deadline>, future: F) -> Timeout<F> {
+    Timeout {
+        future,
+        sleep: sleep_until(deadline),
+    }
+}
+
+/// A future returned by [`timeout`] and [`timeout_at`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Timeout<F> {
+    future: F,
+    sleep: Sleep,
+}
+
+impl<F> Timeout<F> {
+    /// Returns the deadline of the timeout.
+    #[must_use]
+    pub fn deadline(&self) -> Instant {
+        self.sleep.deadline()
+    }
+
+    /// Returns a reference to the wrapped future.
+    pub fn get_ref(&self) -> &F {
+        &self.future
+    }
+
+    /// Returns a mutable reference to the wrapped future.
+    pub fn get_mut(&mut self) -> &mut F {
+        &mut self.future
+    }
+
+    /// Returns the wrapped future.
+    pub fn into_inner(self) -> F {
+        self.future
+    }
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The future is pinned with the `Timeout` and never moved out of a
+        // pinned one, while `Sleep` is `Unpin`
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        if let Poll::Ready(output) = future.poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        Pin::new(&mut this.sleep)
+            .poll(cx)
+            .map(|()| Err(Elapsed(())))
+    }
+}
+
+/// The error of a [`Timeout`] whose deadline passed before the future
+/// completed.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Elapsed(());
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("deadline has elapsed")
+    }
+}
+
+impl Error for Elapsed {}
+
+impl From<Elapsed> for io::Error {
+    fn from(e: Elapsed) -> io::Error {
+        io::Error::new(io::ErrorKind::TimedOut, e)
+    }
+}